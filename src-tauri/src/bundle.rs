@@ -0,0 +1,278 @@
+//! Export of every worktree branch in a project as a single archive.
+//!
+//! The bundle is an uncompressed POSIX (ustar) tarball holding one
+//! `patches/NN-{branch}.patch` per worktree with changes, followed by a
+//! `manifest.json` describing each worktree's branch and base commit.
+//! Patches are staged in a temp file so they're never held in memory.
+
+use crate::git;
+use crate::state::Project;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+/// One worktree's entry in the bundle manifest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleWorktree {
+    pub name: String,
+    pub branch: String,
+    /// Commit the branch diverged from the target branch at
+    pub base_commit: Option<String>,
+    pub head_commit: Option<String>,
+    /// Archive path of the patch; absent when the branch has no changes
+    pub patch: Option<String>,
+    /// Why no patch could be produced, if exporting this worktree failed
+    pub error: Option<String>,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub project: String,
+    pub target_branch: String,
+    pub created_at: String,
+    pub worktrees: Vec<BundleWorktree>,
+}
+
+/// Progress of a running export, sent after each worktree is written
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleProgress {
+    pub current: usize,
+    pub total: usize,
+    pub worktree: String,
+}
+
+/// Write a bundle of `project`'s worktree patches against `target_branch` to `out_path`.
+/// A worktree that can't be exported is recorded in the manifest with its error rather
+/// than failing the whole bundle. The output file is removed if the export fails.
+pub fn export_project_bundle(
+    project: &Project,
+    target_branch: &str,
+    out_path: &Path,
+    on_progress: &mut dyn FnMut(BundleProgress),
+) -> Result<BundleManifest, String> {
+    let file = File::create(out_path).map_err(|e| format!("Failed to create {}: {}", out_path.display(), e))?;
+    let result = write_bundle(project, target_branch, BufWriter::new(file), on_progress);
+    if result.is_err() {
+        let _ = std::fs::remove_file(out_path);
+    }
+    result
+}
+
+fn write_bundle(
+    project: &Project,
+    target_branch: &str,
+    out: BufWriter<File>,
+    on_progress: &mut dyn FnMut(BundleProgress),
+) -> Result<BundleManifest, String> {
+    let mut tar = tar::Builder::new(out);
+    let mtime = unix_now();
+    let total = project.worktrees.len();
+    let mut entries = Vec::with_capacity(total);
+
+    for (index, worktree) in project.worktrees.iter().enumerate() {
+        let path = Path::new(&worktree.path);
+        let mut entry = BundleWorktree {
+            name: worktree.name.clone(),
+            branch: worktree.branch.clone(),
+            base_commit: None,
+            head_commit: None,
+            patch: None,
+            error: None,
+        };
+
+        match stage_patch(path, target_branch) {
+            Ok(staged) => {
+                entry.base_commit = Some(staged.base_commit);
+                entry.head_commit = Some(staged.head_commit);
+                if staged.size > 0 {
+                    let name = patch_entry_name(index, &worktree.branch);
+                    let patch = File::open(&staged.path).map_err(|e| e.to_string())?;
+                    let appended =
+                        tar.append_data(&mut file_header(staged.size, mtime), &name, patch.take(staged.size));
+                    let _ = std::fs::remove_file(&staged.path);
+                    appended.map_err(|e| format!("Failed to write {}: {}", name, e))?;
+                    entry.patch = Some(name);
+                } else {
+                    let _ = std::fs::remove_file(&staged.path);
+                }
+            }
+            Err(e) => {
+                log::warn!("[bundle] Skipping worktree {}: {}", worktree.name, e);
+                entry.error = Some(e);
+            }
+        }
+
+        entries.push(entry);
+        on_progress(BundleProgress {
+            current: index + 1,
+            total,
+            worktree: worktree.name.clone(),
+        });
+    }
+
+    let manifest = BundleManifest {
+        project: project.name.clone(),
+        target_branch: target_branch.to_string(),
+        created_at: crate::worktree::chrono_lite_now(),
+        worktrees: entries,
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    tar.append_data(&mut file_header(json.len() as u64, mtime), "manifest.json", json.as_slice())
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+    let mut out = tar.into_inner().map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+/// A worktree's patch written out to a temp file
+struct StagedPatch {
+    path: std::path::PathBuf,
+    size: u64,
+    base_commit: String,
+    head_commit: String,
+}
+
+fn stage_patch(worktree_path: &Path, target_branch: &str) -> Result<StagedPatch, String> {
+    if !worktree_path.exists() {
+        return Err(format!("Worktree path does not exist: {}", worktree_path.display()));
+    }
+    let base_commit = git::merge_base_commit(worktree_path, target_branch).map_err(|e| e.to_string())?;
+    let head_commit = git::head_commit(worktree_path).map_err(|e| e.to_string())?;
+
+    let path = std::env::temp_dir().join(format!("shellflow-bundle-{}.patch", uuid::Uuid::new_v4()));
+    let mut file = BufWriter::new(File::create(&path).map_err(|e| e.to_string())?);
+    let written = git::write_worktree_patch(worktree_path, target_branch, &mut file)
+        .map_err(|e| e.to_string())
+        .and_then(|size| file.flush().map(|_| size).map_err(|e| e.to_string()));
+
+    match written {
+        Ok(size) => Ok(StagedPatch { path, size, base_commit, head_commit }),
+        Err(e) => {
+            drop(file);
+            let _ = std::fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+/// Archive path for a worktree's patch. The index keeps names unique when
+/// sanitizing makes two branches collide.
+fn patch_entry_name(index: usize, branch: &str) -> String {
+    let sanitized: String = branch
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '-' })
+        .collect();
+    let mut name = format!("patches/{:02}-{}", index + 1, sanitized);
+    // Fit a plain ustar name field (100 bytes) so no extension headers are needed;
+    // the name is ASCII here
+    name.truncate(100 - ".patch".len());
+    name.push_str(".patch");
+    name
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Header for a regular file in the bundle; the path and checksum are filled in on append
+fn file_header(size: u64, mtime: u64) -> tar::Header {
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{git_cmd, init_test_repo_at, worktree};
+    use std::path::PathBuf;
+
+    /// Read an archive into (name, contents) pairs
+    fn read_entries(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = tar::Archive::new(archive);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut contents = Vec::new();
+                entry.read_to_end(&mut contents).unwrap();
+                (name, contents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn patch_entry_names_are_sanitized_and_bounded() {
+        assert_eq!(patch_entry_name(0, "feature/login"), "patches/01-feature-login.patch");
+        let long = patch_entry_name(11, &"x".repeat(200));
+        assert_eq!(long.len(), 100);
+        assert!(long.starts_with("patches/12-xxx") && long.ends_with(".patch"));
+    }
+
+    #[test]
+    fn bundles_changed_worktrees_and_skips_empty_patches() {
+        let root = std::env::temp_dir().join(format!("shellflow-bundle-test-{}", uuid::Uuid::new_v4()));
+        let repo = root.join("repo");
+        init_test_repo_at(&repo);
+
+        let changed: PathBuf = root.join("changed");
+        let unchanged: PathBuf = root.join("unchanged");
+        git_cmd(&repo, &["worktree", "add", "-b", "feature/a", changed.to_str().unwrap()]);
+        git_cmd(&repo, &["worktree", "add", "-b", "feature/b", unchanged.to_str().unwrap()]);
+        std::fs::write(changed.join("README.md"), "hello\nfrom a\n").unwrap();
+        git_cmd(&changed, &["commit", "-am", "change a"]);
+
+        let project = Project {
+            id: "p".to_string(),
+            name: "repo".to_string(),
+            path: repo.to_string_lossy().to_string(),
+            worktrees: vec![
+                worktree("a", "feature/a", &changed),
+                worktree("b", "feature/b", &unchanged),
+                worktree("gone", "feature/gone", &root.join("gone")),
+            ],
+            order: 0,
+            is_active: true,
+            last_accessed_at: None,
+            repo_root: None,
+        };
+
+        let out = root.join("bundle.tar");
+        let mut progress = Vec::new();
+        let manifest = export_project_bundle(&project, "main", &out, &mut |p| progress.push(p.current)).unwrap();
+        assert_eq!(progress, vec![1, 2, 3]);
+
+        let entries = read_entries(&std::fs::read(&out).unwrap());
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["patches/01-feature-a.patch", "manifest.json"]);
+        assert!(String::from_utf8_lossy(&entries[0].1).contains("+from a"));
+
+        let a = &manifest.worktrees[0];
+        assert_eq!(a.patch.as_deref(), Some("patches/01-feature-a.patch"));
+        assert_ne!(a.base_commit, a.head_commit);
+        let b = &manifest.worktrees[1];
+        assert!(b.patch.is_none() && b.error.is_none());
+        assert_eq!(b.base_commit, b.head_commit);
+        assert!(manifest.worktrees[2].error.is_some());
+
+        let json: serde_json::Value = serde_json::from_slice(&entries[1].1).unwrap();
+        assert_eq!(json["targetBranch"], "main");
+        assert_eq!(json["worktrees"][0]["branch"], "feature/a");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -9,13 +9,19 @@
 //!
 //! Each app instance gets its own PID file at `~/.shellflow/pids/{app_pid}.json`.
 //! This allows multiple instances to run simultaneously without interfering.
+//!
+//! Sessions marked detached (see `pty::set_session_detached`) are skipped by all
+//! crash-recovery paths. Detached processes keep running after a crash and are
+//! the user's responsibility to stop.
 
+use crate::config::CleanupConfig;
 use crate::pty;
 use crate::state::AppState;
 use log::{error, info, warn};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -24,6 +30,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct PidFile {
     /// PIDs of spawned PTY processes
     pub pty_pids: Vec<u32>,
+    /// Subset of `pty_pids` the user detached; never killed by crash recovery
+    #[serde(default)]
+    pub detached_pids: Vec<u32>,
     /// Unix timestamp when the file was last updated
     pub timestamp: u64,
 }
@@ -31,6 +40,8 @@ pub struct PidFile {
 lazy_static::lazy_static! {
     /// Lock for atomic PID file operations
     static ref PID_FILE_LOCK: Mutex<()> = Mutex::new(());
+    /// Open handle on the instance lock file, held until the process exits
+    static ref INSTANCE_LOCK: Mutex<Option<std::fs::File>> = Mutex::new(None);
 }
 
 /// Get the pids directory path
@@ -46,6 +57,87 @@ fn get_pid_file_path() -> PathBuf {
     get_pids_dir().join(format!("{}.json", std::process::id()))
 }
 
+/// Get the path of the lock file held by the single running instance
+fn get_instance_lock_path() -> PathBuf {
+    get_pids_dir().join("instance.lock")
+}
+
+/// Open `path` and take an exclusive, non-blocking lock on it.
+/// Returns `Ok(None)` when another process (or another handle) already holds it.
+/// The lock lasts as long as the returned file stays open, so it's released
+/// automatically if the holder crashes.
+#[cfg(unix)]
+fn try_lock_file(path: &std::path::Path) -> std::io::Result<Option<std::fs::File>> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(Some(file));
+    }
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(None)
+    } else {
+        Err(err)
+    }
+}
+
+/// Windows: an exclusive (no sharing) open stands in for a lock; it fails with
+/// a sharing violation while another handle is open.
+#[cfg(windows)]
+fn try_lock_file(path: &std::path::Path) -> std::io::Result<Option<std::fs::File>> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    match std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .share_mode(0)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.raw_os_error() == Some(ERROR_SHARING_VIOLATION) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn try_lock_file(_path: &std::path::Path) -> std::io::Result<Option<std::fs::File>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "file locking not supported"))
+}
+
+/// Try to become the single running instance by locking `instance.lock` in the
+/// pids directory. Returns false only if another live instance holds the lock;
+/// if locking itself fails, startup goes ahead rather than being blocked.
+pub fn acquire_instance_lock() -> bool {
+    let path = get_instance_lock_path();
+    if let Err(e) = std::fs::create_dir_all(get_pids_dir()) {
+        warn!("[Cleanup] Failed to create pids directory: {}", e);
+        return true;
+    }
+
+    match try_lock_file(&path) {
+        Ok(Some(mut file)) => {
+            use std::io::Write;
+            // Record the holder for diagnostics; the lock itself is what matters
+            let _ = file.set_len(0);
+            let _ = write!(file, "{}", std::process::id());
+            *INSTANCE_LOCK.lock() = Some(file);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            warn!("[Cleanup] Failed to lock {:?}, not enforcing a single instance: {}", path, e);
+            true
+        }
+    }
+}
+
 /// Get current Unix timestamp
 fn current_timestamp() -> u64 {
     SystemTime::now()
@@ -67,11 +159,14 @@ fn load_own_pid_file() -> Option<PidFile> {
 
 /// Save the PID file to disk atomically (write to temp, then rename)
 fn save_pid_file(pid_file: &PidFile) -> std::io::Result<()> {
-    let path = get_pid_file_path();
+    save_pid_file_to(&get_pid_file_path(), pid_file)
+}
 
+fn save_pid_file_to(path: &Path, pid_file: &PidFile) -> std::io::Result<()> {
     // Ensure pids directory exists
-    let pids_dir = get_pids_dir();
-    std::fs::create_dir_all(&pids_dir)?;
+    if let Some(pids_dir) = path.parent() {
+        std::fs::create_dir_all(pids_dir)?;
+    }
 
     // Write to temp file first
     let temp_path = path.with_extension("json.tmp");
@@ -80,7 +175,7 @@ fn save_pid_file(pid_file: &PidFile) -> std::io::Result<()> {
     std::fs::write(&temp_path, content)?;
 
     // Atomic rename
-    std::fs::rename(&temp_path, &path)?;
+    std::fs::rename(&temp_path, path)?;
 
     Ok(())
 }
@@ -97,6 +192,7 @@ pub fn init_pid_file() {
 
     let pid_file = PidFile {
         pty_pids: Vec::new(),
+        detached_pids: Vec::new(),
         timestamp: current_timestamp(),
     };
 
@@ -116,6 +212,7 @@ pub fn add_pid(pid: u32) {
 
     let mut pid_file = load_own_pid_file().unwrap_or(PidFile {
         pty_pids: Vec::new(),
+        detached_pids: Vec::new(),
         timestamp: 0,
     });
 
@@ -135,6 +232,7 @@ pub fn remove_pid(pid: u32) {
 
     if let Some(mut pid_file) = load_own_pid_file() {
         pid_file.pty_pids.retain(|&p| p != pid);
+        pid_file.detached_pids.retain(|&p| p != pid);
         pid_file.timestamp = current_timestamp();
 
         if let Err(e) = save_pid_file(&pid_file) {
@@ -143,6 +241,107 @@ pub fn remove_pid(pid: u32) {
     }
 }
 
+/// Mark or unmark a tracked PID as detached (excluded from crash recovery)
+pub fn set_pid_detached(pid: u32, detached: bool) {
+    let _lock = PID_FILE_LOCK.lock();
+
+    let Some(mut pid_file) = load_own_pid_file() else {
+        return;
+    };
+    pid_file.detached_pids.retain(|&p| p != pid);
+    if detached {
+        pid_file.detached_pids.push(pid);
+    }
+    pid_file.timestamp = current_timestamp();
+
+    if let Err(e) = save_pid_file(&pid_file) {
+        warn!("[Cleanup] Failed to update detached state of PID {}: {}", pid, e);
+    }
+}
+
+impl PidFile {
+    /// PIDs that crash recovery should kill (everything not detached)
+    fn pids_to_kill(&self) -> Vec<u32> {
+        self.pty_pids
+            .iter()
+            .copied()
+            .filter(|pid| !self.detached_pids.contains(pid))
+            .collect()
+    }
+}
+
+/// Number of PIDs in this instance's PID file
+pub fn tracked_pid_count() -> usize {
+    let _lock = PID_FILE_LOCK.lock();
+    load_own_pid_file().map(|f| f.pty_pids.len()).unwrap_or(0)
+}
+
+/// Result of `verify_pid_file`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum PidFileHealth {
+    /// The file loaded and was left as is
+    Healthy { tracked: usize },
+    /// The file was missing or didn't parse, and was rebuilt from the live sessions
+    Rebuilt { tracked: usize, reason: String },
+    /// The file was missing or didn't parse, and couldn't be rewritten
+    Failed { error: String },
+}
+
+/// Check that this instance's PID file loads. A corrupt or missing file would
+/// silently disable crash recovery, so it's rebuilt from the live PTY sessions
+/// (the in-memory source of truth), keeping their detached flags.
+pub fn verify_pid_file(state: &AppState) -> PidFileHealth {
+    let sessions: Vec<(u32, bool)> = state
+        .pty_sessions
+        .read()
+        .values()
+        .map(|session| (session.child_pid, session.detached.load(std::sync::atomic::Ordering::SeqCst)))
+        .collect();
+
+    let _lock = PID_FILE_LOCK.lock();
+    verify_pid_file_at(&get_pid_file_path(), &sessions)
+}
+
+/// `verify_pid_file` against an explicit path and `(pid, detached)` session list
+fn verify_pid_file_at(path: &Path, sessions: &[(u32, bool)]) -> PidFileHealth {
+    let reason = match std::fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<PidFile>(&content) {
+            Ok(pid_file) => return PidFileHealth::Healthy { tracked: pid_file.pty_pids.len() },
+            Err(e) => format!("corrupt: {}", e),
+        },
+        Err(e) => format!("unreadable: {}", e),
+    };
+
+    let mut pty_pids: Vec<u32> = sessions.iter().map(|(pid, _)| *pid).collect();
+    pty_pids.sort_unstable();
+    pty_pids.dedup();
+    let mut detached_pids: Vec<u32> = sessions.iter().filter(|(_, detached)| *detached).map(|(pid, _)| *pid).collect();
+    detached_pids.sort_unstable();
+    detached_pids.dedup();
+    let pid_file = PidFile {
+        pty_pids,
+        detached_pids,
+        timestamp: current_timestamp(),
+    };
+
+    match save_pid_file_to(path, &pid_file) {
+        Ok(()) => {
+            warn!(
+                "[Cleanup] Rebuilt PID file {:?} from {} live session(s) ({})",
+                path,
+                pid_file.pty_pids.len(),
+                reason
+            );
+            PidFileHealth::Rebuilt { tracked: pid_file.pty_pids.len(), reason }
+        }
+        Err(e) => {
+            error!("[Cleanup] PID file {:?} is {} and couldn't be rebuilt: {}", path, reason, e);
+            PidFileHealth::Failed { error: e.to_string() }
+        }
+    }
+}
+
 /// Delete this instance's PID file (called on clean shutdown)
 pub fn delete_pid_file() {
     let _lock = PID_FILE_LOCK.lock();
@@ -164,18 +363,70 @@ fn app_pid_from_path(path: &PathBuf) -> Option<u32> {
         .and_then(|s| s.parse().ok())
 }
 
-/// Clean up orphaned processes from previous crashes
-///
-/// Called early in app startup, before any PTYs are spawned.
-/// Scans all PID files and cleans up any where the app is dead.
+/// Environment variable that skips the startup orphan sweep (any value but "" or "0")
+const NO_CLEANUP_ENV: &str = "SHELLFLOW_NO_CLEANUP";
+
+/// Whether to run `cleanup_orphans` at startup: `cleanup.onStartup`, unless
+/// SHELLFLOW_NO_CLEANUP is set. Skipping it means processes from instances that
+/// really crashed are left running. Signal-handler, panic-hook and watchdog
+/// cleanup of this instance's own processes are unaffected.
+pub fn startup_cleanup_enabled(config: &CleanupConfig) -> bool {
+    startup_cleanup_enabled_with(config, std::env::var_os(NO_CLEANUP_ENV))
+}
+
+fn startup_cleanup_enabled_with(config: &CleanupConfig, no_cleanup: Option<std::ffi::OsString>) -> bool {
+    match no_cleanup {
+        Some(value) if !value.is_empty() && value != "0" => false,
+        _ => config.on_startup,
+    }
+}
+
+/// A process left running by an instance that crashed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanInfo {
+    pub pid: u32,
+    /// PID of the crashed instance that spawned it
+    pub app_pid: u32,
+    pub name: Option<String>,
+    /// When it started, to recognise it again before killing it
+    #[serde(skip)]
+    pub started: Option<String>,
+}
+
+impl OrphanInfo {
+    /// Whether the PID still belongs to the process that was found. While the
+    /// user is deciding, the orphan may exit and its PID go to something else.
+    #[cfg(unix)]
+    fn is_same_process(&self) -> bool {
+        pty::is_process_alive(self.pid)
+            && pty::get_process_start(self.pid) == self.started
+            && pty::get_process_name(self.pid) == self.name
+    }
+}
+
+/// How long `cleanup_orphans_with_confirmation` waits for an answer before
+/// killing every orphan anyway
 #[cfg(unix)]
-pub fn cleanup_orphans() {
-    let _lock = PID_FILE_LOCK.lock();
+const ORPHAN_CONFIRM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    /// Orphans awaiting `confirm_orphan_cleanup`, and where to send the answer
+    static ref PENDING_ORPHANS: Mutex<Option<(Vec<OrphanInfo>, Sender<Vec<u32>>)>> = Mutex::new(None);
+}
+
+/// Find processes left by crashed instances, returning them with the PID files
+/// they came from. PID files of dead instances with nothing left to kill (or
+/// that don't parse) are deleted on the way. Caller holds PID_FILE_LOCK.
+#[cfg(unix)]
+fn find_orphans() -> (Vec<OrphanInfo>, Vec<PathBuf>) {
+    let mut orphans = Vec::new();
+    let mut stale_files = Vec::new();
 
     let pids_dir = get_pids_dir();
     let entries = match std::fs::read_dir(&pids_dir) {
         Ok(e) => e,
-        Err(_) => return, // Directory doesn't exist, nothing to clean up
+        Err(_) => return (orphans, stale_files), // Directory doesn't exist, nothing to clean up
     };
 
     let current_pid = std::process::id();
@@ -207,14 +458,23 @@ pub fn cleanup_orphans() {
             continue;
         }
 
-        // The old app is dead - load and clean up its orphaned processes
+        // The old app is dead - load its orphaned processes
         let Some(pid_file) = load_pid_file(&path) else {
             // Can't parse file, just delete it
             let _ = std::fs::remove_file(&path);
             continue;
         };
 
-        if pid_file.pty_pids.is_empty() {
+        let pids = pid_file.pids_to_kill();
+        if !pid_file.detached_pids.is_empty() {
+            info!(
+                "[Cleanup] Leaving {} detached PIDs from instance {} running",
+                pid_file.detached_pids.len(),
+                app_pid
+            );
+        }
+
+        if pids.is_empty() {
             info!("[Cleanup] No orphaned PIDs from instance {}", app_pid);
             let _ = std::fs::remove_file(&path);
             continue;
@@ -222,29 +482,142 @@ pub fn cleanup_orphans() {
 
         info!(
             "[Cleanup] Found {} potentially orphaned PIDs from crashed instance {}",
-            pid_file.pty_pids.len(),
+            pids.len(),
             app_pid
         );
 
-        for pid in &pid_file.pty_pids {
-            if pty::is_process_alive(*pid) {
-                // Kill children first
-                let children = pty::get_child_pids(*pid);
-                for child_pid in children {
-                    if pty::is_process_alive(child_pid) {
-                        pty::send_signal(child_pid, libc::SIGKILL);
-                        info!("[Cleanup] Killed orphaned child process {}", child_pid);
-                    }
-                }
-                // Then kill the parent
-                pty::send_signal(*pid, libc::SIGKILL);
-                info!("[Cleanup] Killed orphaned process {}", pid);
+        orphans.extend(pids.into_iter().filter(|pid| pty::is_process_alive(*pid)).map(|pid| OrphanInfo {
+            pid,
+            app_pid,
+            name: pty::get_process_name(pid),
+            started: pty::get_process_start(pid),
+        }));
+        stale_files.push(path);
+    }
+
+    (orphans, stale_files)
+}
+
+/// Kill an orphaned process and its children
+#[cfg(unix)]
+fn kill_orphan(pid: u32) {
+    if pty::is_process_alive(pid) {
+        // Kill children first
+        let children = pty::get_child_pids(pid);
+        for child_pid in children {
+            if pty::is_process_alive(child_pid) {
+                pty::send_signal(child_pid, libc::SIGKILL);
+                info!("[Cleanup] Killed orphaned child process {}", child_pid);
             }
         }
+        // Then kill the parent
+        pty::send_signal(pid, libc::SIGKILL);
+        info!("[Cleanup] Killed orphaned process {}", pid);
+    }
+}
 
-        // Remove the stale PID file
-        let _ = std::fs::remove_file(&path);
-        info!("[Cleanup] Cleaned up PID file for instance {}", app_pid);
+/// Remove the PID files of crashed instances once their orphans are dealt with
+#[cfg(unix)]
+fn remove_stale_pid_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+        info!("[Cleanup] Cleaned up PID file {:?}", path);
+    }
+}
+
+/// Clean up orphaned processes from previous crashes
+///
+/// Called early in app startup, before any PTYs are spawned.
+/// Scans all PID files and cleans up any where the app is dead.
+#[cfg(unix)]
+pub fn cleanup_orphans() {
+    let _lock = PID_FILE_LOCK.lock();
+
+    let (orphans, stale_files) = find_orphans();
+    for orphan in &orphans {
+        kill_orphan(orphan.pid);
+    }
+    remove_stale_pid_files(&stale_files);
+}
+
+/// Like `cleanup_orphans`, but lets the user choose which orphans to kill
+/// (`cleanup.confirm`). `ask` is called with the candidates (the app emits
+/// `orphans-found`); the answer comes from `confirm_orphan_cleanup`. Without an
+/// answer within a minute every orphan is killed, as `cleanup_orphans` would.
+/// Orphans the user keeps are no longer tracked. Blocks while waiting, so run
+/// it off the main thread.
+#[cfg(unix)]
+pub fn cleanup_orphans_with_confirmation(ask: impl FnOnce(&[OrphanInfo])) {
+    let (orphans, stale_files) = {
+        let _lock = PID_FILE_LOCK.lock();
+        find_orphans()
+    };
+    if !orphans.is_empty() {
+        for orphan in confirm_orphans(orphans, ORPHAN_CONFIRM_TIMEOUT, ask) {
+            if orphan.is_same_process() {
+                kill_orphan(orphan.pid);
+            } else {
+                info!("[Cleanup] Orphaned process {} is gone or its PID was reused, not killing", orphan.pid);
+            }
+        }
+    }
+
+    let _lock = PID_FILE_LOCK.lock();
+    remove_stale_pid_files(&stale_files);
+}
+
+#[cfg(not(unix))]
+pub fn cleanup_orphans_with_confirmation(_ask: impl FnOnce(&[OrphanInfo])) {
+    cleanup_orphans();
+}
+
+/// Offer `orphans` for confirmation and wait for the answer. Returns the orphans
+/// to kill: the confirmed ones that were actually offered, or all of them on timeout.
+#[cfg(unix)]
+fn confirm_orphans(
+    orphans: Vec<OrphanInfo>,
+    timeout: std::time::Duration,
+    ask: impl FnOnce(&[OrphanInfo]),
+) -> Vec<OrphanInfo> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    *PENDING_ORPHANS.lock() = Some((orphans.clone(), tx));
+    ask(&orphans);
+
+    let answer = rx.recv_timeout(timeout);
+    PENDING_ORPHANS.lock().take();
+
+    match answer {
+        Ok(confirmed) => {
+            let (kill, keep): (Vec<OrphanInfo>, Vec<OrphanInfo>) =
+                orphans.into_iter().partition(|orphan| confirmed.contains(&orphan.pid));
+            if !keep.is_empty() {
+                let keep: Vec<u32> = keep.iter().map(|orphan| orphan.pid).collect();
+                info!("[Cleanup] Leaving orphaned PIDs {:?} running at the user's request", keep);
+            }
+            kill
+        }
+        Err(_) => {
+            warn!("[Cleanup] No answer about {} orphaned processes, killing them all", orphans.len());
+            orphans
+        }
+    }
+}
+
+/// Orphans currently awaiting confirmation, for a frontend that missed `orphans-found`
+pub fn pending_orphans() -> Vec<OrphanInfo> {
+    PENDING_ORPHANS
+        .lock()
+        .as_ref()
+        .map(|(orphans, _)| orphans.clone())
+        .unwrap_or_default()
+}
+
+/// Answer a pending orphan confirmation with the PIDs to kill; the rest are
+/// left running. Returns false if nothing is waiting for an answer.
+pub fn confirm_orphan_cleanup(pids: Vec<u32>) -> bool {
+    match PENDING_ORPHANS.lock().as_ref() {
+        Some((_, tx)) => tx.send(pids).is_ok(),
+        None => false,
     }
 }
 
@@ -273,29 +646,37 @@ pub fn cleanup_orphans() {
     }
 }
 
+/// Time budget for emergency cleanup before it stops walking process trees
+#[cfg(unix)]
+const EMERGENCY_CLEANUP_DEADLINE: std::time::Duration = std::time::Duration::from_secs(2);
+
 /// Emergency cleanup for panic/signal contexts
 ///
 /// This is synchronous and uses SIGKILL directly (no time for graceful cascade).
 /// Uses try_read() to avoid deadlock if panicking thread holds the lock.
+///
+/// The whole pass is bounded by `EMERGENCY_CLEANUP_DEADLINE`, checked between
+/// PIDs on the calling thread (no helper threads in panic/signal context).
+/// Walking children via `pty::get_child_pids` is the slow part on a machine with
+/// a large process table, so once the deadline passes only the directly-tracked
+/// PIDs are killed. The tradeoff: grandchildren of those PIDs that haven't been
+/// reached yet may be left running, to be reaped by orphan cleanup on next start.
 #[cfg(unix)]
 pub fn emergency_cleanup(state: &AppState) {
+    use std::time::Instant;
+
     info!("[Cleanup] Performing emergency cleanup...");
+    let deadline = Instant::now() + EMERGENCY_CLEANUP_DEADLINE;
 
     // Try to read PIDs from memory - but don't block if lock is held
     if let Some(sessions) = state.pty_sessions.try_read() {
         for session in sessions.values() {
+            if session.detached.load(std::sync::atomic::Ordering::SeqCst) {
+                continue;
+            }
             let pid = session.child_pid;
             if pid > 0 && pty::is_process_alive(pid) {
-                // Kill children first
-                let children = pty::get_child_pids(pid);
-                for child in children {
-                    unsafe {
-                        libc::kill(child as i32, libc::SIGKILL);
-                    }
-                }
-                unsafe {
-                    libc::kill(pid as i32, libc::SIGKILL);
-                }
+                emergency_kill(pid, deadline);
                 info!("[Cleanup] Emergency killed PID {}", pid);
             }
         }
@@ -305,31 +686,236 @@ pub fn emergency_cleanup(state: &AppState) {
 
     // Also try from our PID file as backup (in case we couldn't get the lock)
     if let Some(pid_file) = load_own_pid_file() {
-        for pid in pid_file.pty_pids {
+        for pid in pid_file.pids_to_kill() {
             if pty::is_process_alive(pid) {
-                let children = pty::get_child_pids(pid);
-                for child in children {
-                    unsafe {
-                        libc::kill(child as i32, libc::SIGKILL);
-                    }
-                }
-                unsafe {
-                    libc::kill(pid as i32, libc::SIGKILL);
-                }
+                emergency_kill(pid, deadline);
             }
         }
     }
 
+    if Instant::now() >= deadline {
+        warn!("[Cleanup] Emergency cleanup deadline passed; child processes may remain");
+    }
+
     // Clean up our PID file
     let _ = std::fs::remove_file(get_pid_file_path());
     info!("[Cleanup] Emergency cleanup complete");
 }
 
+/// SIGKILL a tracked PID, and its children if there's still time to look them up
+#[cfg(unix)]
+fn emergency_kill(pid: u32, deadline: std::time::Instant) {
+    if std::time::Instant::now() < deadline {
+        // Kill children first
+        for child in pty::get_child_pids(pid) {
+            unsafe {
+                libc::kill(child as i32, libc::SIGKILL);
+            }
+        }
+    }
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+}
+
 #[cfg(not(unix))]
 pub fn emergency_cleanup(_state: &AppState) {
     let _ = std::fs::remove_file(get_pid_file_path());
 }
 
+/// A PTY process known to this instance, via its session or PID file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedProcess {
+    pub pid: u32,
+    /// PTY session ID, if the process belongs to a live session
+    pub session_id: Option<String>,
+    /// Whether the process is listed in this instance's PID file
+    pub in_pid_file: bool,
+    /// Process name as reported by the OS (alive processes only)
+    pub name: Option<String>,
+}
+
+/// Read-only report cross-referencing tracked PIDs with the OS process table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessHealth {
+    pub app_pid: u32,
+    /// Tracked processes that are still running
+    pub alive: Vec<TrackedProcess>,
+    /// Processes still tracked (session or PID file) that are no longer running
+    pub dead: Vec<TrackedProcess>,
+    /// Direct children of the app that no session or PID file accounts for.
+    /// Includes the watchdog and in-flight git commands, as well as leaked PTYs.
+    pub untracked_children: Vec<u32>,
+}
+
+/// Build a health report from tracking data and an OS liveness check
+fn build_process_health(
+    app_pid: u32,
+    sessions: &[(String, u32)],
+    pid_file_pids: &[u32],
+    app_children: &[u32],
+    is_alive: impl Fn(u32) -> bool,
+    process_name: impl Fn(u32) -> Option<String>,
+) -> ProcessHealth {
+    let mut tracked: Vec<TrackedProcess> = sessions
+        .iter()
+        .map(|(session_id, pid)| TrackedProcess {
+            pid: *pid,
+            session_id: Some(session_id.clone()),
+            in_pid_file: pid_file_pids.contains(pid),
+            name: None,
+        })
+        .collect();
+
+    for pid in pid_file_pids {
+        if !tracked.iter().any(|t| t.pid == *pid) {
+            tracked.push(TrackedProcess {
+                pid: *pid,
+                session_id: None,
+                in_pid_file: true,
+                name: None,
+            });
+        }
+    }
+    tracked.sort_by_key(|t| t.pid);
+
+    let (mut alive, mut dead) = (Vec::new(), Vec::new());
+    for mut process in tracked {
+        if is_alive(process.pid) {
+            process.name = process_name(process.pid);
+            alive.push(process);
+        } else {
+            dead.push(process);
+        }
+    }
+
+    let untracked_children = app_children
+        .iter()
+        .copied()
+        .filter(|pid| {
+            !alive.iter().any(|t| t.pid == *pid) && !dead.iter().any(|t| t.pid == *pid)
+        })
+        .collect();
+
+    ProcessHealth {
+        app_pid,
+        alive,
+        dead,
+        untracked_children,
+    }
+}
+
+/// Cross-reference live PTY sessions, this instance's PID file, and the OS.
+/// Purely diagnostic - nothing is killed or rewritten.
+#[cfg(unix)]
+pub fn process_health(state: &AppState) -> ProcessHealth {
+    let sessions: Vec<(String, u32)> = state
+        .pty_sessions
+        .read()
+        .iter()
+        .map(|(id, session)| (id.clone(), session.child_pid))
+        .collect();
+    let pid_file_pids = {
+        let _lock = PID_FILE_LOCK.lock();
+        load_own_pid_file().map(|f| f.pty_pids).unwrap_or_default()
+    };
+
+    let app_pid = std::process::id();
+    let app_children = pty::get_direct_child_pids(app_pid);
+
+    build_process_health(
+        app_pid,
+        &sessions,
+        &pid_file_pids,
+        &app_children,
+        pty::is_process_alive,
+        pty::get_process_name,
+    )
+}
+
+#[cfg(not(unix))]
+pub fn process_health(state: &AppState) -> ProcessHealth {
+    // No process table inspection on non-Unix; report everything tracked as alive
+    let sessions: Vec<(String, u32)> = state
+        .pty_sessions
+        .read()
+        .iter()
+        .map(|(id, session)| (id.clone(), session.child_pid))
+        .collect();
+    let pid_file_pids = {
+        let _lock = PID_FILE_LOCK.lock();
+        load_own_pid_file().map(|f| f.pty_pids).unwrap_or_default()
+    };
+
+    build_process_health(
+        std::process::id(),
+        &sessions,
+        &pid_file_pids,
+        &[],
+        |_| true,
+        |_| None,
+    )
+}
+
+/// An app instance known from its PID file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceInfo {
+    pub app_pid: u32,
+    /// Whether the app process is running; `None` where liveness can't be checked
+    pub alive: Option<bool>,
+    /// Whether this is the instance answering the request
+    pub is_current: bool,
+    pub pty_pids: Vec<u32>,
+    /// Unix timestamp when the PID file was last updated
+    pub timestamp: u64,
+}
+
+/// Read every PID file in `pids_dir`, sorted by app PID.
+/// Unreadable or malformed files are skipped.
+fn read_instances(
+    pids_dir: &std::path::Path,
+    current_pid: u32,
+    is_alive: impl Fn(u32) -> Option<bool>,
+) -> Vec<InstanceInfo> {
+    let Ok(entries) = std::fs::read_dir(pids_dir) else {
+        return Vec::new();
+    };
+
+    let mut instances: Vec<InstanceInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "json").unwrap_or(false))
+        .filter_map(|path| {
+            let app_pid = app_pid_from_path(&path)?;
+            let pid_file = load_pid_file(&path)?;
+            let is_current = app_pid == current_pid;
+            Some(InstanceInfo {
+                app_pid,
+                alive: if is_current { Some(true) } else { is_alive(app_pid) },
+                is_current,
+                pty_pids: pid_file.pty_pids,
+                timestamp: pid_file.timestamp,
+            })
+        })
+        .collect();
+    instances.sort_by_key(|i| i.app_pid);
+    instances
+}
+
+/// List all app instances with a PID file, including this one. Read-only.
+pub fn list_instances() -> Vec<InstanceInfo> {
+    #[cfg(unix)]
+    let is_alive = |pid: u32| Some(pty::is_process_alive(pid));
+    #[cfg(not(unix))]
+    let is_alive = |_: u32| None::<bool>;
+
+    let _lock = PID_FILE_LOCK.lock();
+    read_instances(&get_pids_dir(), std::process::id(), is_alive)
+}
+
 /// Install a panic hook that attempts emergency cleanup
 pub fn install_panic_hook(state: Arc<AppState>) {
     let default_hook = std::panic::take_hook();
@@ -368,7 +954,7 @@ pub fn install_signal_handlers(state: Arc<AppState>) {
             info!("[Signal] Received signal {}, performing cleanup...", sig);
 
             // Mark shutdown in progress - if already shutting down, force exit
-            if pty::SHUTDOWN_IN_PROGRESS.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            if pty::begin_shutdown(Some(sig)) {
                 warn!("[Signal] Second signal received, forcing immediate exit");
                 std::process::exit(1);
             }
@@ -461,7 +1047,7 @@ pub fn run_watchdog(parent_pid: u32) {
     let pid_file_path = get_pids_dir().join(format!("{}.json", parent_pid));
 
     if let Some(pid_file) = load_pid_file(&pid_file_path) {
-        for pid in &pid_file.pty_pids {
+        for pid in &pid_file.pids_to_kill() {
             if pty::is_process_alive(*pid) {
                 // Kill children first
                 let children = pty::get_child_pids(*pid);
@@ -483,3 +1069,202 @@ pub fn run_watchdog(parent_pid: u32) {
 pub fn run_watchdog(_parent_pid: u32) {
     // Watchdog not implemented on non-Unix platforms
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detached_pids_are_not_killed() {
+        let pid_file = PidFile {
+            pty_pids: vec![100, 200, 300],
+            detached_pids: vec![200],
+            timestamp: 0,
+        };
+        assert_eq!(pid_file.pids_to_kill(), vec![100, 300]);
+    }
+
+    #[test]
+    fn pid_files_without_detached_list_still_load() {
+        let pid_file: PidFile = serde_json::from_str(r#"{"pty_pids":[1,2],"timestamp":0}"#).unwrap();
+        assert!(pid_file.detached_pids.is_empty());
+        assert_eq!(pid_file.pids_to_kill(), vec![1, 2]);
+    }
+
+    #[test]
+    fn corrupt_pid_file_is_rebuilt_from_live_sessions() {
+        let dir = std::env::temp_dir().join(format!("shellflow-pids-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("1234.json");
+        std::fs::write(&path, "{\"pty_pids\": [1, 2").unwrap();
+
+        let sessions = [(300, false), (100, true), (200, false)];
+        let health = verify_pid_file_at(&path, &sessions);
+        assert!(matches!(health, PidFileHealth::Rebuilt { tracked: 3, ref reason } if reason.starts_with("corrupt")));
+
+        let rebuilt = load_pid_file(&path).expect("rebuilt PID file should load");
+        assert_eq!(rebuilt.pty_pids, vec![100, 200, 300]);
+        assert_eq!(rebuilt.detached_pids, vec![100]);
+
+        // A healthy file is left alone
+        assert_eq!(verify_pid_file_at(&path, &[]), PidFileHealth::Healthy { tracked: 3 });
+        assert_eq!(load_pid_file(&path).unwrap().pty_pids, vec![100, 200, 300]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn orphan_confirmation_kills_chosen_pids_or_all_on_timeout() {
+        use std::time::Duration;
+
+        let orphans: Vec<OrphanInfo> = [10, 20, 30]
+            .into_iter()
+            .map(|pid| OrphanInfo { pid, app_pid: 1, name: None, started: None })
+            .collect();
+        assert!(!confirm_orphan_cleanup(vec![10]), "nothing is pending yet");
+
+        // Answered: only offered PIDs the user picked are killed
+        let kill = confirm_orphans(orphans.clone(), Duration::from_secs(10), |offered| {
+            assert_eq!(offered.len(), 3);
+            assert_eq!(pending_orphans(), offered);
+            std::thread::spawn(|| assert!(confirm_orphan_cleanup(vec![30, 10, 99])));
+        });
+        assert_eq!(kill.iter().map(|orphan| orphan.pid).collect::<Vec<_>>(), vec![10, 30]);
+        assert!(pending_orphans().is_empty());
+
+        // Unanswered: everything is killed
+        let kill = confirm_orphans(orphans.clone(), Duration::from_millis(50), |_| {});
+        assert_eq!(kill, orphans);
+        assert!(!confirm_orphan_cleanup(vec![10]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn orphans_are_recognised_by_start_time_before_killing() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let orphan = OrphanInfo {
+            pid,
+            app_pid: 1,
+            name: pty::get_process_name(pid),
+            started: pty::get_process_start(pid),
+        };
+        assert!(orphan.started.is_some());
+        assert!(orphan.is_same_process());
+
+        // Same PID, different process
+        let reused = OrphanInfo { started: Some("Thu Jan  1 00:00:00 1970".to_string()), ..orphan.clone() };
+        assert!(!reused.is_same_process());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!orphan.is_same_process());
+    }
+
+    #[test]
+    fn process_health_splits_alive_dead_and_untracked() {
+        let sessions = vec![("pty-1".to_string(), 100), ("pty-2".to_string(), 200)];
+        // 300 is a leftover in the PID file whose session already exited
+        let pid_file_pids = vec![100, 300];
+        let app_children = vec![100, 200, 999];
+
+        let health = build_process_health(
+            1,
+            &sessions,
+            &pid_file_pids,
+            &app_children,
+            |pid| pid != 300,
+            |pid| Some(format!("proc-{}", pid)),
+        );
+
+        let alive: Vec<u32> = health.alive.iter().map(|p| p.pid).collect();
+        assert_eq!(alive, vec![100, 200]);
+        assert!(health.alive[0].in_pid_file);
+        assert!(!health.alive[1].in_pid_file, "pty-2 is missing from the PID file");
+        assert_eq!(health.alive[1].name.as_deref(), Some("proc-200"));
+
+        assert_eq!(
+            health.dead,
+            vec![TrackedProcess {
+                pid: 300,
+                session_id: None,
+                in_pid_file: true,
+                name: None,
+            }]
+        );
+        assert_eq!(health.untracked_children, vec![999]);
+    }
+
+    #[test]
+    fn read_instances_marks_current_and_dead() {
+        let dir = std::env::temp_dir().join(format!("shellflow-cleanup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (app_pid, pids) in [(20u32, vec![21u32, 22]), (10, vec![11])] {
+            let pid_file = PidFile {
+                pty_pids: pids,
+                detached_pids: Vec::new(),
+                timestamp: app_pid as u64,
+            };
+            std::fs::write(
+                dir.join(format!("{}.json", app_pid)),
+                serde_json::to_string(&pid_file).unwrap(),
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.join("30.json"), "not json").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let instances = read_instances(&dir, 20, |_| Some(false));
+        assert_eq!(
+            instances,
+            vec![
+                InstanceInfo {
+                    app_pid: 10,
+                    alive: Some(false),
+                    is_current: false,
+                    pty_pids: vec![11],
+                    timestamp: 10,
+                },
+                InstanceInfo {
+                    app_pid: 20,
+                    alive: Some(true),
+                    is_current: true,
+                    pty_pids: vec![21, 22],
+                    timestamp: 20,
+                },
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn instance_lock_excludes_a_second_holder_until_released() {
+        let path = std::env::temp_dir().join(format!("shellflow-cleanup-test-{}.lock", uuid::Uuid::new_v4()));
+
+        let first = try_lock_file(&path).unwrap();
+        assert!(first.is_some());
+        assert!(try_lock_file(&path).unwrap().is_none());
+
+        drop(first);
+        assert!(try_lock_file(&path).unwrap().is_some());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn startup_cleanup_respects_config_and_env_override() {
+        let enabled = CleanupConfig::default();
+        let disabled = CleanupConfig {
+            on_startup: false,
+            ..CleanupConfig::default()
+        };
+
+        assert!(startup_cleanup_enabled_with(&enabled, None));
+        assert!(!startup_cleanup_enabled_with(&disabled, None));
+        assert!(!startup_cleanup_enabled_with(&enabled, Some("1".into())));
+        assert!(startup_cleanup_enabled_with(&enabled, Some("0".into())));
+        assert!(startup_cleanup_enabled_with(&enabled, Some("".into())));
+    }
+}
@@ -0,0 +1,141 @@
+//! Captured output of worktree init commands, so the tail can be fetched on demand.
+//!
+//! Persistent init commands run in a PTY whose recent output is kept in an
+//! in-memory ring buffer. One-shot init commands write their full output to
+//! `~/.shellflow/logs/{worktree_id}-init.log`.
+
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// Bytes of recent output kept per PTY session
+const TAIL_CAPACITY: usize = 64 * 1024;
+
+/// Recent output of one PTY session
+#[derive(Default)]
+struct OutputTail {
+    bytes: VecDeque<u8>,
+    /// Set once older output has been dropped, so the first line may be partial
+    truncated: bool,
+}
+
+impl OutputTail {
+    fn push(&mut self, data: &[u8]) {
+        self.bytes.extend(data);
+        if self.bytes.len() > TAIL_CAPACITY {
+            let excess = self.bytes.len() - TAIL_CAPACITY;
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PTY_TAILS: Mutex<HashMap<String, OutputTail>> = Mutex::new(HashMap::new());
+}
+
+/// Record output read from a PTY session
+pub fn append(pty_id: &str, data: &[u8]) {
+    PTY_TAILS.lock().entry(pty_id.to_string()).or_default().push(data);
+}
+
+/// Drop the recorded output of a PTY session
+pub fn forget(pty_id: &str) {
+    PTY_TAILS.lock().remove(pty_id);
+}
+
+/// Last `lines` lines of a PTY session's output, oldest first
+pub fn pty_tail(pty_id: &str, lines: usize) -> Option<Vec<String>> {
+    let tails = PTY_TAILS.lock();
+    let tail = tails.get(pty_id)?;
+    let (front, back) = tail.bytes.as_slices();
+    let mut bytes = Vec::with_capacity(tail.bytes.len());
+    bytes.extend_from_slice(front);
+    bytes.extend_from_slice(back);
+    Some(last_lines(&String::from_utf8_lossy(&bytes), tail.truncated, lines))
+}
+
+/// Path of the log file for a worktree's one-shot init command
+pub fn init_log_path(worktree_id: &str) -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".shellflow")
+        .join("logs")
+        .join(format!("{}-init.log", worktree_id))
+}
+
+/// Write the output of a worktree's one-shot init command to its log file
+pub fn write_init_log(worktree_id: &str, output: &[u8]) -> std::io::Result<()> {
+    let path = init_log_path(worktree_id);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, output)
+}
+
+/// Remove a worktree's init log file, if any
+pub fn remove_init_log(worktree_id: &str) {
+    let _ = std::fs::remove_file(init_log_path(worktree_id));
+}
+
+/// Last `lines` lines of a worktree's init log file, oldest first.
+/// Only the end of large files is read.
+pub fn init_log_tail(worktree_id: &str, lines: usize) -> std::io::Result<Vec<String>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(init_log_path(worktree_id))?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_CAPACITY as u64);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(last_lines(&String::from_utf8_lossy(&bytes), start > 0, lines))
+}
+
+/// Split captured output into its last `count` lines, oldest first.
+/// When the capture starts mid-stream, the first (partial) line is dropped.
+fn last_lines(text: &str, truncated: bool, count: usize) -> Vec<String> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    if truncated && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(count);
+    lines[skip..]
+        .iter()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_lines_returns_oldest_first() {
+        assert_eq!(last_lines("a\nb\nc\n", false, 2), vec!["b", "c"]);
+        assert_eq!(last_lines("a\r\nb\r\n", false, 10), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn last_lines_drops_partial_line_at_buffer_boundary() {
+        assert_eq!(last_lines("tial line\nfull\n", true, 10), vec!["full"]);
+    }
+
+    #[test]
+    fn pty_tail_keeps_only_recent_output() {
+        let pty_id = format!("test-{}", uuid::Uuid::new_v4());
+        for i in 0..10_000 {
+            append(&pty_id, format!("line {}\n", i).as_bytes());
+        }
+
+        let tail = pty_tail(&pty_id, 3).unwrap();
+        assert_eq!(tail, vec!["line 9997", "line 9998", "line 9999"]);
+
+        let all = pty_tail(&pty_id, usize::MAX).unwrap();
+        assert!(all.len() < 10_000);
+        assert!(all[0].starts_with("line "));
+
+        forget(&pty_id);
+        assert!(pty_tail(&pty_id, 3).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Default configuration file content (embedded at compile time)
@@ -28,16 +29,38 @@ pub struct RawConfig {
     pub worktree: WorktreeConfig,
     pub navigation: NavigationConfig,
     pub indicators: IndicatorsConfig,
+    pub notifications: NotificationsConfig,
+    pub git: GitConfig,
+    pub log: LogConfig,
+    pub cleanup: CleanupConfig,
+    pub watcher: WatcherConfig,
+    pub terminal: TerminalConfig,
     pub tasks: Vec<TaskConfig>,
+    /// Extra main-pane command presets, listed after the built-in ones
+    #[serde(rename = "commandPresets")]
+    pub command_presets: Vec<CommandPreset>,
     pub actions: ActionsConfig,
     pub scratch: ScratchConfig,
     pub mappings: MappingsConfig,
+    /// Menu accelerators keyed by action ID (e.g. "worktree::new": "CmdOrCtrl+N"),
+    /// overriding `mappings`
+    pub keybindings: HashMap<String, String>,
     pub panes: PanesConfig,
     /// Theme configuration. Can be a single theme name (string) or an object with light/dark themes.
     pub theme: Option<ThemeConfig>,
     /// How to handle borders when adapting themes.
     #[serde(rename = "themeBorderStyle", default)]
     pub theme_border_style: ThemeBorderStyle,
+    /// Per-theme corrections, keyed by theme name
+    #[serde(rename = "themeOverrides")]
+    pub theme_overrides: HashMap<String, ThemeOverride>,
+    /// Where themes are looked up beyond the bundled and user directories
+    pub themes: ThemesConfig,
+    /// Named sets of environment variables, selected per worktree
+    pub environments: HashMap<String, EnvMap>,
+    /// Focus the running instance instead of starting a second one
+    #[serde(rename = "singleInstance")]
+    pub single_instance: bool,
 }
 
 impl Default for RawConfig {
@@ -49,13 +72,25 @@ impl Default for RawConfig {
             worktree: WorktreeConfig::default(),
             navigation: NavigationConfig::default(),
             indicators: IndicatorsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            git: GitConfig::default(),
+            log: LogConfig::default(),
+            cleanup: CleanupConfig::default(),
+            watcher: WatcherConfig::default(),
+            terminal: TerminalConfig::default(),
             tasks: Vec::new(),
+            command_presets: Vec::new(),
             actions: ActionsConfig::default(),
             scratch: ScratchConfig::default(),
             mappings: MappingsConfig::default(),
+            keybindings: HashMap::new(),
             panes: PanesConfig::default(),
             theme: None, // Uses default Catppuccin themes when None
             theme_border_style: ThemeBorderStyle::default(),
+            theme_overrides: HashMap::new(),
+            themes: ThemesConfig::default(),
+            environments: HashMap::new(),
+            single_instance: true,
         }
     }
 }
@@ -69,16 +104,38 @@ pub struct Config {
     pub worktree: WorktreeConfig,
     pub navigation: NavigationConfig,
     pub indicators: IndicatorsConfig,
+    pub notifications: NotificationsConfig,
+    pub git: GitConfig,
+    pub log: LogConfig,
+    pub cleanup: CleanupConfig,
+    pub watcher: WatcherConfig,
+    pub terminal: TerminalConfig,
     pub tasks: Vec<TaskConfig>,
+    /// Extra main-pane command presets, listed after the built-in ones
+    #[serde(rename = "commandPresets")]
+    pub command_presets: Vec<CommandPreset>,
     pub actions: ActionsConfig,
     pub scratch: ScratchConfig,
     pub mappings: MappingsConfig,
+    /// Menu accelerators keyed by action ID (e.g. "worktree::new": "CmdOrCtrl+N"),
+    /// overriding `mappings`
+    pub keybindings: HashMap<String, String>,
     pub panes: PanesConfig,
     /// Theme configuration. Can be a single theme name or an object with light/dark themes.
     pub theme: Option<ThemeConfig>,
     /// How to handle borders when adapting themes.
     #[serde(rename = "themeBorderStyle")]
     pub theme_border_style: ThemeBorderStyle,
+    /// Per-theme corrections, keyed by theme name
+    #[serde(rename = "themeOverrides")]
+    pub theme_overrides: HashMap<String, ThemeOverride>,
+    /// Where themes are looked up beyond the bundled and user directories
+    pub themes: ThemesConfig,
+    /// Named sets of environment variables, selected per worktree
+    pub environments: HashMap<String, EnvMap>,
+    /// Focus the running instance instead of starting a second one
+    #[serde(rename = "singleInstance")]
+    pub single_instance: bool,
 }
 
 impl Config {
@@ -91,13 +148,25 @@ impl Config {
             worktree: raw.worktree,
             navigation: raw.navigation,
             indicators: raw.indicators,
+            notifications: raw.notifications,
+            git: raw.git,
+            log: raw.log,
+            cleanup: raw.cleanup,
+            watcher: raw.watcher,
+            terminal: raw.terminal.validated(),
             tasks: raw.tasks,
+            command_presets: raw.command_presets,
             actions: raw.actions,
             scratch: raw.scratch,
-            mappings: raw.mappings,
+            mappings: raw.mappings.with_overrides(&raw.keybindings),
+            keybindings: raw.keybindings,
             panes: raw.panes,
             theme: raw.theme,
             theme_border_style: raw.theme_border_style,
+            theme_overrides: raw.theme_overrides,
+            themes: raw.themes,
+            environments: raw.environments,
+            single_instance: raw.single_instance,
         }
     }
 }
@@ -174,6 +243,35 @@ pub enum ThemeBorderStyle {
     Visible,
 }
 
+/// Whether a theme is light or dark
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeType {
+    Light,
+    Dark,
+}
+
+impl ThemeType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThemeType::Light => "light",
+            ThemeType::Dark => "dark",
+        }
+    }
+}
+
+/// Corrections for a single theme.
+/// Example: `{ "type": "dark", "colors": { "terminal.ansiRed": "#ff5555" } }`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct ThemeOverride {
+    /// Force the theme's type when it's detected wrongly
+    #[serde(rename = "type")]
+    pub theme_type: Option<ThemeType>,
+    /// Colors replacing the theme's own `colors` entries (e.g. `terminal.ansiRed`)
+    pub colors: HashMap<String, String>,
+}
+
 /// A named URL with label and template.
 /// Example: { "Dev": "http://localhost:{{ branch | hash_port }}" }
 pub type UrlMap = std::collections::HashMap<String, String>;
@@ -205,6 +303,50 @@ pub struct TaskConfig {
     pub urls: UrlMap,
 }
 
+/// A suggested command for the main pane (e.g. an AI coding tool)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandPreset {
+    /// Display name
+    pub name: String,
+    /// Full command line, suitable for `main.command`
+    pub command: String,
+    /// Short description shown in the settings UI
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Built-in command presets. Kept in code so the list can evolve between releases.
+fn builtin_command_presets() -> Vec<CommandPreset> {
+    [
+        ("Claude Code", "claude", "Anthropic's agentic coding tool"),
+        ("Codex", "codex", "OpenAI's coding agent for the terminal"),
+        ("Aider", "aider", "AI pair programming in your terminal"),
+        ("Cursor Agent", "cursor-agent", "Cursor's coding agent CLI"),
+        ("Gemini CLI", "gemini", "Google's Gemini agent for the terminal"),
+        ("Shell", "shell", "Your login shell"),
+    ]
+    .into_iter()
+    .map(|(name, command, description)| CommandPreset {
+        name: name.to_string(),
+        command: command.to_string(),
+        description: description.to_string(),
+    })
+    .collect()
+}
+
+/// Built-in presets followed by user-defined ones. A user preset with the same
+/// name as a built-in replaces it in place.
+pub fn list_command_presets(user_presets: &[CommandPreset]) -> Vec<CommandPreset> {
+    let mut presets = builtin_command_presets();
+    for preset in user_presets {
+        match presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset.clone(),
+            None => presets.push(preset.clone()),
+        }
+    }
+    presets
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct MergeConfig {
@@ -274,6 +416,251 @@ impl Default for IndicatorsConfig {
     }
 }
 
+/// Native desktop notifications for background events.
+/// Notifications are never shown while the main window is focused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Notify when a merge or rebase completes
+    #[serde(rename = "mergeComplete")]
+    pub merge_complete: bool,
+    /// Notify when a merge or rebase stops on conflicts
+    #[serde(rename = "mergeConflict")]
+    pub merge_conflict: bool,
+    /// Notify when a terminal process exits with a nonzero code
+    #[serde(rename = "processFailed")]
+    pub process_failed: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            merge_complete: true,
+            merge_conflict: true,
+            process_failed: false,
+        }
+    }
+}
+
+/// Git behaviour configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// How many times to retry fetch, pull and push after a transient network failure
+    #[serde(rename = "networkRetries")]
+    pub network_retries: u32,
+    /// Flag changed files whose mode (e.g. the executable bit) changed
+    #[serde(rename = "detectModeChanges")]
+    pub detect_mode_changes: bool,
+    /// Seconds before a fetch, pull, push or changed-files scan is killed; 0 disables
+    #[serde(rename = "commandTimeout")]
+    pub command_timeout: u64,
+    /// Append every git invocation (argv, cwd, duration, exit code, stderr) to
+    /// `~/.shellflow/logs/git.log`
+    #[serde(rename = "auditLog")]
+    pub audit_log: bool,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            network_retries: 2,
+            detect_mode_changes: false,
+            command_timeout: 120,
+            audit_log: false,
+        }
+    }
+}
+
+impl GitConfig {
+    /// `command_timeout` as a duration, or None when disabled
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        (self.command_timeout > 0).then(|| std::time::Duration::from_secs(self.command_timeout))
+    }
+}
+
+/// Theme lookup configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemesConfig {
+    /// Additional directories to scan for themes (e.g. `~/.vscode/extensions`),
+    /// each listed under its directory name as the theme source
+    #[serde(rename = "extraDirectories")]
+    pub extra_directories: Vec<String>,
+}
+
+/// File watcher configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatcherConfig {
+    /// Only re-check the paths that changed instead of re-scanning the whole
+    /// worktree on every change, with a periodic full scan to stay accurate
+    pub incremental: bool,
+    /// Emit a `watcher-event` for every raw filesystem event, before debouncing.
+    /// Diagnostic only; noisy in busy worktrees.
+    pub debug: bool,
+    /// Seconds a worktree folder may be missing (with its parent still there)
+    /// before it's reported deleted, so a briefly unavailable folder isn't
+    /// torn down
+    #[serde(rename = "removalGraceSecs")]
+    pub removal_grace_secs: u64,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            incremental: false,
+            debug: false,
+            removal_grace_secs: 10,
+        }
+    }
+}
+
+/// Worktree terminal pane configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Don't start a worktree's terminal shell until its pane is first focused
+    #[serde(rename = "lazySpawn")]
+    pub lazy_spawn: bool,
+    /// Signal sent to session processes that survive SIGHUP on quit, before
+    /// escalating to SIGKILL. One of the signals `signal_session` accepts.
+    #[serde(rename = "shutdownSignal")]
+    pub shutdown_signal: String,
+    /// Thresholds for the busy/idle heuristic behind `session_activity`
+    pub activity: ActivityConfig,
+    /// Start the drawer's shells and commands through a login shell (`-l`), so
+    /// profile scripts set up PATH, rbenv, nvm etc. See `LoginShell::resolve`.
+    #[serde(rename = "loginShell", skip_serializing_if = "Option::is_none")]
+    pub login_shell: Option<bool>,
+}
+
+const DEFAULT_SHUTDOWN_SIGNAL: &str = "SIGTERM";
+
+/// Which sessions start as or through a login shell (`-l`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoginShell {
+    /// The user's shell itself
+    pub shell: bool,
+    /// Other commands, run through the shell with `-l -c`
+    pub commands: bool,
+}
+
+impl LoginShell {
+    /// `loginShell` applies to both when set. Unset, the shell starts as a login
+    /// shell as it always has, and commands go through one only on macOS, where
+    /// apps launched from Finder or the Dock don't inherit the PATH set up by
+    /// shell profiles.
+    pub fn resolve(setting: Option<bool>) -> Self {
+        Self {
+            shell: setting.unwrap_or(true),
+            commands: setting.unwrap_or(cfg!(target_os = "macos")),
+        }
+    }
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            lazy_spawn: false,
+            shutdown_signal: DEFAULT_SHUTDOWN_SIGNAL.to_string(),
+            activity: ActivityConfig::default(),
+            login_shell: None,
+        }
+    }
+}
+
+/// Thresholds for classifying a session as busy or idle from its output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityConfig {
+    /// Milliseconds of continuous output before a session counts as busy
+    #[serde(rename = "busyAfterMs")]
+    pub busy_after_ms: u64,
+    /// Milliseconds without output before a session counts as idle (or
+    /// waiting for input, when it's sitting at a prompt)
+    #[serde(rename = "idleAfterMs")]
+    pub idle_after_ms: u64,
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            busy_after_ms: 1000,
+            idle_after_ms: 2000,
+        }
+    }
+}
+
+impl TerminalConfig {
+    /// Normalize `shutdownSignal` (e.g. "term" to "SIGTERM"), falling back to
+    /// SIGTERM with a warning when it isn't an allowed signal
+    fn validated(mut self) -> Self {
+        match crate::pty::canonical_signal_name(&self.shutdown_signal) {
+            Some(name) => self.shutdown_signal = format!("SIG{}", name),
+            None => {
+                log::warn!(
+                    "[config] Invalid terminal.shutdownSignal {:?}, using {}",
+                    self.shutdown_signal,
+                    DEFAULT_SHUTDOWN_SIGNAL
+                );
+                self.shutdown_signal = DEFAULT_SHUTDOWN_SIGNAL.to_string();
+            }
+        }
+        self
+    }
+}
+
+/// Logging configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Startup log level (off, error, warn, info, debug, trace).
+    /// Unset means debug in development builds and info in release builds.
+    pub level: Option<String>,
+}
+
+impl LogConfig {
+    /// The configured level, falling back to the build default when unset or invalid
+    pub fn level_filter(&self) -> log::LevelFilter {
+        let default = if cfg!(debug_assertions) {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Info
+        };
+        match self.level.as_deref().map(str::parse::<log::LevelFilter>) {
+            Some(Ok(level)) => level,
+            Some(Err(_)) => {
+                log::warn!("[config] Invalid log.level {:?}, using {}", self.level, default);
+                default
+            }
+            None => default,
+        }
+    }
+}
+
+/// Crash-recovery cleanup configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupConfig {
+    /// Kill processes left behind by crashed instances when starting. Turning this
+    /// off (or setting SHELLFLOW_NO_CLEANUP) can leave real orphans running.
+    #[serde(rename = "onStartup")]
+    pub on_startup: bool,
+    /// Ask the user which orphans to kill (`orphans-found`) instead of killing
+    /// them all; unanswered prompts still kill everything
+    pub confirm: bool,
+}
+
+impl Default for CleanupConfig {
+    fn default() -> Self {
+        Self {
+            on_startup: true,
+            confirm: false,
+        }
+    }
+}
+
 /// Default prompt for merging a worktree with conflicts.
 /// Available template variables:
 /// - `worktree_dir` - Full path to the worktree
@@ -382,6 +769,15 @@ pub struct MainConfig {
     /// If not specified, uses panes.unfocusedOpacity.
     #[serde(rename = "unfocusedOpacity", skip_serializing_if = "Option::is_none")]
     pub unfocused_opacity: Option<f64>,
+    /// Theme for the main pane. If not specified, uses the top-level theme.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
+    /// Environment variables for every terminal, before any worktree profile
+    pub env: EnvMap,
+    /// Start the main pane's shell and command through a login shell (`-l`).
+    /// See `LoginShell::resolve`.
+    #[serde(rename = "loginShell", skip_serializing_if = "Option::is_none")]
+    pub login_shell: Option<bool>,
 }
 
 impl Default for MainConfig {
@@ -393,6 +789,9 @@ impl Default for MainConfig {
             font_ligatures: false,
             padding: 8,
             unfocused_opacity: None, // Uses panes.unfocusedOpacity when None
+            theme: None,             // Uses the top-level theme when None
+            env: EnvMap::new(),
+            login_shell: None,
         }
     }
 }
@@ -402,10 +801,14 @@ impl Default for MainConfig {
 pub struct WorktreeConfig {
     /// Directory where worktrees are created.
     /// Supports placeholder: {{ repo_directory }} (the repository directory)
-    /// Final path: {directory}/{workspace_name}
+    /// Final path: {directory}/{workspace_name}, shaped by `layout`
     /// Default: {{ repo_directory }}/.worktrees
     pub directory: Option<String>,
 
+    /// How a branch name like `feature/foo` maps onto the worktree's path
+    /// under `directory`
+    pub layout: WorktreeLayout,
+
     /// Branch to create new worktrees from.
     /// - "auto" (default): Auto-detect default branch (main/master)
     /// - "current": Use the currently checked out branch
@@ -416,6 +819,10 @@ pub struct WorktreeConfig {
     /// Configuration for copying files to new worktrees
     pub copy: CopyConfig,
 
+    /// Initialize and check out submodules in new worktrees
+    #[serde(rename = "initSubmodules")]
+    pub init_submodules: bool,
+
     /// Focus the branch name input when creating a new worktree
     #[serde(rename = "focusNewBranchNames")]
     pub focus_new_branch_names: bool,
@@ -425,17 +832,70 @@ pub struct WorktreeConfig {
 
     /// Configuration for delete operations
     pub delete: DeleteConfig,
+
+    /// Command to run in each new worktree once it has been created (and after
+    /// gitignored files have been copied). Supports the same template variables as tasks.
+    pub init: Option<InitCommandConfig>,
 }
 
 impl Default for WorktreeConfig {
     fn default() -> Self {
         Self {
             directory: None,
+            layout: WorktreeLayout::default(),
             base_branch: BaseBranch::default(),
             copy: CopyConfig::default(),
+            init_submodules: false,
             focus_new_branch_names: false,
             merge: MergeConfig::default(),
             delete: DeleteConfig::default(),
+            init: None,
+        }
+    }
+}
+
+/// Worktree path layout under `worktree.directory`.
+/// - "flat" (default): one directory per worktree, `/` replaced by `-` (`feature-foo`)
+/// - "nested": the branch path mirrored as subdirectories (`feature/foo`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WorktreeLayout {
+    #[default]
+    Flat,
+    Nested,
+}
+
+/// Worktree init command configuration.
+/// Examples:
+/// - One-shot: `"npm install"`
+/// - Persistent: `{ "command": "npm run dev", "persistent": true }`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InitCommandConfig {
+    /// Simple string form - runs once to completion
+    Simple(String),
+    /// Full object form
+    Full {
+        command: String,
+        /// Keep the command running as a PTY session (e.g. a dev server)
+        /// instead of waiting for it to exit
+        #[serde(default)]
+        persistent: bool,
+    },
+}
+
+impl InitCommandConfig {
+    pub fn command(&self) -> &str {
+        match self {
+            InitCommandConfig::Simple(cmd) => cmd,
+            InitCommandConfig::Full { command, .. } => command,
+        }
+    }
+
+    pub fn persistent(&self) -> bool {
+        match self {
+            InitCommandConfig::Simple(_) => false,
+            InitCommandConfig::Full { persistent, .. } => *persistent,
         }
     }
 }
@@ -449,6 +909,40 @@ pub struct CopyConfig {
 
     /// Glob patterns to exclude from copying
     pub except: Vec<String>,
+
+    /// Glob patterns to copy; when non-empty, other gitignored entries are skipped
+    pub include: Vec<String>,
+
+    /// Rewrite the contents of matching files as they're copied
+    pub transform: Vec<TransformRule>,
+
+    /// File in the repo listing exact relative paths to copy, one per line,
+    /// whether or not they're gitignored
+    pub manifest: Option<String>,
+
+    /// How this file's `except` and `include` combine with the lists from the
+    /// config files before it. Only read while merging project configs.
+    pub mode: CopyMergeMode,
+}
+
+/// How a project config's `worktree.copy.except` and `worktree.copy.include`
+/// combine with the lists they override
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyMergeMode {
+    /// Add the project's patterns to the inherited ones (the built-in defaults
+    /// when no earlier file sets the list)
+    #[default]
+    Extend,
+    /// Use only the project's patterns for each list it sets
+    Replace,
+}
+
+impl CopyConfig {
+    /// Whether anything is copied into new worktrees
+    pub fn is_enabled(&self) -> bool {
+        self.gitignored || self.manifest.is_some()
+    }
 }
 
 impl Default for CopyConfig {
@@ -456,10 +950,26 @@ impl Default for CopyConfig {
         Self {
             gitignored: false,
             except: vec![".claude".to_string(), ".worktrees".to_string()],
+            include: Vec::new(),
+            transform: Vec::new(),
+            manifest: None,
+            mode: CopyMergeMode::Extend,
         }
     }
 }
 
+/// Substitutions applied to copied files matching a glob.
+/// Each key is literal text to find; each value is a template expanded with the
+/// worktree context (`repo_directory`, `branch`, `worktree_name`).
+/// Example: { "glob": ".env", "replace": { "PORT=3000": "PORT={{ branch | hash_port }}" } }
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransformRule {
+    /// Glob matched against the path relative to the project root.
+    /// Patterns without a `/` also match the file name in any directory.
+    pub glob: String,
+    pub replace: std::collections::BTreeMap<String, String>,
+}
+
 /// Raw drawer config as stored in JSON (fields optional, inherit from main)
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RawDrawerConfig {
@@ -475,6 +985,9 @@ pub struct RawDrawerConfig {
     /// If not specified, uses panes.unfocusedOpacity.
     #[serde(rename = "unfocusedOpacity", skip_serializing_if = "Option::is_none")]
     pub unfocused_opacity: Option<f64>,
+    /// Theme for the drawer. If not specified, inherits the main pane's theme.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
 }
 
 /// Resolved drawer config with all fields populated (inherits from main)
@@ -491,6 +1004,9 @@ pub struct DrawerConfig {
     /// Opacity (0.0 to 1.0) applied to the drawer when open but not focused.
     #[serde(rename = "unfocusedOpacity")]
     pub unfocused_opacity: f64,
+    /// Theme for the drawer. None means the top-level theme.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
 }
 
 impl DrawerConfig {
@@ -503,6 +1019,7 @@ impl DrawerConfig {
             font_ligatures: raw.font_ligatures.unwrap_or(main.font_ligatures),
             padding: raw.padding.unwrap_or(main.padding),
             unfocused_opacity: raw.unfocused_opacity.unwrap_or(panes_unfocused_opacity),
+            theme: raw.theme.clone().or_else(|| main.theme.clone()),
         }
     }
 }
@@ -515,6 +1032,7 @@ impl Default for DrawerConfig {
             font_ligatures: false,
             padding: 8,
             unfocused_opacity: 0.7, // Same default as panes.unfocusedOpacity
+            theme: None,
         }
     }
 }
@@ -711,6 +1229,12 @@ pub fn shortcut_to_accelerator(shortcut: &str) -> String {
         .join("+")
 }
 
+/// Check a Tauri accelerator string (as produced by `shortcut_to_accelerator`)
+/// with the parser Tauri's menus use.
+pub fn is_valid_accelerator(accelerator: &str) -> bool {
+    accelerator.parse::<muda::accelerator::Accelerator>().is_ok()
+}
+
 /// Keyboard shortcut mappings configuration
 /// Field names use namespaced format matching action IDs (e.g., "app::addProject")
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -941,7 +1465,133 @@ impl Default for MappingsConfig {
     }
 }
 
+impl MappingsConfig {
+    /// Resolved Tauri accelerators for the current platform, keyed by action ID
+    /// (e.g. "worktree::new" -> "Cmd+N").
+    pub fn accelerators(&self) -> std::collections::BTreeMap<String, String> {
+        let value = serde_json::to_value(self).unwrap_or_default();
+        value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(action, shortcut)| {
+                let shortcut: Shortcut = serde_json::from_value(shortcut.clone()).ok()?;
+                Some((action.clone(), shortcut.to_accelerator()))
+            })
+            .collect()
+    }
+
+    /// These mappings with accelerators from the "keybindings" section put in
+    /// place of the shortcuts for their actions
+    pub fn with_overrides(self, keybindings: &HashMap<String, String>) -> Self {
+        let Ok(serde_json::Value::Object(mut mappings)) = serde_json::to_value(&self) else {
+            return self;
+        };
+        for (action, accelerator) in keybindings {
+            if let Some(shortcut) = mappings.get_mut(action) {
+                *shortcut = serde_json::Value::String(accelerator.clone());
+            }
+        }
+        serde_json::from_value(serde_json::Value::Object(mappings)).unwrap_or(self)
+    }
+}
+
+/// Drop invalid entries from the "keybindings" section of a merged config value: ones
+/// for unknown actions, and accelerators Tauri can't parse. The rest of the config,
+/// and the action's default binding, still apply.
+fn sanitize_keybindings(config: &mut serde_json::Value) {
+    let Some(keybindings) = config.get_mut("keybindings").and_then(|k| k.as_object_mut()) else {
+        return;
+    };
+
+    let actions = MappingsConfig::default().accelerators();
+    keybindings.retain(|action, value| {
+        if !actions.contains_key(action) {
+            log::warn!("[config] Ignoring keybinding for unknown action {}", action);
+            return false;
+        }
+        match value.as_str() {
+            Some(accelerator) if is_valid_accelerator(accelerator) => true,
+            _ => {
+                log::warn!("[config] Ignoring invalid keybinding for {}: {}", action, value);
+                false
+            }
+        }
+    });
+}
+
+/// Drop invalid entries from the "mappings" section of a merged config value so that
+/// a single bad binding falls back to its default instead of invalidating the whole config.
+fn sanitize_mappings(config: &mut serde_json::Value) {
+    let Some(mappings) = config.get_mut("mappings").and_then(|m| m.as_object_mut()) else {
+        return;
+    };
+
+    mappings.retain(|action, value| {
+        let shortcut: Shortcut = match serde_json::from_value(value.clone()) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                log::warn!("[config] Ignoring keybinding for {}: {}", action, e);
+                return false;
+            }
+        };
+
+        let shortcuts = match &shortcut {
+            Shortcut::Universal(s) => vec![s],
+            Shortcut::Platform { mac, other } => vec![mac, other],
+        };
+        match shortcuts
+            .into_iter()
+            .find(|s| !is_valid_accelerator(&shortcut_to_accelerator(s)))
+        {
+            Some(invalid) => {
+                log::warn!("[config] Ignoring invalid keybinding for {}: {:?}", action, invalid);
+                false
+            }
+            None => true,
+        }
+    });
+}
+
+lazy_static::lazy_static! {
+    /// Global config file given with `--config`, replacing the default location
+    static ref CONFIG_PATH_OVERRIDE: parking_lot::Mutex<Option<PathBuf>> = parking_lot::Mutex::new(None);
+}
+
+/// Use `path` as the global config file instead of ~/.config/shellflow/config.jsonc
+/// (from `--config`). Everything that reads, writes or watches the global config
+/// follows it. The path is validated first; see `validate_config_path`.
+pub fn set_config_path_override(path: &Path) -> Result<PathBuf, String> {
+    let path = validate_config_path(path)?;
+    *CONFIG_PATH_OVERRIDE.lock() = Some(path.clone());
+    Ok(path)
+}
+
+/// Resolve a config path given on the command line to an absolute path. It may
+/// not exist yet, but its directory must, and must be writable so the default
+/// config can be created there.
+fn validate_config_path(path: &Path) -> Result<PathBuf, String> {
+    let path = std::path::absolute(path).map_err(|e| format!("Invalid config path {}: {}", path.display(), e))?;
+    if path.is_dir() {
+        return Err(format!("Config path {} is a directory", path.display()));
+    }
+    let parent = path
+        .parent()
+        .filter(|parent| parent.is_dir())
+        .ok_or_else(|| format!("Directory of config path {} doesn't exist", path.display()))?;
+
+    let probe = parent.join(format!(".shellflow-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Config directory {} isn't writable: {}", parent.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+
+    Ok(path)
+}
+
 pub fn get_config_path() -> PathBuf {
+    if let Some(path) = CONFIG_PATH_OVERRIDE.lock().clone() {
+        return path;
+    }
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("/tmp"))
         .join(".config")
@@ -963,6 +1613,282 @@ fn parse_jsonc_value(content: &str) -> Result<serde_json::Value, String> {
         .map_err(|e| format!("{}", e))
 }
 
+/// Read the raw (JSONC) contents of the global config file.
+/// Creates the file from the default config first if it doesn't exist yet.
+pub fn read_config_raw() -> Result<String, String> {
+    let path = get_config_path();
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        std::fs::write(&path, DEFAULT_CONFIG).map_err(|e| format!("Failed to write default config: {}", e))?;
+    }
+    std::fs::read_to_string(&path).map_err(|e| format!("Failed to read config file: {}", e))
+}
+
+/// Validate and save the global config file.
+/// Content that doesn't parse is rejected with the parse error; valid content is
+/// written atomically (temp file + rename) so a crash never leaves a truncated config.
+pub fn write_config_raw(content: &str) -> Result<(), String> {
+    write_config_raw_to(&get_config_path(), content)
+}
+
+fn write_config_raw_to(path: &Path, content: &str) -> Result<(), String> {
+    let value = parse_jsonc_value(content)?;
+    if !value.is_object() {
+        return Err("Config must be a JSON object".to_string());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let tmp_path = path.with_extension("jsonc.tmp");
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to write config file: {}", e))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to save config file: {}", e)
+    })
+}
+
+/// Set one value in the global config file, e.g. `["main", "fontSize"]`,
+/// creating missing sections. The file is edited in place so its comments and
+/// formatting survive; only if it can't be scanned is it rewritten from the
+/// parsed value (dropping comments). Saved atomically like `write_config_raw`.
+pub fn set_config_value(path: &[&str], value: serde_json::Value) -> Result<(), String> {
+    let content = read_config_raw()?;
+    let updated = match set_jsonc_member(&content, path, &value) {
+        Some(updated) => updated,
+        None => {
+            log::warn!("[Config] Couldn't edit {} in place; rewriting the config without comments", path.join("."));
+            let mut parsed = parse_jsonc_value(&content)?;
+            set_value_at(&mut parsed, path, value)?;
+            serde_json::to_string_pretty(&parsed).map_err(|e| e.to_string())?
+        }
+    };
+    write_config_raw(&updated)
+}
+
+/// Set `path` in a parsed config, creating missing objects along the way
+fn set_value_at(root: &mut serde_json::Value, path: &[&str], value: serde_json::Value) -> Result<(), String> {
+    let Some((last, parents)) = path.split_last() else {
+        return Err("Config path is empty".to_string());
+    };
+    let mut current = root;
+    for key in parents {
+        let object = current.as_object_mut().ok_or_else(|| format!("{} is not an object", key))?;
+        current = object
+            .entry(key.to_string())
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    }
+    current
+        .as_object_mut()
+        .ok_or_else(|| format!("{} is not an object", last))?
+        .insert(last.to_string(), value);
+    Ok(())
+}
+
+/// Clamp a font size to the range `validate_config` accepts without warning
+pub fn clamp_font_size(size: u16) -> u16 {
+    size.clamp(*SANE_FONT_SIZES.start(), *SANE_FONT_SIZES.end())
+}
+
+/// One `"key": value` member of a JSONC object, as byte offsets into the source
+struct JsoncMember {
+    key: String,
+    key_start: usize,
+    value: std::ops::Range<usize>,
+    /// Just past the member's trailing comma, if it has one
+    comma_end: Option<usize>,
+}
+
+/// A JSONC object's members and the offsets of its braces
+struct JsoncObject {
+    open: usize,
+    close: usize,
+    members: Vec<JsoncMember>,
+}
+
+/// Just enough of a JSONC parser to find where object members start and end,
+/// skipping strings and comments
+struct JsoncScanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsoncScanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            if self.src[self.pos..].starts_with(b"//") {
+                while self.peek().is_some_and(|b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if self.src[self.pos..].starts_with(b"/*") {
+                self.pos = match self.src[self.pos + 2..].windows(2).position(|w| w == b"*/") {
+                    Some(offset) => self.pos + 2 + offset + 2,
+                    None => self.src.len(),
+                };
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<()> {
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => self.string(),
+            b'{' => self.object().map(|_| ()),
+            b'[' => {
+                self.pos += 1;
+                loop {
+                    self.skip_trivia();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(());
+                    }
+                    self.value()?;
+                    self.skip_trivia();
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|b| !b.is_ascii_whitespace() && !matches!(b, b',' | b'}' | b']' | b'/'))
+                {
+                    self.pos += 1;
+                }
+                (self.pos > start).then_some(())
+            }
+        }
+    }
+
+    fn object(&mut self) -> Option<JsoncObject> {
+        if self.peek() != Some(b'{') {
+            return None;
+        }
+        let open = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.peek()? == b'}' {
+                let close = self.pos;
+                self.pos += 1;
+                return Some(JsoncObject { open, close, members });
+            }
+            let key_start = self.pos;
+            self.string()?;
+            let key: String = serde_json::from_slice(&self.src[key_start..self.pos]).ok()?;
+            self.skip_trivia();
+            if self.peek()? != b':' {
+                return None;
+            }
+            self.pos += 1;
+            self.skip_trivia();
+            let value_start = self.pos;
+            self.value()?;
+            let value = value_start..self.pos;
+            self.skip_trivia();
+            let comma_end = (self.peek() == Some(b',')).then(|| {
+                self.pos += 1;
+                self.pos
+            });
+            members.push(JsoncMember { key, key_start, value, comma_end });
+        }
+    }
+}
+
+/// Leading whitespace of the line containing `pos`
+fn line_indent(content: &str, pos: usize) -> &str {
+    let line_start = content[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = &content[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Return `content` with the value at `path` set to `value`, leaving everything
+/// else (comments included) untouched. Missing members are appended to their
+/// object. None if the content can't be scanned or a parent isn't an object.
+fn set_jsonc_member(content: &str, path: &[&str], value: &serde_json::Value) -> Option<String> {
+    let mut scanner = JsoncScanner { src: content.as_bytes(), pos: 0 };
+    scanner.skip_trivia();
+    for (depth, key) in path.iter().enumerate() {
+        let object = scanner.object()?;
+        let Some(member) = object.members.iter().find(|m| m.key == *key) else {
+            let nested = path[depth + 1..]
+                .iter()
+                .rev()
+                .fold(value.clone(), |inner, key| serde_json::json!({ *key: inner }));
+            let entry = format!("{}: {}", serde_json::to_string(key).ok()?, serde_json::to_string(&nested).ok()?);
+            return Some(insert_jsonc_member(content, &object, &entry));
+        };
+        if depth + 1 == path.len() {
+            let replacement = serde_json::to_string(value).ok()?;
+            return Some(format!("{}{}{}", &content[..member.value.start], replacement, &content[member.value.end..]));
+        }
+        scanner.pos = member.value.start;
+    }
+    None
+}
+
+/// Append a `"key": value` entry to an object, matching the indentation of its
+/// last member (or nesting one level in when it's empty)
+fn insert_jsonc_member(content: &str, object: &JsoncObject, entry: &str) -> String {
+    let splice = |at: usize, text: String| format!("{}{}{}", &content[..at], text, &content[at..]);
+    match object.members.last() {
+        Some(last) => {
+            let indent = line_indent(content, last.key_start);
+            match last.comma_end {
+                Some(comma_end) => splice(comma_end, format!("\n{}{}", indent, entry)),
+                None => splice(last.value.end, format!(",\n{}{}", indent, entry)),
+            }
+        }
+        None => {
+            let outer = line_indent(content, object.open);
+            let inner = format!("{}  ", outer);
+            if content[object.open + 1..object.close].trim().is_empty() {
+                format!(
+                    "{}\n{}{}\n{}{}",
+                    &content[..object.open + 1],
+                    inner,
+                    entry,
+                    outer,
+                    &content[object.close..]
+                )
+            } else {
+                splice(object.open + 1, format!("\n{}{}", inner, entry))
+            }
+        }
+    }
+}
+
 /// Get all config file paths that should be watched for a given project
 pub fn get_config_paths(project_path: Option<&str>) -> Vec<PathBuf> {
     let mut paths = vec![get_config_path()];
@@ -997,6 +1923,42 @@ fn deep_merge(base: &mut serde_json::Value, overlay: &serde_json::Value) {
     }
 }
 
+/// Merge a repo or local config file over the config so far. Arrays normally
+/// accumulate (see `deep_merge`); `worktree.copy.except` and `.include` follow
+/// the file's `worktree.copy.mode` instead.
+fn merge_project_layer(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    use serde_json::Value;
+
+    if let Some(overlay_copy) = overlay.pointer("/worktree/copy").and_then(Value::as_object) {
+        let mode: CopyMergeMode = overlay_copy
+            .get("mode")
+            .and_then(|mode| serde_json::from_value(mode.clone()).ok())
+            .unwrap_or_default();
+        let defaults = CopyConfig::default();
+        let base_copy = base
+            .as_object_mut()
+            .and_then(|base| base.entry("worktree").or_insert_with(|| serde_json::json!({})).as_object_mut())
+            .and_then(|worktree| worktree.entry("copy").or_insert_with(|| serde_json::json!({})).as_object_mut());
+        if let Some(base_copy) = base_copy {
+            for (key, default) in [("except", &defaults.except), ("include", &defaults.include)] {
+                if !overlay_copy.contains_key(key) {
+                    continue;
+                }
+                match mode {
+                    CopyMergeMode::Replace => {
+                        base_copy.remove(key);
+                    }
+                    CopyMergeMode::Extend => {
+                        base_copy.entry(key).or_insert_with(|| serde_json::json!(default));
+                    }
+                }
+            }
+        }
+    }
+
+    deep_merge(base, overlay);
+}
+
 /// Merge two arrays. If items are objects with a "name" field, merge by name.
 /// Items with matching names are overridden; unique items accumulate.
 fn merge_arrays(base: &mut serde_json::Value, overlay: &serde_json::Value) {
@@ -1093,7 +2055,7 @@ pub fn load_config_with_errors(project_path: Option<&str>) -> ConfigResult {
         if repo_config_path.exists() {
             match std::fs::read_to_string(&repo_config_path) {
                 Ok(content) => match parse_jsonc_value(&content) {
-                    Ok(repo_value) => deep_merge(&mut merged, &repo_value),
+                    Ok(repo_value) => merge_project_layer(&mut merged, &repo_value),
                     Err(e) => errors.push(ConfigError {
                         file: repo_config_path.display().to_string(),
                         message: e,
@@ -1111,7 +2073,7 @@ pub fn load_config_with_errors(project_path: Option<&str>) -> ConfigResult {
         if local_config_path.exists() {
             match std::fs::read_to_string(&local_config_path) {
                 Ok(content) => match parse_jsonc_value(&content) {
-                    Ok(local_value) => deep_merge(&mut merged, &local_value),
+                    Ok(local_value) => merge_project_layer(&mut merged, &local_value),
                     Err(e) => errors.push(ConfigError {
                         file: local_config_path.display().to_string(),
                         message: e,
@@ -1125,6 +2087,9 @@ pub fn load_config_with_errors(project_path: Option<&str>) -> ConfigResult {
         }
     }
 
+    sanitize_mappings(&mut merged);
+    sanitize_keybindings(&mut merged);
+
     // Deserialize merged config as RawConfig, then resolve to Config
     let raw_config: RawConfig = serde_json::from_value(merged).unwrap_or_default();
     let config = Config::from_raw(raw_config);
@@ -1132,6 +2097,164 @@ pub fn load_config_with_errors(project_path: Option<&str>) -> ConfigResult {
     ConfigResult { config, errors }
 }
 
+/// How serious a config validation issue is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The setting can't work as given
+    Error,
+    /// The setting is probably a mistake but may work
+    Warning,
+}
+
+/// A semantically invalid config value, for showing inline in settings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    /// Dotted path of the offending key, as written in the config file (e.g. `main.fontSize`)
+    pub field_path: String,
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(field_path: &str, severity: IssueSeverity, message: impl Into<String>) -> Self {
+        Self {
+            field_path: field_path.to_string(),
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// Font sizes outside this range are almost certainly typos
+const SANE_FONT_SIZES: std::ops::RangeInclusive<u16> = 6..=72;
+
+/// Stands in for `{{ repo_directory }}` when expanding the worktree directory
+/// without a project, so repo-relative results can be recognised
+const REPO_DIRECTORY_MARKER: &str = "\u{1}repo_directory\u{1}";
+
+/// Check config values that parse but can't work: font sizes out of range, a
+/// main command that isn't on the user's PATH, a worktree directory that doesn't
+/// expand or can't be created, and merge flags that contradict each other.
+/// Only reads the filesystem; nothing is created or changed.
+pub fn validate_config(cfg: &Config) -> Vec<ConfigIssue> {
+    validate_config_with_path(cfg, &crate::pty::get_cached_user_path())
+}
+
+fn validate_config_with_path(cfg: &Config, search_path: &str) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    issues.extend(check_font_size("main.fontSize", cfg.main.font_size));
+    issues.extend(check_font_size("drawer.fontSize", cfg.drawer.font_size));
+    if let Some(command) = cfg.main.command.as_deref() {
+        issues.extend(check_command("main.command", command, search_path));
+    }
+    issues.extend(check_worktree_directory(cfg.worktree.directory.as_deref()));
+    issues.extend(check_merge_flags(&cfg.worktree.merge));
+    issues
+}
+
+fn check_font_size(field_path: &str, size: u16) -> Option<ConfigIssue> {
+    if size == 0 {
+        Some(ConfigIssue::new(field_path, IssueSeverity::Error, "Font size must be greater than 0"))
+    } else if !SANE_FONT_SIZES.contains(&size) {
+        Some(ConfigIssue::new(
+            field_path,
+            IssueSeverity::Warning,
+            format!(
+                "Font size {} is outside the usual range ({}-{})",
+                size,
+                SANE_FONT_SIZES.start(),
+                SANE_FONT_SIZES.end()
+            ),
+        ))
+    } else {
+        None
+    }
+}
+
+fn check_command(field_path: &str, command: &str, search_path: &str) -> Option<ConfigIssue> {
+    let Some(program) = command.split_whitespace().next() else {
+        return Some(ConfigIssue::new(field_path, IssueSeverity::Error, "Command is empty"));
+    };
+    if crate::pty::find_program_in(program, search_path).is_some() {
+        return None;
+    }
+    Some(ConfigIssue::new(
+        field_path,
+        IssueSeverity::Warning,
+        format!("'{}' was not found on your PATH", program),
+    ))
+}
+
+fn check_worktree_directory(directory: Option<&str>) -> Option<ConfigIssue> {
+    const FIELD: &str = "worktree.directory";
+    let template = directory?;
+
+    let ctx = crate::template::TemplateContext::new(REPO_DIRECTORY_MARKER)
+        .with_branch("branch")
+        .with_worktree_name("name");
+    let expanded = match crate::template::expand_template(template, &ctx) {
+        Ok(expanded) => expanded,
+        Err(e) => return Some(ConfigIssue::new(FIELD, IssueSeverity::Error, format!("Template error: {}", e))),
+    };
+    if expanded.trim().is_empty() {
+        return Some(ConfigIssue::new(FIELD, IssueSeverity::Error, "Directory expands to an empty path"));
+    }
+
+    // Paths inside or relative to the repository depend on the project, so there's
+    // nothing fixed to check
+    let path = match expanded.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()?.join(rest),
+        None => PathBuf::from(&expanded),
+    };
+    if expanded.contains(REPO_DIRECTORY_MARKER) || !path.is_absolute() {
+        return None;
+    }
+
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    if !existing.is_dir() {
+        return Some(ConfigIssue::new(
+            FIELD,
+            IssueSeverity::Error,
+            format!("{} is not a directory", existing.display()),
+        ));
+    }
+    if !is_writable_dir(existing) {
+        return Some(ConfigIssue::new(
+            FIELD,
+            IssueSeverity::Error,
+            format!("{} is not writable", existing.display()),
+        ));
+    }
+    None
+}
+
+#[cfg(unix)]
+fn is_writable_dir(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable_dir(dir: &Path) -> bool {
+    std::fs::metadata(dir).map(|m| !m.permissions().readonly()).unwrap_or(false)
+}
+
+fn check_merge_flags(merge: &MergeConfig) -> Option<ConfigIssue> {
+    // The branch stays checked out in the worktree, so git refuses to delete it
+    (merge.delete_local_branch && !merge.delete_worktree).then(|| {
+        ConfigIssue::new(
+            "worktree.merge.deleteLocalBranch",
+            IssueSeverity::Warning,
+            "The local branch can't be deleted while its worktree is kept; enable deleteWorktree too",
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1443,4 +2566,471 @@ mod tests {
             assert_eq!(apps.editor_target(), AppTarget::Tab);
         }
     }
+
+    mod init_command_config {
+        use super::*;
+
+        #[test]
+        fn deserializes_simple_string_as_one_shot() {
+            let json = r#""npm install""#;
+            let config: InitCommandConfig = serde_json::from_str(json).unwrap();
+            assert_eq!(config.command(), "npm install");
+            assert!(!config.persistent());
+        }
+
+        #[test]
+        fn deserializes_persistent_object() {
+            let json = r#"{"command": "npm run dev", "persistent": true}"#;
+            let config: InitCommandConfig = serde_json::from_str(json).unwrap();
+            assert_eq!(config.command(), "npm run dev");
+            assert!(config.persistent());
+        }
+
+        #[test]
+        fn persistent_defaults_to_false() {
+            let json = r#"{"command": "make setup"}"#;
+            let config: InitCommandConfig = serde_json::from_str(json).unwrap();
+            assert!(!config.persistent());
+        }
+
+        #[test]
+        fn worktree_config_has_no_init_by_default() {
+            assert!(WorktreeConfig::default().init.is_none());
+        }
+    }
+
+    mod keybindings {
+        use super::*;
+
+        #[test]
+        fn accepts_default_mappings() {
+            for (action, accelerator) in MappingsConfig::default().accelerators() {
+                assert!(is_valid_accelerator(&accelerator), "{} -> {}", action, accelerator);
+            }
+        }
+
+        #[test]
+        fn rejects_unknown_keys_and_modifiers() {
+            assert!(!is_valid_accelerator("Cmd+Banana"));
+            assert!(!is_valid_accelerator("Hyper+N"));
+            assert!(!is_valid_accelerator("Cmd+"));
+            assert!(!is_valid_accelerator("F25"));
+        }
+
+        #[test]
+        fn accelerators_are_keyed_by_action_id() {
+            let accelerators = MappingsConfig::default().accelerators();
+            assert!(accelerators.contains_key("worktree::new"));
+            assert_eq!(accelerators.get("worktree::renameBranch").map(String::as_str), Some("F2"));
+        }
+
+        #[test]
+        fn keybindings_override_mappings_for_their_action() {
+            let keybindings = HashMap::from([("worktree::new".to_string(), "CmdOrCtrl+Shift+W".to_string())]);
+            let accelerators = MappingsConfig::default().with_overrides(&keybindings).accelerators();
+            assert_eq!(accelerators["worktree::new"], "CmdOrCtrl+Shift+W");
+            assert_eq!(accelerators["worktree::renameBranch"], "F2");
+        }
+
+        #[test]
+        fn sanitize_drops_unknown_and_unparseable_keybindings() {
+            let mut value = serde_json::json!({
+                "keybindings": {
+                    "worktree::new": "CmdOrCtrl+N",
+                    "worktree::renameBranch": "Hyper+R",
+                    "nope::nothing": "F4",
+                    "drawer::toggle": 42
+                }
+            });
+            sanitize_keybindings(&mut value);
+
+            let keybindings = value["keybindings"].as_object().unwrap();
+            assert_eq!(keybindings.len(), 1);
+            assert_eq!(keybindings["worktree::new"], "CmdOrCtrl+N");
+        }
+
+        #[test]
+        fn sanitize_drops_invalid_bindings_only() {
+            let mut value = serde_json::json!({
+                "mappings": {
+                    "worktree::new": "ctrl+shift+banana",
+                    "worktree::renameBranch": "F3",
+                    "drawer::toggle": 42
+                }
+            });
+            sanitize_mappings(&mut value);
+
+            let mappings = value["mappings"].as_object().unwrap();
+            assert_eq!(mappings.len(), 1);
+            assert_eq!(mappings["worktree::renameBranch"], "F3");
+
+            let raw: RawConfig = serde_json::from_value(value).unwrap();
+            assert_eq!(raw.mappings.rename_branch.for_current_platform(), "F3");
+            assert_eq!(raw.mappings.toggle_drawer.for_current_platform(), "ctrl+`");
+        }
+    }
+
+    mod write_config_raw {
+        use super::*;
+
+        fn temp_config_path() -> PathBuf {
+            std::env::temp_dir()
+                .join(format!("shellflow-config-test-{}", uuid::Uuid::new_v4()))
+                .join("config.jsonc")
+        }
+
+        #[test]
+        fn writes_valid_jsonc() {
+            let path = temp_config_path();
+            let content = "{\n  // comment\n  \"theme\": \"Dracula\"\n}\n";
+
+            write_config_raw_to(&path, content).unwrap();
+
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+            assert!(!path.with_extension("jsonc.tmp").exists());
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+
+        #[test]
+        fn rejects_invalid_content_without_touching_file() {
+            let path = temp_config_path();
+            write_config_raw_to(&path, "{}").unwrap();
+
+            assert!(write_config_raw_to(&path, "{ \"theme\": ").is_err());
+            assert!(write_config_raw_to(&path, "[1, 2]").is_err());
+
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+    }
+
+    mod set_jsonc_member {
+        use super::*;
+
+        const CONFIG: &str = r#"{
+  // Main pane
+  "main": {
+    "fontSize": 13, // inline comment
+    /* block */ "padding": 8
+  },
+  "drawer": {}
+}
+"#;
+
+        fn set(path: &[&str], value: serde_json::Value) -> String {
+            let updated = set_jsonc_member(CONFIG, path, &value).unwrap();
+            parse_jsonc_value(&updated).expect("edited config should still parse");
+            updated
+        }
+
+        #[test]
+        fn replaces_values_and_keeps_comments() {
+            let updated = set(&["main", "fontSize"], serde_json::json!(16));
+            assert_eq!(updated, CONFIG.replace("\"fontSize\": 13", "\"fontSize\": 16"));
+        }
+
+        #[test]
+        fn appends_missing_members() {
+            let updated = set(&["main", "fontFamily"], serde_json::json!("Fira Code"));
+            assert!(updated.contains("\"padding\": 8,\n    \"fontFamily\": \"Fira Code\"\n  },"));
+            assert!(updated.contains("// inline comment"));
+
+            let updated = set(&["drawer", "fontSize"], serde_json::json!(11));
+            assert!(updated.contains("\"drawer\": {\n    \"fontSize\": 11\n  }\n}"));
+        }
+
+        #[test]
+        fn creates_missing_sections() {
+            let updated = set(&["panes", "unfocusedOpacity"], serde_json::json!(0.5));
+            assert!(updated.contains("\"drawer\": {},\n  \"panes\": {\"unfocusedOpacity\":0.5}\n}"));
+        }
+
+        #[test]
+        fn appends_after_a_trailing_comma() {
+            let updated = set_jsonc_member("{\n  \"a\": 1,\n}", &["b"], &serde_json::json!(2)).unwrap();
+            assert_eq!(updated, "{\n  \"a\": 1,\n  \"b\": 2\n}");
+        }
+
+        #[test]
+        fn gives_up_when_a_parent_is_not_an_object() {
+            assert!(set_jsonc_member(CONFIG, &["main", "fontSize", "x"], &serde_json::json!(1)).is_none());
+        }
+
+        #[test]
+        fn clamps_font_sizes() {
+            assert_eq!(clamp_font_size(0), 6);
+            assert_eq!(clamp_font_size(14), 14);
+            assert_eq!(clamp_font_size(500), 72);
+        }
+    }
+
+    mod config_path_override {
+        use super::*;
+
+        #[test]
+        fn accepts_new_file_in_existing_directory() {
+            let dir = std::env::temp_dir().join(format!("shellflow-config-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("work.jsonc");
+
+            assert_eq!(validate_config_path(&path).unwrap(), path);
+            // The writability probe is cleaned up
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+            assert!(validate_config_path(Path::new("relative.jsonc")).unwrap().is_absolute());
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn rejects_directories_and_missing_parents() {
+            let dir = std::env::temp_dir().join(format!("shellflow-config-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            assert!(validate_config_path(&dir).unwrap_err().contains("is a directory"));
+            assert!(validate_config_path(&dir.join("missing/config.jsonc"))
+                .unwrap_err()
+                .contains("doesn't exist"));
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    mod pane_themes {
+        use super::*;
+
+        #[test]
+        fn drawer_inherits_main_theme() {
+            let raw: RawConfig = serde_json::from_str(r#"{"main": {"theme": "Dracula"}}"#).unwrap();
+            let config = Config::from_raw(raw);
+            assert_eq!(config.main.theme, Some(ThemeConfig::Single("Dracula".to_string())));
+            assert_eq!(config.drawer.theme, Some(ThemeConfig::Single("Dracula".to_string())));
+        }
+
+        #[test]
+        fn drawer_theme_overrides_main() {
+            let raw: RawConfig = serde_json::from_str(
+                r#"{"main": {"theme": "Dracula"}, "drawer": {"theme": {"light": "Catppuccin Latte", "dark": "Nord"}}}"#,
+            )
+            .unwrap();
+            let config = Config::from_raw(raw);
+            assert_eq!(
+                config.drawer.theme,
+                Some(ThemeConfig::LightDark {
+                    light: "Catppuccin Latte".to_string(),
+                    dark: "Nord".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn pane_themes_default_to_none() {
+            let config = Config::default();
+            assert!(config.main.theme.is_none());
+            assert!(config.drawer.theme.is_none());
+        }
+    }
+
+    mod validate_config {
+        use super::*;
+
+        fn fields(issues: &[ConfigIssue]) -> Vec<(&str, IssueSeverity)> {
+            issues.iter().map(|i| (i.field_path.as_str(), i.severity)).collect()
+        }
+
+        #[test]
+        fn default_config_is_valid() {
+            assert!(validate_config_with_path(&Config::default(), "").is_empty());
+        }
+
+        #[test]
+        fn checks_font_sizes() {
+            assert_eq!(check_font_size("main.fontSize", 13), None);
+            assert_eq!(check_font_size("main.fontSize", 0).unwrap().severity, IssueSeverity::Error);
+            assert_eq!(check_font_size("main.fontSize", 200).unwrap().severity, IssueSeverity::Warning);
+
+            let mut cfg = Config::default();
+            cfg.main.font_size = 0;
+            cfg.drawer.font_size = 4;
+            assert_eq!(
+                fields(&validate_config_with_path(&cfg, "")),
+                vec![("main.fontSize", IssueSeverity::Error), ("drawer.fontSize", IssueSeverity::Warning)]
+            );
+        }
+
+        #[test]
+        fn checks_command_on_search_path() {
+            let dir = std::env::temp_dir().join(format!("shellflow-config-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("my-agent"), "").unwrap();
+            let search_path = dir.to_string_lossy().to_string();
+
+            assert_eq!(check_command("main.command", "my-agent --fast", &search_path), None);
+            let issue = check_command("main.command", "missing-agent", &search_path).unwrap();
+            assert_eq!(issue.severity, IssueSeverity::Warning);
+            assert!(issue.message.contains("missing-agent"));
+            assert_eq!(check_command("main.command", "  ", &search_path).unwrap().severity, IssueSeverity::Error);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn checks_worktree_directory() {
+            assert_eq!(check_worktree_directory(None), None);
+            assert_eq!(check_worktree_directory(Some("{{ repo_directory }}/.worktrees")), None);
+            assert_eq!(check_worktree_directory(Some("../trees")), None);
+
+            let dir = std::env::temp_dir().join(format!("shellflow-config-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let nested = format!("{}/not/yet/created", dir.display());
+            assert_eq!(check_worktree_directory(Some(&nested)), None);
+
+            std::fs::write(dir.join("file"), "").unwrap();
+            let under_file = format!("{}/file/trees", dir.display());
+            assert_eq!(check_worktree_directory(Some(&under_file)).unwrap().severity, IssueSeverity::Error);
+
+            let broken = check_worktree_directory(Some("{{ repo_directory")).unwrap();
+            assert_eq!(broken.severity, IssueSeverity::Error);
+            assert!(broken.message.starts_with("Template error"));
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn checks_merge_flags() {
+            let mut merge = MergeConfig::default();
+            assert_eq!(check_merge_flags(&merge), None);
+
+            merge.delete_worktree = false;
+            merge.delete_local_branch = true;
+            let issue = check_merge_flags(&merge).unwrap();
+            assert_eq!(issue.field_path, "worktree.merge.deleteLocalBranch");
+        }
+    }
+
+    mod terminal_config {
+        use super::*;
+
+        #[test]
+        fn shutdown_signal_defaults_to_sigterm() {
+            let config = Config::from_raw(RawConfig::default());
+            assert_eq!(config.terminal.shutdown_signal, "SIGTERM");
+        }
+
+        #[test]
+        fn shutdown_signal_is_normalized() {
+            let raw: RawConfig = serde_json::from_str(r#"{"terminal": {"shutdownSignal": "int"}}"#).unwrap();
+            assert_eq!(Config::from_raw(raw).terminal.shutdown_signal, "SIGINT");
+        }
+
+        #[test]
+        fn invalid_shutdown_signal_falls_back_to_sigterm() {
+            let raw: RawConfig = serde_json::from_str(r#"{"terminal": {"shutdownSignal": "SIGSTOP"}}"#).unwrap();
+            assert_eq!(Config::from_raw(raw).terminal.shutdown_signal, "SIGTERM");
+        }
+    }
+
+    mod log_config {
+        use super::*;
+
+        #[test]
+        fn parses_level_case_insensitively() {
+            let raw: RawConfig = serde_json::from_str(r#"{"log": {"level": "Trace"}}"#).unwrap();
+            assert_eq!(raw.log.level_filter(), log::LevelFilter::Trace);
+        }
+
+        #[test]
+        fn invalid_or_missing_level_uses_build_default() {
+            let expected = LogConfig::default().level_filter();
+            let raw: RawConfig = serde_json::from_str(r#"{"log": {"level": "loud"}}"#).unwrap();
+            assert_eq!(raw.log.level_filter(), expected);
+        }
+    }
+
+    mod command_presets {
+        use super::*;
+
+        fn preset(name: &str, command: &str) -> CommandPreset {
+            CommandPreset {
+                name: name.to_string(),
+                command: command.to_string(),
+                description: String::new(),
+            }
+        }
+
+        #[test]
+        fn includes_builtins_without_user_presets() {
+            let presets = list_command_presets(&[]);
+            assert!(presets.iter().any(|p| p.command == "claude"));
+            assert!(presets.iter().any(|p| p.command == "aider"));
+        }
+
+        #[test]
+        fn appends_user_presets_and_overrides_by_name() {
+            let builtin_count = list_command_presets(&[]).len();
+            let presets = list_command_presets(&[
+                preset("Claude Code", "claude --continue"),
+                preset("My Agent", "my-agent --fast"),
+            ]);
+
+            assert_eq!(presets.len(), builtin_count + 1);
+            assert_eq!(presets[0].command, "claude --continue");
+            assert_eq!(presets.last().unwrap().name, "My Agent");
+        }
+
+        #[test]
+        fn deserializes_from_config() {
+            let raw: RawConfig = serde_json::from_str(
+                r#"{"commandPresets": [{"name": "Goose", "command": "goose session"}]}"#,
+            )
+            .unwrap();
+            assert_eq!(raw.command_presets, vec![preset("Goose", "goose session")]);
+        }
+    }
+
+    mod copy_merge_mode {
+        use super::*;
+        use serde_json::json;
+
+        fn merged_copy(global: serde_json::Value, project: serde_json::Value) -> CopyConfig {
+            let mut merged = global;
+            merge_project_layer(&mut merged, &project);
+            let raw: RawConfig = serde_json::from_value(merged).unwrap();
+            Config::from_raw(raw).worktree.copy
+        }
+
+        #[test]
+        fn extend_adds_project_patterns_to_global_ones() {
+            let copy = merged_copy(
+                json!({"worktree": {"copy": {"except": [".claude", "node_modules"], "include": [".env*"]}}}),
+                json!({"worktree": {"copy": {"except": ["node_modules", "target"], "include": [".env*", "*.pem"]}}}),
+            );
+            assert_eq!(copy.except, vec![".claude", "node_modules", "target"]);
+            assert_eq!(copy.include, vec![".env*", "*.pem"]);
+        }
+
+        #[test]
+        fn extend_builds_on_defaults_when_global_config_has_no_list() {
+            let copy = merged_copy(json!({}), json!({"worktree": {"copy": {"except": [".worktrees", "dist"]}}}));
+            assert_eq!(copy.except, vec![".claude", ".worktrees", "dist"]);
+        }
+
+        #[test]
+        fn replace_uses_only_project_patterns_for_lists_it_sets() {
+            let copy = merged_copy(
+                json!({"worktree": {"copy": {"except": [".claude", "node_modules"], "include": [".env*"]}}}),
+                json!({"worktree": {"copy": {"mode": "replace", "except": ["node_modules", "target"]}}}),
+            );
+            assert_eq!(copy.except, vec!["node_modules", "target"]);
+            // Lists the project doesn't set are still inherited
+            assert_eq!(copy.include, vec![".env*"]);
+        }
+
+        #[test]
+        fn replace_with_empty_list_clears_it() {
+            let copy = merged_copy(
+                json!({"worktree": {"copy": {"include": [".env*"]}}}),
+                json!({"worktree": {"copy": {"mode": "replace", "include": []}}}),
+            );
+            assert!(copy.include.is_empty());
+        }
+    }
 }
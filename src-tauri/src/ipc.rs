@@ -0,0 +1,229 @@
+//! Local IPC endpoint so scripts can drive a running instance.
+//!
+//! The running app listens on a Unix domain socket at `~/.shellflow/ipc.sock`.
+//! The protocol is line-delimited JSON: the client writes one `IpcRequest` per
+//! line and the server answers each with one `IpcResponse` line.
+//!
+//! Example:
+//! ```text
+//! > {"command":"newWorktree","projectPath":"/path/to/repo","name":"feature-x"}
+//! < {"ok":true,"worktree":{"id":"...","name":"feature-x",...}}
+//! ```
+
+use crate::state::Worktree;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A request sent to the running instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
+pub enum IpcRequest {
+    /// Create a worktree in the project containing `project_path` and open it
+    #[serde(rename_all = "camelCase")]
+    NewWorktree { project_path: String, name: Option<String> },
+    /// Bring the main window to the front (sent by a second launch before it exits)
+    Focus,
+}
+
+/// The running instance's reply to a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<Worktree>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub fn worktree(worktree: Worktree) -> Self {
+        Self { ok: true, worktree: Some(worktree), error: None }
+    }
+
+    pub fn ok() -> Self {
+        Self { ok: true, worktree: None, error: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, worktree: None, error: Some(message.into()) }
+    }
+}
+
+/// Path of the IPC socket
+pub fn socket_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".shellflow")
+        .join("ipc.sock")
+}
+
+/// Parse one request line and run it through the handler, producing one response line
+fn handle_line<F>(line: &str, handler: &F) -> String
+where
+    F: Fn(IpcRequest) -> IpcResponse,
+{
+    let response = match serde_json::from_str::<IpcRequest>(line) {
+        Ok(request) => handler(request),
+        Err(e) => IpcResponse::error(format!("Invalid request: {}", e)),
+    };
+    serde_json::to_string(&response).unwrap_or_else(|_| r#"{"ok":false}"#.to_string())
+}
+
+/// Start listening for IPC requests on a background thread.
+/// A socket left behind by a crashed instance is replaced; if another instance
+/// is already listening, this instance leaves it alone and doesn't listen.
+#[cfg(unix)]
+pub fn start_server<F>(handler: F)
+where
+    F: Fn(IpcRequest) -> IpcResponse + Send + Sync + 'static,
+{
+    start_server_at(socket_path(), handler)
+}
+
+#[cfg(unix)]
+fn start_server_at<F>(path: PathBuf, handler: F)
+where
+    F: Fn(IpcRequest) -> IpcResponse + Send + Sync + 'static,
+{
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::Arc;
+
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            log::warn!("[ipc] Another instance is already listening on {:?}", path);
+            return;
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::warn!("[ipc] Failed to bind {:?}: {}", path, e);
+            return;
+        }
+    };
+    log::info!("[ipc] Listening on {:?}", path);
+
+    let handler = Arc::new(handler);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let handler = Arc::clone(&handler);
+            std::thread::spawn(move || {
+                let Ok(mut writer) = stream.try_clone() else { return };
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else { break };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let response = handle_line(&line, &*handler);
+                    if writeln!(writer, "{}", response).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn start_server<F>(_handler: F)
+where
+    F: Fn(IpcRequest) -> IpcResponse + Send + Sync + 'static,
+{
+    log::info!("[ipc] IPC is not supported on this platform yet");
+}
+
+/// Send a single request to the running instance and wait for its response
+#[cfg(unix)]
+pub fn send_request(request: &IpcRequest) -> Result<IpcResponse, String> {
+    send_request_to(&socket_path(), request)
+}
+
+#[cfg(unix)]
+fn send_request_to(path: &std::path::Path, request: &IpcRequest) -> Result<IpcResponse, String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path)
+        .map_err(|e| format!("Shellflow doesn't appear to be running ({}): {}", path.display(), e))?;
+    let line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+    writeln!(stream, "{}", line).map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    serde_json::from_str(&response).map_err(|e| format!("Invalid response: {}", e))
+}
+
+#[cfg(not(unix))]
+pub fn send_request(_request: &IpcRequest) -> Result<IpcResponse, String> {
+    Err("IPC is not supported on this platform yet".to_string())
+}
+
+/// Remove the socket file on shutdown
+pub fn remove_socket() {
+    let _ = std::fs::remove_file(socket_path());
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_new_worktree_request() {
+        let request: IpcRequest =
+            serde_json::from_str(r#"{"command":"newWorktree","projectPath":"/repo","name":"feature-x"}"#).unwrap();
+        assert_eq!(
+            request,
+            IpcRequest::NewWorktree {
+                project_path: "/repo".to_string(),
+                name: Some("feature-x".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_focus_request() {
+        let request: IpcRequest = serde_json::from_str(r#"{"command":"focus"}"#).unwrap();
+        assert_eq!(request, IpcRequest::Focus);
+    }
+
+    #[test]
+    fn invalid_lines_get_an_error_response() {
+        let response = handle_line("not json", &|_| IpcResponse::error("unreachable"));
+        let response: IpcResponse = serde_json::from_str(&response).unwrap();
+        assert!(!response.ok);
+        assert!(response.error.unwrap().starts_with("Invalid request"));
+    }
+
+    #[test]
+    fn round_trips_over_socket() {
+        let dir = std::env::temp_dir().join(format!("shellflow-ipc-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("ipc.sock");
+
+        start_server_at(path.clone(), |request| match request {
+            IpcRequest::NewWorktree { name, .. } => IpcResponse::error(format!("got {}", name.unwrap_or_default())),
+            IpcRequest::Focus => IpcResponse::ok(),
+        });
+
+        let response = send_request_to(
+            &path,
+            &IpcRequest::NewWorktree {
+                project_path: "/repo".to_string(),
+                name: Some("feature-x".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(response.error.as_deref(), Some("got feature-x"));
+        assert!(send_request_to(&path, &IpcRequest::Focus).unwrap().ok);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,11 +1,18 @@
+mod bundle;
 mod cleanup;
+mod command_log;
 mod config;
 mod git;
+mod ipc;
 mod mappings;
 mod menu;
+mod metrics;
+mod notifications;
 mod pty;
 mod state;
 mod template;
+#[cfg(test)]
+mod test_support;
 mod theme;
 mod watcher;
 mod worktree;
@@ -14,14 +21,16 @@ use config::MergeStrategy;
 use git::{MergeFeasibility, WorktreeDeleteStatus};
 use log::info;
 use serde::{Deserialize, Serialize};
-use state::{AppState, FileChange, Project, Worktree};
-use std::collections::HashMap;
+use state::{AppState, FileChange, Pane, Project, Worktree};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::{AppHandle, Emitter, Manager, State};
 
 type Result<T> = std::result::Result<T, String>;
+/// Result for git commands whose errors the frontend branches on (see `git::GitError`)
+type GitResult<T> = std::result::Result<T, git::GitError>;
 
 fn map_err<E: std::fmt::Display>(e: E) -> String {
     e.to_string()
@@ -39,36 +48,36 @@ fn add_project(state: State<'_, Arc<AppState>>, path: &str) -> Result<Project> {
     let path = Path::new(path);
     let canonical_path = path.canonicalize().map_err(map_err)?;
 
-    {
-        let mut persisted = state.persisted.write();
-
-        // Check if project with this path already exists
-        if let Some(existing) = persisted
-            .projects
-            .iter_mut()
-            .find(|p| Path::new(&p.path).canonicalize().ok() == Some(canonical_path.clone()))
-        {
-            // Reactivate existing project instead of creating duplicate
+    // Check if project with this path already exists
+    let existing_id = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .find(|p| Path::new(&p.path).canonicalize().ok() == Some(canonical_path.clone()))
+        .map(|p| p.id.clone());
+    if let Some(existing_id) = existing_id {
+        // Reactivate existing project instead of creating duplicate
+        let reactivated = state.update_project(&existing_id, |existing| {
             existing.is_active = true;
             existing.last_accessed_at = Some(worktree::chrono_lite_now());
-            let project = existing.clone();
-            drop(persisted);
-            state.save().map_err(map_err)?;
+            existing.clone()
+        });
+        if let Some(project) = reactivated {
             return Ok(project);
         }
     }
 
     let project = worktree::create_project(path).map_err(map_err)?;
-
-    {
-        let mut persisted = state.persisted.write();
-        persisted.projects.push(project.clone());
-    }
-
-    state.save().map_err(map_err)?;
+    state.add_project(project.clone());
     Ok(project)
 }
 
+#[tauri::command]
+fn inspect_path(path: &str) -> git::PathInspection {
+    git::inspect_path(path)
+}
+
 #[tauri::command]
 fn list_projects(state: State<'_, Arc<AppState>>) -> Result<Vec<Project>> {
     Ok(state.persisted.read().projects.clone())
@@ -76,18 +85,14 @@ fn list_projects(state: State<'_, Arc<AppState>>) -> Result<Vec<Project>> {
 
 #[tauri::command]
 fn hide_project(state: State<'_, Arc<AppState>>, project_id: &str) -> Result<()> {
-    {
-        let mut persisted = state.persisted.write();
-        if let Some(project) = persisted.projects.iter_mut().find(|p| p.id == project_id) {
-            // Stop watching individual worktrees
-            for wt in &project.worktrees {
-                watcher::stop_watching(&wt.id);
-            }
-            // Mark as hidden (keeps in project list for reopening)
-            project.is_active = false;
+    state.update_project(project_id, |project| {
+        // Stop watching individual worktrees
+        for wt in &project.worktrees {
+            watcher::stop_watching(&wt.id);
         }
-    }
-    state.save().map_err(map_err)?;
+        // Mark as hidden (keeps in project list for reopening)
+        project.is_active = false;
+    });
     Ok(())
 }
 
@@ -97,118 +102,558 @@ fn touch_project(
     state: State<'_, Arc<AppState>>,
     project_id: &str,
 ) -> Result<()> {
-    let worktrees_to_watch: Vec<(String, String)>;
-    {
-        let mut persisted = state.persisted.write();
-        if let Some(project) = persisted.projects.iter_mut().find(|p| p.id == project_id) {
+    let worktrees_to_watch: Vec<(String, String)> = state
+        .update_project(project_id, |project| {
             let was_inactive = !project.is_active;
             project.last_accessed_at = Some(worktree::chrono_lite_now());
             project.is_active = true;
 
             // If reactivating, collect worktrees to watch
-            worktrees_to_watch = if was_inactive {
-                project.worktrees.iter().map(|wt| (wt.id.clone(), wt.path.clone())).collect()
+            if was_inactive {
+                project
+                    .worktrees
+                    .iter()
+                    .filter(|wt| !wt.suspended)
+                    .map(|wt| (wt.id.clone(), wt.path.clone()))
+                    .collect()
             } else {
                 Vec::new()
-            };
-        } else {
-            worktrees_to_watch = Vec::new();
-        }
-    }
+            }
+        })
+        .unwrap_or_default();
 
     // Start watching worktrees outside the lock
     for (id, path) in worktrees_to_watch {
         watcher::watch_worktree(app.clone(), id, path);
     }
 
-    state.save().map_err(map_err)?;
     Ok(())
 }
 
 // Worktree commands
+/// Adopt worktrees added to the project's repository outside Shellflow
+#[tauri::command]
+fn import_existing_worktrees(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    project_id: &str,
+) -> Result<Vec<Worktree>> {
+    let (imported, is_active) = state.mutate(|persisted| {
+        let project = persisted
+            .projects
+            .iter_mut()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+        let imported = worktree::import_existing_worktrees(project).map_err(map_err)?;
+        Ok::<_, String>((imported, project.is_active))
+    })?;
+
+    // Hidden projects start watching when they're reopened
+    if is_active {
+        for wt in &imported {
+            watcher::watch_worktree(app.clone(), wt.id.clone(), wt.path.clone());
+        }
+    }
+    Ok(imported)
+}
+
+/// Create a worktree for a project. Checking out a large tree can take a while,
+/// so it runs on the blocking pool, leaving `cancel_worktree_creation` free to run
+/// against the `op_id` the caller picked.
 #[tauri::command]
-fn create_worktree(
+async fn create_worktree(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
+    project_path: String,
+    name: Option<String>,
+    op_id: Option<String>,
+) -> Result<CreatedWorktree> {
+    let state = Arc::clone(&state);
+    tauri::async_runtime::spawn_blocking(move || {
+        create_worktree_blocking(&app, &state, &project_path, name, op_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn create_worktree_blocking(
+    app: &AppHandle,
+    state: &Arc<AppState>,
     project_path: &str,
     name: Option<String>,
-) -> Result<Worktree> {
+    op_id: Option<String>,
+) -> Result<CreatedWorktree> {
+    use std::sync::atomic::Ordering;
+
     let total_start = Instant::now();
     info!("[create_worktree] Starting...");
 
+    // Register the creation before doing anything, so a cancel sent with a caller-chosen
+    // op ID is seen by the synchronous steps as well as the background ones
+    let op_id = op_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let creation = Arc::new(state::PendingCreation {
+        worktree_id: std::sync::OnceLock::new(),
+        cancel: git::CancelToken::new(),
+        init_pid: parking_lot::Mutex::new(None),
+    });
+    state.worktree_creations.write().insert(op_id.clone(), Arc::clone(&creation));
+
     let start = Instant::now();
     let cfg = config::load_config_for_project(Some(project_path));
     info!("[create_worktree] load_config took {:?}", start.elapsed());
 
     let start = Instant::now();
-    let mut persisted = state.persisted.write();
-    info!("[create_worktree] acquire write lock took {:?}", start.elapsed());
-
-    let project = persisted
-        .projects
-        .iter_mut()
-        .find(|p| p.path == project_path)
-        .ok_or_else(|| format!("Project not found: {}", project_path))?;
+    let created = state.mutate(|persisted| {
+        let project = persisted
+            .projects
+            .iter_mut()
+            .find(|p| p.path == project_path)
+            .ok_or_else(|| format!("Project not found: {}", project_path))?;
+
+        let project_path_buf = Path::new(project.repo_path()).to_path_buf();
+        let wt = worktree::create_worktree(
+            project,
+            name,
+            cfg.worktree.directory.as_deref(),
+            cfg.worktree.layout,
+            &cfg.worktree.base_branch,
+        )
+        .map_err(map_err)?;
+        Ok::<_, String>((wt, project_path_buf))
+    });
+    let (wt, project_path_buf) = match created {
+        Ok(created) => created,
+        Err(e) => {
+            state.worktree_creations.write().remove(&op_id);
+            return Err(e);
+        }
+    };
+    let _ = creation.worktree_id.set(wt.id.clone());
+    info!("[create_worktree] worktree::create_worktree took {:?}", start.elapsed());
+    if creation.cancel.is_cancelled() {
+        state.worktree_creations.write().remove(&op_id);
+        undo_worktree_creation(app, state, &op_id, &wt.id);
+        return Err("Worktree creation cancelled".to_string());
+    }
 
-    let project_path_buf = Path::new(&project.path).to_path_buf();
+    // Resolve the init command (if any) up front so it can run after the copy finishes
+    let init = cfg.worktree.init.as_ref().map(|init| {
+        let ctx = template::TemplateContext::for_project(project_path)
+            .with_branch(&wt.branch)
+            .with_worktree_name(&wt.name);
+        let command = template::expand_template(init.command(), &ctx)
+            .unwrap_or_else(|_| init.command().to_string());
+        (command, init.persistent())
+    });
 
+    // Start file watcher for this worktree before any background work that may undo it
     let start = Instant::now();
-    let wt = worktree::create_worktree(
-        project,
-        name,
-        cfg.worktree.directory.as_deref(),
-        &cfg.worktree.base_branch,
-    )
-    .map_err(map_err)?;
-    info!("[create_worktree] worktree::create_worktree took {:?}", start.elapsed());
+    watcher::watch_worktree(app.clone(), wt.id.clone(), wt.path.clone());
+    info!("[create_worktree] watch_worktree took {:?}", start.elapsed());
 
-    // Copy gitignored files if enabled in config (in background thread)
-    if cfg.worktree.copy.gitignored {
+    // Initialize submodules and copy gitignored files (if enabled in config), then run
+    // the init command, in a background thread. All three can be aborted through
+    // the creation operation.
+    let init_submodules = cfg.worktree.init_submodules && git::has_submodules(Path::new(&wt.path));
+    let git_limits = git::GitLimits { timeout: cfg.git.timeout(), cancel: Some(creation.cancel.clone()) };
+    let copy_gitignored = cfg.worktree.copy.is_enabled();
+    let creation_op_id = if init_submodules || copy_gitignored || init.is_some() {
         let worktree_path = wt.path.clone();
         let worktree_id = wt.id.clone();
-        let except = cfg.worktree.copy.except.clone();
+        let copy_config = cfg.worktree.copy.clone();
+        let copy_ctx = template::TemplateContext::for_project(project_path)
+            .with_branch(&wt.branch)
+            .with_worktree_name(&wt.name);
         let app_handle = app.clone();
         let project_path_buf_clone = project_path_buf.clone();
-
-        // Emit copy started event
-        let _ = app_handle.emit("worktree-copy-started", &worktree_id);
+        let state_arc = Arc::clone(state);
+        let copy_cancel = copy_gitignored.then(|| {
+            let cancel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            state.copy_cancellations.write().insert(worktree_id.clone(), Arc::clone(&cancel));
+            // A cancel that landed before the copy was registered didn't reach it
+            if creation.cancel.is_cancelled() {
+                cancel.store(true, Ordering::SeqCst);
+            }
+            // Emit copy started event
+            let _ = app_handle.emit("worktree-copy-started", &worktree_id);
+            cancel
+        });
+        let op_id_clone = op_id.clone();
 
         std::thread::spawn(move || {
-            let start = Instant::now();
-            let result = worktree::copy_gitignored_files(
-                &project_path_buf_clone,
-                Path::new(&worktree_path),
-                &except,
-            );
+            if init_submodules {
+                let result = git::init_submodules(Path::new(&worktree_path), &git_limits, &mut |line| {
+                    let _ = app_handle.emit(
+                        "git-progress",
+                        GitProgress {
+                            worktree_id: worktree_id.clone(),
+                            operation: "submodules".to_string(),
+                            message: line.to_string(),
+                        },
+                    );
+                });
+                if let Err(e) = result {
+                    log::warn!("[create_worktree] Failed to initialize submodules: {}", e);
+                }
+            }
+
+            let mut copy_cancelled = false;
+            if let Some(cancel) = copy_cancel {
+                let start = Instant::now();
+                let result = worktree::copy_gitignored_files(
+                    &project_path_buf_clone,
+                    Path::new(&worktree_path),
+                    &copy_config,
+                    &copy_ctx,
+                    &cancel,
+                    &mut |progress| {
+                        let _ = app_handle.emit(
+                            "worktree-copy-progress",
+                            WorktreeCopyProgress {
+                                worktree_id: worktree_id.clone(),
+                                files_copied: progress.files_copied,
+                                total: progress.total,
+                                current_path: progress.current_path.clone(),
+                            },
+                        );
+                    },
+                );
+                state_arc.copy_cancellations.write().remove(&worktree_id);
+
+                match &result {
+                    Ok(()) => info!("[create_worktree] background copy_gitignored_files took {:?}", start.elapsed()),
+                    Err(e) => info!("[create_worktree] background copy_gitignored_files failed: {}", e),
+                }
+                copy_cancelled = matches!(result, Err(worktree::WorktreeError::Cancelled));
+
+                // Emit copy completed event
+                let _ = app_handle.emit("worktree-copy-completed", serde_json::json!({
+                    "worktreeId": worktree_id,
+                    "success": result.is_ok(),
+                    "cancelled": copy_cancelled,
+                    "durationMs": start.elapsed().as_millis() as u64,
+                }));
+            }
 
-            match &result {
-                Ok(()) => info!("[create_worktree] background copy_gitignored_files took {:?}", start.elapsed()),
-                Err(e) => info!("[create_worktree] background copy_gitignored_files failed: {}", e),
+            if !copy_cancelled {
+                if let Some((command, persistent)) = init {
+                    run_worktree_init(
+                        &app_handle,
+                        &state_arc,
+                        &worktree_id,
+                        &worktree_path,
+                        &command,
+                        persistent,
+                        Some(&*creation),
+                    );
+                }
             }
 
-            // Emit copy completed event
-            let _ = app_handle.emit("worktree-copy-completed", serde_json::json!({
-                "worktreeId": worktree_id,
-                "success": result.is_ok(),
-                "durationMs": start.elapsed().as_millis() as u64,
-            }));
+            // Once removed, the creation can no longer be cancelled
+            state_arc.worktree_creations.write().remove(&op_id_clone);
+            if creation.cancel.is_cancelled() {
+                undo_worktree_creation(&app_handle, &state_arc, &op_id_clone, &worktree_id);
+            }
         });
-        info!("[create_worktree] spawned background thread for copy_gitignored_files");
+        info!("[create_worktree] spawned background thread for submodules, copy and init");
+        Some(op_id)
+    } else {
+        // Nothing left to cancel: close the creation, undoing it if a cancel got in first
+        state.worktree_creations.write().remove(&op_id);
+        if creation.cancel.is_cancelled() {
+            undo_worktree_creation(app, state, &op_id, &wt.id);
+            return Err("Worktree creation cancelled".to_string());
+        }
+        None
+    };
+
+    info!("[create_worktree] TOTAL took {:?}", total_start.elapsed());
+    Ok(CreatedWorktree {
+        worktree: wt,
+        creation_op_id,
+    })
+}
+
+/// A newly created worktree, with the ID of its creation operation while the
+/// file copy or init command is still running in the background
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedWorktree {
+    #[serde(flatten)]
+    pub worktree: Worktree,
+    /// Pass to `cancel_worktree_creation` to abort and undo the creation
+    pub creation_op_id: Option<String>,
+}
+
+/// Abort an in-flight worktree creation: stop the file copy and kill the init command.
+/// The background thread then removes the partial worktree and emits
+/// `worktree-create-cancelled`. Returns false if the creation already finished.
+#[tauri::command]
+fn cancel_worktree_creation(state: State<'_, Arc<AppState>>, op_id: &str) -> bool {
+    use std::sync::atomic::Ordering;
+
+    // Flag the creation while holding the read lock: whoever finishes it removes it under
+    // the write lock before checking the flag, so a cancel reported here is never missed
+    let creations = state.worktree_creations.read();
+    let Some(creation) = creations.get(op_id).cloned() else {
+        return false;
+    };
+    creation.cancel.cancel();
+    drop(creations);
+
+    let Some(worktree_id) = creation.worktree_id.get() else {
+        info!("[cancel_worktree_creation] Cancelling {} before its worktree exists", op_id);
+        return true;
+    };
+    info!("[cancel_worktree_creation] Cancelling {} ({})", op_id, worktree_id);
+    if let Some(cancel) = state.copy_cancellations.read().get(worktree_id) {
+        cancel.store(true, Ordering::SeqCst);
+    }
+    if let Some(pid) = *creation.init_pid.lock() {
+        kill_init_command(pid);
     }
+    true
+}
 
-    // Start file watcher for this worktree
-    let start = Instant::now();
-    watcher::watch_worktree(app.clone(), wt.id.clone(), wt.path.clone());
-    info!("[create_worktree] watch_worktree took {:?}", start.elapsed());
+/// Kill a one-shot init command along with anything it spawned
+fn kill_init_command(pid: u32) {
+    #[cfg(unix)]
+    pty::signal_process_group(pid, libc::SIGKILL);
+    #[cfg(not(unix))]
+    info!("[worktree_init] Can't kill init command {} on this platform", pid);
+}
+
+/// Undo a cancelled worktree creation: stop its watcher and init command, remove the
+/// git worktree and its directory, and drop it from the persisted state.
+fn undo_worktree_creation(app: &AppHandle, state: &AppState, op_id: &str, worktree_id: &str) {
+    info!("[create_worktree] Undoing cancelled creation of {}", worktree_id);
+    watcher::stop_watching(worktree_id);
+
+    let init_status = state.init_status.write().remove(worktree_id);
+    if let Some(state::InitStatus::Persistent { pty_id }) = init_status {
+        let _ = pty::kill_pty(state, &pty_id);
+    }
+    command_log::remove_init_log(worktree_id);
+
+    if let Some((project_path, wt)) = state.remove_worktree(worktree_id) {
+        if let Err(e) = git::force_remove_worktree(Path::new(&project_path), Path::new(&wt.path)) {
+            info!("[create_worktree] git worktree remove failed: {}", e);
+        }
+        if Path::new(&wt.path).exists() {
+            let _ = std::fs::remove_dir_all(&wt.path);
+        }
+    }
+
+    let _ = app.emit("worktree-create-cancelled", serde_json::json!({
+        "opId": op_id,
+        "worktreeId": worktree_id,
+    }));
+}
+
+/// Payload for the worktree-init-complete event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorktreeInitComplete {
+    worktree_id: String,
+    exit_code: Option<i32>,
+    duration_ms: u64,
+}
+
+/// Run a worktree's init command. One-shot commands block until they exit, so call
+/// this from a background thread. Persistent commands are spawned as a PTY session
+/// (registered with the cleanup PID file like any other) and left running.
+/// When part of a cancellable creation, nothing is started once it's cancelled and
+/// the one-shot command's PID is recorded so it can be killed.
+fn run_worktree_init(
+    app: &AppHandle,
+    state: &AppState,
+    worktree_id: &str,
+    worktree_path: &str,
+    command: &str,
+    persistent: bool,
+    creation: Option<&state::PendingCreation>,
+) {
+    use state::InitStatus;
+
+    if creation.is_some_and(|c| c.cancel.is_cancelled()) {
+        info!("[worktree_init] creation cancelled, not running '{}'", command);
+        return;
+    }
 
-    drop(persisted);
+    if persistent {
+        let shell = pty::get_cached_user_shell();
+        match pty::spawn_pty(app, state, worktree_id, worktree_path, command, None, None, Some(&shell), None, Pane::Terminal) {
+            Ok(pty_id) => {
+                info!("[worktree_init] spawned persistent init '{}' as {}", command, pty_id);
+                state
+                    .init_status
+                    .write()
+                    .insert(worktree_id.to_string(), InitStatus::Persistent { pty_id: pty_id.clone() });
+                let _ = app.emit("worktree-init-started", serde_json::json!({
+                    "worktreeId": worktree_id,
+                    "command": command,
+                    "persistent": true,
+                    "ptyId": pty_id,
+                }));
+            }
+            Err(e) => {
+                log::warn!("[worktree_init] failed to spawn persistent init '{}': {}", command, e);
+                state.init_status.write().insert(
+                    worktree_id.to_string(),
+                    InitStatus::Failed { exit_code: None, duration_ms: 0 },
+                );
+                let _ = app.emit("worktree-init-complete", WorktreeInitComplete {
+                    worktree_id: worktree_id.to_string(),
+                    exit_code: None,
+                    duration_ms: 0,
+                });
+            }
+        }
+        return;
+    }
+
+    state.init_status.write().insert(worktree_id.to_string(), InitStatus::Running);
+    let _ = app.emit("worktree-init-started", serde_json::json!({
+        "worktreeId": worktree_id,
+        "command": command,
+        "persistent": false,
+    }));
 
     let start = Instant::now();
-    state.save().map_err(map_err)?;
-    info!("[create_worktree] state.save took {:?}", start.elapsed());
+    let mut cmd = std::process::Command::new(pty::get_cached_user_shell());
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(worktree_path)
+        .env("PATH", pty::get_cached_user_path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+    // Own process group, so cancelling kills everything the command started
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let output = cmd.spawn().and_then(|child| {
+        if let Some(creation) = creation {
+            *creation.init_pid.lock() = Some(child.id());
+            // Cancelled between the check above and the spawn
+            if creation.cancel.is_cancelled() {
+                kill_init_command(child.id());
+            }
+        }
+        let output = child.wait_with_output();
+        if let Some(creation) = creation {
+            *creation.init_pid.lock() = None;
+        }
+        output
+    });
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let exit_code = match &output {
+        Ok(out) => {
+            let mut log = out.stdout.clone();
+            log.extend_from_slice(&out.stderr);
+            if let Err(e) = command_log::write_init_log(worktree_id, &log) {
+                log::warn!("[worktree_init] failed to write init log: {}", e);
+            }
+            if !out.status.success() {
+                log::warn!(
+                    "[worktree_init] '{}' failed: {}",
+                    command,
+                    String::from_utf8_lossy(&out.stderr).trim()
+                );
+            }
+            out.status.code()
+        }
+        Err(e) => {
+            log::warn!("[worktree_init] failed to run '{}': {}", command, e);
+            None
+        }
+    };
+    info!("[worktree_init] '{}' finished with {:?} in {}ms", command, exit_code, duration_ms);
 
-    info!("[create_worktree] TOTAL took {:?}", total_start.elapsed());
-    Ok(wt)
+    let status = if exit_code == Some(0) {
+        InitStatus::Succeeded { duration_ms }
+    } else {
+        InitStatus::Failed { exit_code, duration_ms }
+    };
+    state.init_status.write().insert(worktree_id.to_string(), status);
+
+    let _ = app.emit("worktree-init-complete", WorktreeInitComplete {
+        worktree_id: worktree_id.to_string(),
+        exit_code,
+        duration_ms,
+    });
+}
+
+/// Progress of copying gitignored files into a new worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeCopyProgress {
+    pub worktree_id: String,
+    pub files_copied: usize,
+    pub total: usize,
+    pub current_path: String,
+}
+
+/// Stop an in-flight gitignored-file copy for a worktree.
+/// Returns false if no copy is running.
+#[tauri::command]
+fn cancel_worktree_copy(state: State<'_, Arc<AppState>>, worktree_id: &str) -> bool {
+    match state.copy_cancellations.read().get(worktree_id) {
+        Some(cancel) => {
+            cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+fn get_worktree_init_status(state: State<'_, Arc<AppState>>, worktree_id: &str) -> Option<state::InitStatus> {
+    state.init_status.read().get(worktree_id).cloned()
+}
+
+/// Last `lines` lines of a worktree's init command output, oldest first.
+/// Reads the PTY ring buffer for persistent commands and the log file for one-shot ones.
+#[tauri::command]
+fn get_command_log(state: State<'_, Arc<AppState>>, worktree_id: &str, lines: usize) -> Result<Vec<String>> {
+    let status = state.init_status.read().get(worktree_id).cloned();
+    match status {
+        Some(state::InitStatus::Persistent { pty_id }) => {
+            Ok(command_log::pty_tail(&pty_id, lines).unwrap_or_default())
+        }
+        // One-shot output is written to the log file once the command finishes
+        Some(state::InitStatus::Running) => Ok(Vec::new()),
+        Some(_) => command_log::init_log_tail(worktree_id, lines).map_err(map_err),
+        None => Err(format!("No init command has run for worktree: {}", worktree_id)),
+    }
+}
+
+/// Where a worktree with the given name would be created, before creating it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedWorktreePath {
+    pub path: String,
+    /// Something already exists at `path`
+    pub exists: bool,
+}
+
+#[tauri::command]
+fn resolve_worktree_path(project_path: &str, name: &str) -> Result<ResolvedWorktreePath> {
+    let cfg = config::load_config_for_project(Some(project_path));
+    let normalized = worktree::normalize_worktree_name(name)?;
+    let path = worktree::resolve_worktree_path(
+        cfg.worktree.directory.as_deref(),
+        cfg.worktree.layout,
+        Path::new(project_path),
+        &normalized,
+    )
+    .map_err(map_err)?;
+    Ok(ResolvedWorktreePath {
+        exists: path.exists(),
+        path: path.to_string_lossy().to_string(),
+    })
 }
 
 #[tauri::command]
@@ -226,19 +671,23 @@ fn list_worktrees(state: State<'_, Arc<AppState>>, project_path: &str) -> Result
 
 #[tauri::command]
 fn delete_worktree(state: State<'_, Arc<AppState>>, worktree_id: &str) -> Result<()> {
-    let mut persisted = state.persisted.write();
-
-    // Find the project containing this worktree
-    for project in &mut persisted.projects {
-        if project.worktrees.iter().any(|w| w.id == worktree_id) {
-            worktree::delete_worktree(project, worktree_id).map_err(map_err)?;
-            drop(persisted);
-            state.save().map_err(map_err)?;
-            return Ok(());
-        }
+    // Don't keep copying files into a worktree that's going away
+    if let Some(cancel) = state.copy_cancellations.read().get(worktree_id) {
+        cancel.store(true, std::sync::atomic::Ordering::SeqCst);
     }
 
-    Err(format!("Worktree not found: {}", worktree_id))
+    // Find the project containing this worktree
+    state.mutate(|persisted| {
+        let project = persisted
+            .projects
+            .iter_mut()
+            .find(|p| p.worktrees.iter().any(|w| w.id == worktree_id))
+            .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+        worktree::delete_worktree(project, worktree_id).map_err(map_err)
+    })?;
+    state.init_status.write().remove(worktree_id);
+    command_log::remove_init_log(worktree_id);
+    Ok(())
 }
 
 #[tauri::command]
@@ -248,6 +697,25 @@ fn check_worktree_delete_status(worktree_path: &str, project_path: Option<String
     git::check_worktree_delete_status(path, &cfg.worktree.base_branch).map_err(map_err)
 }
 
+#[tauri::command]
+fn is_worktree_locked(worktree_path: &str) -> Option<String> {
+    git::is_worktree_locked(Path::new(worktree_path))
+}
+
+#[tauri::command]
+fn lock_worktree(worktree_path: &str, reason: Option<String>) -> GitResult<()> {
+    let path = Path::new(worktree_path);
+    git::ensure_writable(path)?;
+    git::lock_worktree(path, reason.as_deref())
+}
+
+#[tauri::command]
+fn unlock_worktree(worktree_path: &str) -> GitResult<()> {
+    let path = Path::new(worktree_path);
+    git::ensure_writable(path)?;
+    git::unlock_worktree(path)
+}
+
 #[tauri::command]
 fn execute_delete_worktree_workflow(
     app: AppHandle,
@@ -263,7 +731,6 @@ fn execute_delete_worktree_workflow(
         for project in &persisted.projects {
             if let Some(worktree) = project.worktrees.iter().find(|w| w.id == worktree_id) {
                 found = Some((
-                    worktree.name.clone(),
                     worktree.path.clone(),
                     worktree.branch.clone(),
                     project.path.clone(),
@@ -290,7 +757,7 @@ fn execute_delete_worktree_workflow(
 
     let worktree_id = worktree_id.to_string();
     let app_state = Arc::clone(&*state);
-    let (worktree_name, worktree_path, branch_name, project_path) = worktree_info;
+    let (worktree_path, branch_name, project_path) = worktree_info;
 
     // Spawn background thread to avoid blocking UI
     std::thread::spawn(move || {
@@ -313,7 +780,7 @@ fn execute_delete_worktree_workflow(
             },
         );
         let project_path = Path::new(&project_path);
-        if let Err(e) = git::delete_worktree(project_path, &worktree_name) {
+        if let Err(e) = git::delete_worktree(project_path, Path::new(&worktree_path)) {
             let _ = app.emit(
                 "delete-worktree-progress",
                 DeleteWorktreeProgress {
@@ -339,6 +806,7 @@ fn execute_delete_worktree_workflow(
                 info!("Failed to remove worktree directory: {}", e);
             }
         }
+        worktree::remove_empty_layout_dirs(worktree_path, &branch_name);
 
         // Step 3: Delete local branch if requested
         if options.delete_branch {
@@ -364,19 +832,8 @@ fn execute_delete_worktree_workflow(
                 message: "Saving...".to_string(),
             },
         );
-        {
-            let mut persisted = app_state.persisted.write();
-            for project in &mut persisted.projects {
-                if let Some(idx) = project.worktrees.iter().position(|w| w.id == worktree_id) {
-                    project.worktrees.remove(idx);
-                    break;
-                }
-            }
-        }
-
-        if let Err(e) = app_state.save() {
-            info!("Failed to save state after worktree deletion: {}", e);
-        }
+        app_state.remove_worktree(&worktree_id);
+        git::leave_inspect_mode(&worktree_id);
 
         // Emit completion
         let _ = app.emit(
@@ -400,29 +857,141 @@ fn execute_delete_worktree_workflow(
     info!("[execute_delete_worktree_workflow] spawned background thread");
 }
 
+/// A worktree considered by prune_merged_worktrees
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrunedWorktree {
+    worktree_id: String,
+    name: String,
+    branch: String,
+    reason: git::PruneReason,
+    pruned: bool,
+    /// Why the worktree was left in place, if it wasn't pruned
+    skipped_reason: Option<String>,
+}
+
+/// Remove worktrees whose branch is merged into the base branch or whose upstream was
+/// deleted. Worktrees with uncommitted changes, locked worktrees and worktrees with an
+/// operation in progress are never removed; they're returned with a skipped reason.
+/// Checking every branch and removing worktrees can take a while, so it runs on
+/// the blocking pool.
+#[tauri::command]
+async fn prune_merged_worktrees(state: State<'_, Arc<AppState>>, project_path: String) -> Result<Vec<PrunedWorktree>> {
+    let state = Arc::clone(&state);
+    tauri::async_runtime::spawn_blocking(move || prune_merged_worktrees_blocking(&state, &project_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn prune_merged_worktrees_blocking(state: &AppState, project_path: &str) -> Result<Vec<PrunedWorktree>> {
+    let cfg = config::load_config_for_project(Some(project_path));
+    let repo_path = Path::new(project_path);
+    let target_branch = git::target_branch(repo_path, &cfg.worktree.base_branch).map_err(map_err)?;
+
+    let worktrees: Vec<Worktree> = {
+        let persisted = state.persisted.read();
+        persisted
+            .projects
+            .iter()
+            .find(|p| p.path == project_path)
+            .ok_or_else(|| format!("Project not found: {}", project_path))?
+            .worktrees
+            .clone()
+    };
+
+    let mut results = Vec::new();
+    for wt in worktrees {
+        let reason = match git::prune_reason(repo_path, &wt.branch, &target_branch) {
+            Ok(Some(reason)) => reason,
+            Ok(None) => continue,
+            Err(e) => {
+                info!("[prune_merged_worktrees] Skipping {}: {}", wt.name, e);
+                continue;
+            }
+        };
+
+        let mut result = PrunedWorktree {
+            worktree_id: wt.id.clone(),
+            name: wt.name.clone(),
+            branch: wt.branch.clone(),
+            reason,
+            pruned: false,
+            skipped_reason: None,
+        };
+
+        let worktree_path = Path::new(&wt.path);
+        if git::has_uncommitted_changes_at_path(worktree_path).unwrap_or(true) {
+            result.skipped_reason = Some("Uncommitted changes".to_string());
+            results.push(result);
+            continue;
+        }
+        if git::is_worktree_locked(worktree_path).is_some() {
+            result.skipped_reason = Some("Worktree is locked".to_string());
+            results.push(result);
+            continue;
+        }
+        let _op_guard = match git::lock_worktree_op(&wt.id) {
+            Ok(guard) => guard,
+            Err(e) => {
+                result.skipped_reason = Some(e.to_string());
+                results.push(result);
+                continue;
+            }
+        };
+
+        // Stop everything running in the worktree before removing it
+        watcher::stop_watching(&wt.id);
+        let pty_ids: Vec<String> = state
+            .pty_sessions
+            .read()
+            .iter()
+            .filter(|(_, session)| session.worktree_id == wt.id)
+            .map(|(pty_id, _)| pty_id.clone())
+            .collect();
+        for pty_id in pty_ids {
+            let _ = pty::kill_pty(state, &pty_id);
+        }
+
+        let removed = state.mutate(|persisted| match persisted.projects.iter_mut().find(|p| p.path == project_path) {
+            Some(project) => worktree::delete_worktree(project, &wt.id).map_err(map_err),
+            None => Err(format!("Project not found: {}", project_path)),
+        });
+        match removed {
+            Ok(()) => {
+                info!("[prune_merged_worktrees] Pruned {} ({:?})", wt.name, reason);
+                state.init_status.write().remove(&wt.id);
+                result.pruned = true;
+            }
+            Err(e) => result.skipped_reason = Some(e),
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
 /// Remove a worktree from state by its path (used when worktree folder is deleted externally)
 #[tauri::command]
 fn remove_stale_worktree(state: State<'_, Arc<AppState>>, worktree_path: &str) -> Result<()> {
-    let mut persisted = state.persisted.write();
-
-    for project in &mut persisted.projects {
-        if let Some(idx) = project.worktrees.iter().position(|w| w.path == worktree_path) {
-            let worktree = &project.worktrees[idx];
-            info!(
-                "[remove_stale_worktree] Removing '{}' from project '{}'",
-                worktree.name, project.name
-            );
+    state.mutate(|persisted| {
+        for project in &mut persisted.projects {
+            if let Some(idx) = project.worktrees.iter().position(|w| w.path == worktree_path) {
+                let worktree = &project.worktrees[idx];
+                info!(
+                    "[remove_stale_worktree] Removing '{}' from project '{}'",
+                    worktree.name, project.name
+                );
 
-            // Stop watching this worktree
-            watcher::stop_watching(&worktree.id);
+                // Stop watching this worktree
+                watcher::stop_watching(&worktree.id);
+                git::leave_inspect_mode(&worktree.id);
 
-            // Remove from state (don't try to delete files - they're already gone)
-            project.worktrees.remove(idx);
-            drop(persisted);
-            state.save().map_err(map_err)?;
-            return Ok(());
+                // Remove from state (don't try to delete files - they're already gone)
+                project.worktrees.remove(idx);
+                return;
+            }
         }
-    }
+    });
 
     // Not found is OK - might have already been cleaned up
     Ok(())
@@ -437,8 +1006,75 @@ fn spawn_main(
     cols: Option<u16>,
     rows: Option<u16>,
 ) -> Result<String> {
-    // Find worktree info and parent project path
-    let (worktree_path, worktree_name, worktree_branch, project_path) = {
+    spawn_pane(&app, &state, worktree_id, Pane::Main, cols, rows)
+}
+
+#[tauri::command]
+fn spawn_terminal(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    worktree_id: &str,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String> {
+    spawn_pane(&app, &state, worktree_id, Pane::Terminal, cols, rows)
+}
+
+/// A pane's PTY session as returned by `focus_session`
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FocusedSession {
+    pty_id: String,
+    /// The session was started by this call rather than already running
+    spawned: bool,
+}
+
+/// Session for a worktree pane, spawning it if the pane has no live shell yet.
+/// With `terminal.lazySpawn` on, the frontend uses this when a terminal pane is
+/// first focused instead of spawning every worktree's terminal up front.
+#[tauri::command]
+fn focus_session(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    worktree_id: &str,
+    pane: Pane,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<FocusedSession> {
+    if let Some(pty_id) = state.pane_session(worktree_id, pane) {
+        return Ok(FocusedSession { pty_id, spawned: false });
+    }
+    let pty_id = spawn_pane(&app, &state, worktree_id, pane, cols, rows)?;
+    Ok(FocusedSession { pty_id, spawned: true })
+}
+
+/// Spawn a pane's PTY session and record it as the pane's live session.
+/// The PTY's process is tracked in the PID file like any other session.
+fn spawn_pane(
+    app: &AppHandle,
+    state: &AppState,
+    worktree_id: &str,
+    pane: Pane,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String> {
+    let pty_id = match pane {
+        Pane::Main => spawn_main_session(app, state, worktree_id, cols, rows)?,
+        Pane::Terminal => spawn_terminal_session(app, state, worktree_id, cols, rows)?,
+    };
+    state.set_pane_session(worktree_id, pane, &pty_id);
+    Ok(pty_id)
+}
+
+fn spawn_main_session(
+    app: &AppHandle,
+    state: &AppState,
+    worktree_id: &str,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String> {
+    // Find worktree info and parent project path
+    let (worktree_path, worktree_name, worktree_branch, worktree_command, project_path) = {
         let persisted = state.persisted.read();
         let mut found = None;
 
@@ -448,6 +1084,7 @@ fn spawn_main(
                     worktree.path.clone(),
                     worktree.name.clone(),
                     worktree.branch.clone(),
+                    worktree.command_line(),
                     project.path.clone(),
                 ));
                 break;
@@ -460,10 +1097,11 @@ fn spawn_main(
     // Load config with project-specific overrides
     let cfg = config::load_config_for_project(Some(&project_path));
 
-    // Expand template variables in command, or use shell if not configured
-    let command = match &cfg.main.command {
+    // Expand template variables in the worktree's own command (falling back to
+    // main.command), or use shell if neither is configured
+    let command = match worktree_command.as_ref().or(cfg.main.command.as_ref()) {
         Some(cmd) => {
-            let ctx = template::TemplateContext::new(&project_path)
+            let ctx = template::TemplateContext::for_project(&project_path)
                 .with_branch(&worktree_branch)
                 .with_worktree_name(&worktree_name);
             template::expand_template(cmd, &ctx).map_err(map_err)?
@@ -471,13 +1109,12 @@ fn spawn_main(
         None => "shell".to_string(),
     };
 
-    pty::spawn_pty(&app, &state, worktree_id, &worktree_path, &command, cols, rows, None, None).map_err(map_err)
+    pty::spawn_pty(app, state, worktree_id, &worktree_path, &command, cols, rows, None, None, Pane::Main).map_err(map_err)
 }
 
-#[tauri::command]
-fn spawn_terminal(
-    app: AppHandle,
-    state: State<'_, Arc<AppState>>,
+fn spawn_terminal_session(
+    app: &AppHandle,
+    state: &AppState,
     worktree_id: &str,
     cols: Option<u16>,
     rows: Option<u16>,
@@ -504,7 +1141,7 @@ fn spawn_terminal(
         }).ok_or_else(|| format!("Worktree or project not found: {}", worktree_id))?
     };
 
-    pty::spawn_pty(&app, &state, worktree_id, &path, "shell", cols, rows, None, None).map_err(map_err)
+    pty::spawn_pty(app, state, worktree_id, &path, "shell", cols, rows, None, None, Pane::Terminal).map_err(map_err)
 }
 
 #[tauri::command]
@@ -542,7 +1179,7 @@ fn spawn_action(
     // Get user's shell to run the command through
     let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
 
-    pty::spawn_pty(&app, &state, worktree_id, &worktree_path, &command, cols, rows, Some(&shell), None).map_err(map_err)
+    pty::spawn_pty(&app, &state, worktree_id, &worktree_path, &command, cols, rows, Some(&shell), None, Pane::Terminal).map_err(map_err)
 }
 
 #[tauri::command]
@@ -562,10 +1199,47 @@ fn watch_merge_state(
             .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?
     };
 
+    // Merges started by `execute_merge_workflow` start this watcher themselves
+    // while they still hold the worktree; watching is idempotent, so this is
+    // only needed for merges started elsewhere (e.g. by an action)
     watcher::watch_merge_state(app, worktree_id.to_string(), project_path);
     Ok(())
 }
 
+#[tauri::command]
+fn list_conflicts(worktree_path: &str) -> GitResult<Vec<git::ConflictFile>> {
+    git::list_conflicts(Path::new(worktree_path))
+}
+
+/// Returns the conflicts still unresolved. Concluding the merge is left to `continue_merge`.
+#[tauri::command]
+fn resolve_conflict(worktree_path: &str, file: &str, side: git::Side) -> GitResult<Vec<git::ConflictFile>> {
+    let path = Path::new(worktree_path);
+    git::ensure_writable(path)?;
+    git::resolve_conflict(path, file, side)?;
+    git::list_conflicts(path)
+}
+
+#[tauri::command]
+fn mark_resolved(worktree_path: &str, file: &str) -> GitResult<Vec<git::ConflictFile>> {
+    let path = Path::new(worktree_path);
+    git::ensure_writable(path)?;
+    git::mark_resolved(path, file)?;
+    git::list_conflicts(path)
+}
+
+/// Conclude the merge (or continue the rebase) once every conflict is resolved, so
+/// the merge/rebase watcher sees it complete. Returns the conflicts still left, which
+/// after a rebase continues may be the next commit's.
+#[tauri::command]
+fn continue_merge(worktree_id: &str, worktree_path: &str) -> GitResult<Vec<git::ConflictFile>> {
+    let _op_guard = git::lock_worktree_op(worktree_id)?;
+    let path = Path::new(worktree_path);
+    git::ensure_writable(path)?;
+    git::continue_merge(path)?;
+    git::list_conflicts(path)
+}
+
 #[tauri::command]
 fn stop_merge_watcher(worktree_id: &str) {
     watcher::stop_merge_watcher(worktree_id);
@@ -648,7 +1322,7 @@ fn spawn_task(
         .ok_or_else(|| format!("Task not found: {}", task_name))?;
 
     // Expand template variables in command
-    let mut ctx = template::TemplateContext::new(&project_path).with_branch(&branch);
+    let mut ctx = template::TemplateContext::for_project(&project_path).with_branch(&branch);
     if let Some(name) = worktree_name {
         ctx = ctx.with_worktree_name(name);
     }
@@ -665,7 +1339,7 @@ fn spawn_task(
         .collect();
     let env_vars = if expanded_env.is_empty() { None } else { Some(&expanded_env) };
 
-    pty::spawn_pty(&app, &state, entity_id, &entity_path, &command, cols, rows, task.shell.as_deref(), env_vars)
+    pty::spawn_pty(&app, &state, entity_id, &entity_path, &command, cols, rows, task.shell.as_deref(), env_vars, Pane::Terminal)
         .map_err(map_err)
 }
 
@@ -719,7 +1393,7 @@ fn get_task_urls(
         .ok_or_else(|| format!("Task not found: {}", task_name))?;
 
     // Build template context
-    let ctx = TemplateContext::new(&project_path).with_branch(&branch);
+    let ctx = TemplateContext::for_project(&project_path).with_branch(&branch);
 
     // Render each URL template, keeping the name
     let urls: Vec<NamedUrl> = task
@@ -760,14 +1434,14 @@ fn spawn_project_shell(
     // Expand template variables in command, or use shell if not configured
     let command = match &cfg.main.command {
         Some(cmd) => {
-            let ctx = template::TemplateContext::new(&project_path);
+            let ctx = template::TemplateContext::for_project(&project_path);
             template::expand_template(cmd, &ctx).map_err(map_err)?
         }
         None => "shell".to_string(),
     };
 
     // Use project_id as the "worktree_id" for PTY tracking purposes
-    pty::spawn_pty(&app, &state, project_id, &project_path, &command, cols, rows, None, None).map_err(map_err)
+    pty::spawn_pty(&app, &state, project_id, &project_path, &command, cols, rows, None, None, Pane::Main).map_err(map_err)
 }
 
 #[tauri::command]
@@ -789,11 +1463,9 @@ fn spawn_scratch_terminal(
         }
     };
 
-    // Get the user's shell (scratch terminals just run a shell, no main command)
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-
-    // Use scratch_id as the entity ID for PTY tracking purposes
-    pty::spawn_pty(&app, &state, scratch_id, &path, &shell, cols, rows, None, None).map_err(map_err)
+    // Scratch terminals just run the user's shell, started like a drawer shell
+    // (so `terminal.loginShell` applies). scratch_id is the entity ID for PTY tracking.
+    pty::spawn_pty(&app, &state, scratch_id, &path, "shell", cols, rows, None, None, Pane::Terminal).map_err(map_err)
 }
 
 #[tauri::command]
@@ -815,7 +1487,7 @@ fn spawn_shell(
         }
     };
 
-    pty::spawn_pty(&app, &state, entity_id, &path, "shell", cols, rows, None, None).map_err(map_err)
+    pty::spawn_pty(&app, &state, entity_id, &path, "shell", cols, rows, None, None, Pane::Terminal).map_err(map_err)
 }
 
 /// Spawn a PTY running a specific command (for opening editors in drawer/tab)
@@ -831,7 +1503,7 @@ fn spawn_command(
 ) -> Result<String> {
     // Run through user's shell so quoted paths and shell features work correctly
     let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-    pty::spawn_pty(&app, &state, entity_id, directory, command, cols, rows, Some(&shell), None).map_err(map_err)
+    pty::spawn_pty(&app, &state, entity_id, directory, command, cols, rows, Some(&shell), None, Pane::Terminal).map_err(map_err)
 }
 
 #[tauri::command]
@@ -849,11 +1521,59 @@ fn pty_interrupt(state: State<'_, Arc<AppState>>, pty_id: &str) -> Result<()> {
     pty::interrupt_pty(&state, pty_id).map_err(map_err)
 }
 
+/// Send a named signal (TERM, INT, HUP, USR1, USR2 or KILL) to a PTY session
+#[tauri::command]
+fn signal_session(state: State<'_, Arc<AppState>>, session_id: &str, signal: &str) -> Result<()> {
+    pty::signal_session(&state, session_id, signal).map_err(map_err)
+}
+
+/// Exclude (or re-include) a session's process from crash-recovery cleanup
+#[tauri::command]
+fn set_session_detached(state: State<'_, Arc<AppState>>, session_id: &str, detached: bool) -> Result<()> {
+    pty::set_session_detached(&state, session_id, detached).map_err(map_err)
+}
+
+/// Trial-run a command (e.g. `main.command`) to check it launches
+#[tauri::command]
+async fn test_command(command: String, args: Vec<String>, cwd: String) -> pty::TestResult {
+    tauri::async_runtime::spawn_blocking(move || pty::test_command(&command, &args, &cwd))
+        .await
+        .unwrap_or_else(|e| pty::TestResult {
+            spawned: false,
+            exit_code: None,
+            output_preview: String::new(),
+            error: Some(e.to_string()),
+        })
+}
+
 #[tauri::command]
 fn pty_kill(state: State<'_, Arc<AppState>>, pty_id: &str) -> Result<()> {
     pty::kill_pty(&state, pty_id).map_err(map_err)
 }
 
+/// CPU and memory use of a session's process tree (children included unless
+/// `include_children` is false). Blocks briefly to sample CPU, so it runs on the
+/// blocking pool.
+#[tauri::command]
+async fn session_stats(
+    state: State<'_, Arc<AppState>>,
+    pty_id: String,
+    include_children: Option<bool>,
+) -> Result<pty::SessionStats> {
+    let state = Arc::clone(&state);
+    tauri::async_runtime::spawn_blocking(move || {
+        pty::session_stats(&state, &pty_id, include_children.unwrap_or(true)).map_err(map_err)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Whether a session looks busy, idle or waiting for input. Heuristic; see `pty::Activity`.
+#[tauri::command]
+fn session_activity(state: State<'_, Arc<AppState>>, session_id: &str) -> Result<pty::Activity> {
+    pty::session_activity(&state, session_id).map_err(map_err)
+}
+
 #[tauri::command]
 fn pty_force_kill(state: State<'_, Arc<AppState>>, pty_id: &str) -> Result<()> {
     pty::force_kill_pty(&state, pty_id).map_err(map_err)
@@ -865,6 +1585,60 @@ fn get_config(project_path: Option<String>) -> config::ConfigResult {
     config::load_config_with_errors(project_path.as_deref())
 }
 
+#[tauri::command]
+fn validate_config(project_path: Option<String>) -> Vec<config::ConfigIssue> {
+    config::validate_config(&config::load_config_for_project(project_path.as_deref()))
+}
+
+#[tauri::command]
+fn list_command_presets(project_path: Option<String>) -> Vec<config::CommandPreset> {
+    let cfg = config::load_config_for_project(project_path.as_deref());
+    config::list_command_presets(&cfg.command_presets)
+}
+
+#[tauri::command]
+fn read_config_raw() -> Result<String> {
+    config::read_config_raw()
+}
+
+#[tauri::command]
+fn write_config_raw(app: AppHandle, content: &str) -> Result<()> {
+    config::write_config_raw(content)?;
+    // Reload immediately rather than waiting on the (debounced) file watcher
+    let _ = app.emit("config-changed", watcher::ConfigChanged { project_path: None });
+    Ok(())
+}
+
+/// Config section holding a pane's font settings (the terminal pane is the drawer)
+fn font_config_section(pane: Pane) -> &'static str {
+    match pane {
+        Pane::Main => "main",
+        Pane::Terminal => "drawer",
+    }
+}
+
+/// Set a pane's font size in the global config, clamped to a sane range,
+/// keeping the file's comments. Returns the size saved.
+#[tauri::command]
+fn set_font_size(app: AppHandle, pane: Pane, size: u16) -> Result<u16> {
+    let size = config::clamp_font_size(size);
+    config::set_config_value(&[font_config_section(pane), "fontSize"], serde_json::json!(size))?;
+    let _ = app.emit("config-changed", watcher::ConfigChanged { project_path: None });
+    Ok(size)
+}
+
+/// Set a pane's font family in the global config, keeping the file's comments
+#[tauri::command]
+fn set_font_family(app: AppHandle, pane: Pane, family: String) -> Result<()> {
+    let family = family.trim();
+    if family.is_empty() {
+        return Err("Font family can't be empty".to_string());
+    }
+    config::set_config_value(&[font_config_section(pane), "fontFamily"], serde_json::json!(family))?;
+    let _ = app.emit("config-changed", watcher::ConfigChanged { project_path: None });
+    Ok(())
+}
+
 #[tauri::command]
 fn watch_config(app: AppHandle, project_path: Option<String>) {
     watcher::watch_config(app, project_path);
@@ -881,6 +1655,13 @@ fn get_mappings() -> mappings::MappingsResult {
     mappings::load_mappings()
 }
 
+/// Menu accelerators for the current platform, keyed by action ID, with the config's
+/// "keybindings" applied. Invalid bindings have already been dropped in favour of defaults.
+#[tauri::command]
+fn get_keybindings(project_path: Option<String>) -> std::collections::BTreeMap<String, String> {
+    config::load_config_for_project(project_path.as_deref()).mappings.accelerators()
+}
+
 #[tauri::command]
 fn watch_mappings(app: AppHandle) {
     watcher::watch_mappings(app);
@@ -950,9 +1731,18 @@ fn expand_action_prompt(
 
 // Git commands
 #[tauri::command]
-fn get_changed_files(worktree_path: &str) -> Result<Vec<FileChange>> {
-    let path = Path::new(worktree_path);
-    git::get_changed_files(path).map_err(map_err)
+async fn get_changed_files(worktree_path: String, project_path: Option<String>) -> GitResult<Vec<FileChange>> {
+    // Run off the main thread - status on a large tree can take a while
+    tauri::async_runtime::spawn_blocking(move || {
+        let cfg = config::load_config_for_project(project_path.as_deref());
+        git::get_changed_files(
+            Path::new(&worktree_path),
+            cfg.git.detect_mode_changes,
+            &git::GitLimits::timeout(cfg.git.timeout()),
+        )
+    })
+    .await
+    .map_err(|e| git::GitError::Other(e.to_string()))?
 }
 
 #[tauri::command]
@@ -965,18 +1755,421 @@ fn get_branch_info(
     git::get_branch_info(path, &cfg.worktree.base_branch).map_err(map_err)
 }
 
+/// Most worktrees refreshed at once by refresh_all_worktrees
+const MAX_REFRESH_THREADS: usize = 8;
+
+/// Git status of one worktree, as returned by refresh_all_worktrees
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeStatus {
+    pub worktree_id: String,
+    pub path: String,
+    /// False when the directory is gone; the other fields are then empty
+    pub exists: bool,
+    pub files: Vec<FileChange>,
+    pub branch_info: Option<state::BranchInfo>,
+    /// Set when git failed for an existing directory
+    pub error: Option<String>,
+}
+
+fn worktree_status(worktree_id: String, path: String, cfg: &config::Config) -> WorktreeStatus {
+    let mut status = WorktreeStatus {
+        worktree_id,
+        exists: Path::new(&path).is_dir(),
+        path,
+        files: Vec::new(),
+        branch_info: None,
+        error: None,
+    };
+    if !status.exists {
+        return status;
+    }
+    let dir = Path::new(&status.path);
+    match git::get_changed_files(dir, cfg.git.detect_mode_changes, &git::GitLimits::timeout(cfg.git.timeout())) {
+        Ok(files) => status.files = files,
+        Err(e) => status.error = Some(e.to_string()),
+    }
+    match git::get_branch_info(dir, &cfg.worktree.base_branch) {
+        Ok(info) => status.branch_info = Some(info),
+        Err(e) => status.error = status.error.or(Some(e.to_string())),
+    }
+    status
+}
+
+/// Whether a worktree can be deleted without losing anything, as returned by
+/// classify_worktrees
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeSafety {
+    pub id: String,
+    /// False when the directory was removed outside Shellflow; the git fields are then false
+    pub exists: bool,
+    pub has_uncommitted: bool,
+    pub is_merged: bool,
+    pub has_unpushed_commits: bool,
+    /// Has a live terminal session
+    pub is_running: bool,
+    /// Set when git failed for an existing directory
+    pub error: Option<String>,
+}
+
+/// Classify every worktree of a project as safe to delete or holding unsaved
+/// work, combining read-only git checks with live session state
+#[tauri::command]
+async fn classify_worktrees(state: State<'_, Arc<AppState>>, project_id: String) -> Result<Vec<WorktreeSafety>> {
+    let worktrees: Vec<(String, String, Option<String>)> = {
+        let persisted = state.persisted.read();
+        let project = persisted
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+        project
+            .worktrees
+            .iter()
+            .map(|wt| (wt.id.clone(), wt.path.clone(), wt.base_branch.clone()))
+            .collect()
+    };
+    let running: HashSet<String> = state
+        .pty_sessions
+        .read()
+        .values()
+        .map(|session| session.worktree_id.clone())
+        .collect();
+
+    tauri::async_runtime::spawn_blocking(move || classify(worktrees, &running))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The git half of `classify_worktrees`, run off the async runtime
+fn classify(worktrees: Vec<(String, String, Option<String>)>, running: &HashSet<String>) -> Vec<WorktreeSafety> {
+    worktrees
+        .into_iter()
+        .map(|(id, path, base_branch)| {
+            let dir = Path::new(&path);
+            let mut safety = WorktreeSafety {
+                is_running: running.contains(&id),
+                id,
+                exists: dir.is_dir(),
+                has_uncommitted: false,
+                is_merged: false,
+                has_unpushed_commits: false,
+                error: None,
+            };
+            if !safety.exists {
+                return safety;
+            }
+            // Compare against the branch it was created from, if that still exists
+            let work_state = git::resolve_merge_target(dir, base_branch.as_deref())
+                .or_else(|_| git::resolve_merge_target(dir, None))
+                .and_then(|target| git::worktree_work_state(dir, &target));
+            match work_state {
+                Ok(work_state) => {
+                    safety.has_uncommitted = work_state.has_uncommitted;
+                    safety.is_merged = work_state.is_merged;
+                    safety.has_unpushed_commits = work_state.has_unpushed_commits;
+                }
+                Err(e) => safety.error = Some(e.to_string()),
+            }
+            safety
+        })
+        .collect()
+}
+
+/// Recompute the status of every non-suspended worktree in one call, e.g. when
+/// the window regains focus. Worktrees are processed on a bounded set of threads;
+/// results come back in project and worktree order.
+#[tauri::command]
+async fn refresh_all_worktrees(state: State<'_, Arc<AppState>>) -> Result<Vec<WorktreeStatus>> {
+    let projects: Vec<(String, Vec<(String, String)>)> = {
+        let persisted = state.persisted.read();
+        persisted
+            .projects
+            .iter()
+            .map(|project| {
+                let worktrees = project
+                    .worktrees
+                    .iter()
+                    .filter(|wt| !wt.suspended)
+                    .map(|wt| (wt.id.clone(), wt.path.clone()))
+                    .collect();
+                (project.path.clone(), worktrees)
+            })
+            .collect()
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let jobs: Vec<(usize, String, String, Arc<config::Config>)> = projects
+            .into_iter()
+            .flat_map(|(project_path, worktrees)| {
+                let cfg = Arc::new(config::load_config_for_project(Some(&project_path)));
+                worktrees.into_iter().map(move |(id, path)| (id, path, Arc::clone(&cfg)))
+            })
+            .enumerate()
+            .map(|(index, (id, path, cfg))| (index, id, path, cfg))
+            .collect();
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_REFRESH_THREADS)
+            .min(jobs.len());
+        let queue = parking_lot::Mutex::new(jobs.into_iter());
+        let results = parking_lot::Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let Some((index, id, path, cfg)) = queue.lock().next() else { break };
+                    let status = worktree_status(id, path, &cfg);
+                    results.lock().push((index, status));
+                });
+            }
+        });
+        let mut results = results.into_inner();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, status)| status).collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_branch_diff(worktree_path: &str, target_branch: &str) -> GitResult<Vec<FileChange>> {
+    let path = Path::new(worktree_path);
+    git::get_branch_diff(path, target_branch)
+}
+
+#[tauri::command]
+fn get_branch_file_diff(worktree_path: &str, target_branch: &str, file_path: &str) -> GitResult<String> {
+    let path = Path::new(worktree_path);
+    git::get_branch_file_diff(path, target_branch, file_path)
+}
+
+#[tauri::command]
+fn diff_worktrees(path_a: &str, path_b: &str) -> GitResult<Vec<FileChange>> {
+    git::diff_worktrees(Path::new(path_a), Path::new(path_b))
+}
+
+#[tauri::command]
+fn diff_worktrees_file(path_a: &str, path_b: &str, file_path: &str) -> GitResult<String> {
+    git::diff_worktrees_file(Path::new(path_a), Path::new(path_b), file_path)
+}
+
+/// Save the worktree's patch against `target_branch` to `out_path` byte for byte,
+/// returning its size
+#[tauri::command]
+fn export_worktree_patch(worktree_path: &str, target_branch: &str, out_path: &str) -> GitResult<u64> {
+    let path = Path::new(worktree_path);
+    let mut out = std::fs::File::create(out_path)?;
+    git::write_worktree_patch(path, target_branch, &mut out)
+}
+
+/// Write every worktree's patch against the project's base branch, plus a manifest of
+/// branches and base commits, into a tarball at `out_path`. Emits
+/// "export-bundle-progress" after each worktree.
+#[tauri::command]
+async fn export_project_bundle(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    project_id: String,
+    out_path: String,
+) -> Result<()> {
+    let project = {
+        let persisted = state.persisted.read();
+        persisted
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .cloned()
+            .ok_or_else(|| format!("Project not found: {}", project_id))?
+    };
+
+    // Run off the main thread - diffing every worktree can take a while
+    tauri::async_runtime::spawn_blocking(move || -> Result<()> {
+        let cfg = config::load_config_for_project(Some(&project.path));
+        let target_branch =
+            git::target_branch(Path::new(project.repo_path()), &cfg.worktree.base_branch).map_err(map_err)?;
+
+        let manifest = bundle::export_project_bundle(&project, &target_branch, Path::new(&out_path), &mut |progress| {
+            let _ = app.emit("export-bundle-progress", progress);
+        })?;
+        log::info!(
+            "[export_project_bundle] Wrote {} worktrees of {} to {}",
+            manifest.worktrees.len(),
+            project.name,
+            out_path
+        );
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Run git housekeeping (gc, worktree prune, remote prune) on a project's
+/// repository off the command thread, streaming output as "git-progress" with
+/// the project's ID. Worktrees that `git worktree prune` forgot are removed from
+/// the project too.
+#[tauri::command]
+async fn run_git_maintenance(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    project_path: String,
+    tasks: Vec<git::MaintenanceTask>,
+) -> Result<git::MaintenanceSummary> {
+    let (project_id, repo_path) = {
+        let persisted = state.persisted.read();
+        let project = persisted
+            .projects
+            .iter()
+            .find(|p| p.path == project_path)
+            .ok_or_else(|| format!("Project not found: {}", project_path))?;
+        (project.id.clone(), project.repo_path().to_string())
+    };
+    git::ensure_writable(Path::new(&repo_path)).map_err(map_err)?;
+
+    let timeout = config::load_config_for_project(Some(&project_path)).git.timeout();
+    let progress_app = app.clone();
+    let progress_id = project_id.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        let mut on_progress = |task: git::MaintenanceTask, line: &str| {
+            let _ = progress_app.emit(
+                "git-progress",
+                GitProgress {
+                    worktree_id: progress_id.clone(),
+                    operation: task.label().to_string(),
+                    message: line.to_string(),
+                },
+            );
+        };
+        git::run_git_maintenance(Path::new(&repo_path), &tasks, &git::GitLimits::timeout(timeout), &mut on_progress)
+    })
+    .await
+    .map_err(map_err)?
+    .map_err(map_err)?;
+
+    for worktree in state.remove_worktrees_at(&project_id, &summary.pruned_worktrees) {
+        info!("[run_git_maintenance] Removing pruned worktree '{}' from state", worktree.name);
+        watcher::stop_watching(&worktree.id);
+        git::leave_inspect_mode(&worktree.id);
+    }
+    Ok(summary)
+}
+
+/// Disk space used by each of a project's worktrees, skipping the shared git
+/// object store. Partial and final totals are emitted as "worktree-disk-usage"
+/// while the walk runs.
+#[tauri::command]
+async fn worktree_disk_usage(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    project_id: String,
+) -> Result<Vec<worktree::WorktreeUsage>> {
+    let worktrees: Vec<(String, String)> = {
+        let persisted = state.persisted.read();
+        let project = persisted
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+        project.worktrees.iter().map(|w| (w.id.clone(), w.path.clone())).collect()
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        worktrees
+            .iter()
+            .map(|(id, path)| {
+                worktree::worktree_disk_usage(id, Path::new(path), &mut |usage| {
+                    let _ = app.emit("worktree-disk-usage", usage);
+                })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Install hooks in this worktree only that signal merges and rebases finishing,
+/// chaining to any existing hooks
+#[tauri::command]
+fn install_worktree_hooks(worktree_path: &str) -> Result<()> {
+    git::ensure_writable(Path::new(worktree_path)).map_err(map_err)?;
+    git::install_worktree_hooks(Path::new(worktree_path))
+}
+
+#[tauri::command]
+fn uninstall_worktree_hooks(worktree_path: &str) -> Result<()> {
+    git::ensure_writable(Path::new(worktree_path)).map_err(map_err)?;
+    git::uninstall_worktree_hooks(Path::new(worktree_path))
+}
+
+#[tauri::command]
+fn get_submodule_status(worktree_path: &str) -> GitResult<Vec<git::SubmoduleStatus>> {
+    git::get_submodule_status(Path::new(worktree_path))
+}
+
+#[tauri::command]
+fn list_branches(project_path: &str) -> GitResult<git::BranchList> {
+    let path = Path::new(project_path);
+    git::list_branches(path)
+}
+
+/// The base a worktree's branch diff and merge checks compare against: the branch it
+/// was created from while that still exists, otherwise the configured `worktree.baseBranch`
+fn worktree_base_branch(state: &AppState, worktree_path: &str, configured: &config::BaseBranch) -> config::BaseBranch {
+    let recorded = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .flat_map(|p| p.worktrees.iter())
+        .find(|w| w.path == worktree_path)
+        .and_then(|w| w.base_branch.clone());
+    match recorded {
+        Some(name) if git::branch_exists(Path::new(worktree_path), &name).unwrap_or(false) => {
+            config::BaseBranch::Named { name }
+        }
+        _ => configured.clone(),
+    }
+}
+
+/// Record a best-guess base branch for worktrees created before bases were stored
+fn backfill_base_branches(state: &AppState) {
+    let missing: Vec<(String, String, String)> = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .flat_map(|p| {
+            p.worktrees
+                .iter()
+                .filter(|w| w.base_branch.is_none())
+                .map(|w| (w.id.clone(), p.path.clone(), w.path.clone()))
+        })
+        .collect();
+
+    for (id, project_path, worktree_path) in missing {
+        if let Some(base) = git::infer_base_branch(Path::new(&project_path), Path::new(&worktree_path)) {
+            info!("[backfill_base_branches] {} is based on {}", worktree_path, base);
+            state.update_worktree(&id, |w| w.base_branch = Some(base));
+        }
+    }
+}
+
 #[tauri::command]
 fn get_branch_changed_files(
+    state: State<'_, Arc<AppState>>,
     worktree_path: &str,
     project_path: Option<String>,
 ) -> Result<Vec<FileChange>> {
     let path = Path::new(worktree_path);
     let cfg = config::load_config_for_project(project_path.as_deref());
-    git::get_branch_changed_files(path, &cfg.worktree.base_branch).map_err(map_err)
+    let base_branch = worktree_base_branch(&state, worktree_path, &cfg.worktree.base_branch);
+    git::get_branch_changed_files(path, &base_branch).map_err(map_err)
 }
 
 #[tauri::command]
 fn get_file_diff_content(
+    state: State<'_, Arc<AppState>>,
     worktree_path: &str,
     file_path: &str,
     mode: &str,
@@ -984,7 +2177,7 @@ fn get_file_diff_content(
 ) -> Result<state::DiffContent> {
     let path = Path::new(worktree_path);
     let cfg = config::load_config_for_project(project_path.as_deref());
-    let base_branch = &cfg.worktree.base_branch;
+    let base_branch = &worktree_base_branch(&state, worktree_path, &cfg.worktree.base_branch);
 
     let (original, modified, original_label, modified_label) = match mode {
         "uncommitted" => {
@@ -1024,6 +2217,12 @@ fn get_file_diff_content(
     })
 }
 
+#[tauri::command]
+fn get_file_diff(worktree_path: &str, file_path: &str, staged: bool) -> GitResult<String> {
+    let path = Path::new(worktree_path);
+    git::get_file_diff(path, file_path, staged)
+}
+
 #[tauri::command]
 fn has_uncommitted_changes(project_path: &str) -> Result<bool> {
     let path = Path::new(project_path);
@@ -1033,24 +2232,163 @@ fn has_uncommitted_changes(project_path: &str) -> Result<bool> {
 #[tauri::command]
 fn stash_changes(project_path: &str) -> Result<String> {
     let path = Path::new(project_path);
+    git::ensure_writable(path).map_err(map_err)?;
     git::stash_changes(path).map_err(map_err)
 }
 
 #[tauri::command]
 fn stash_pop(project_path: &str, stash_id: &str) -> Result<()> {
     let path = Path::new(project_path);
+    git::ensure_writable(path).map_err(map_err)?;
     git::stash_pop(path, stash_id).map_err(map_err)
 }
 
 #[tauri::command]
-fn abort_merge(project_path: &str) -> Result<()> {
+fn stash_worktree(worktree_id: &str, worktree_path: &str, message: Option<String>) -> GitResult<()> {
+    let _op_guard = git::lock_worktree_op(worktree_id)?;
+    let path = Path::new(worktree_path);
+    git::stash_worktree(path, message.as_deref())
+}
+
+#[tauri::command]
+fn unstash_worktree(worktree_id: &str, worktree_path: &str, index: usize) -> GitResult<()> {
+    let _op_guard = git::lock_worktree_op(worktree_id)?;
+    let path = Path::new(worktree_path);
+    git::unstash_worktree(path, index)
+}
+
+#[tauri::command]
+fn list_stashes(worktree_path: &str) -> GitResult<Vec<git::StashEntry>> {
+    let path = Path::new(worktree_path);
+    git::list_stashes(path)
+}
+
+#[tauri::command]
+fn get_recent_commits(worktree_path: &str, limit: usize) -> GitResult<Vec<git::CommitInfo>> {
+    let path = Path::new(worktree_path);
+    git::get_recent_commits(path, limit)
+}
+
+/// Progress line from a long-running git command (fetch, pull)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitProgress {
+    pub worktree_id: String,
+    pub operation: String,
+    pub message: String,
+}
+
+lazy_static::lazy_static! {
+    /// Cancel tokens of in-flight fetches and pulls, keyed by operation and worktree ID
+    static ref GIT_CANCELLATIONS: parking_lot::Mutex<HashMap<(String, String), git::CancelToken>> =
+        parking_lot::Mutex::new(HashMap::new());
+}
+
+/// Limits for a network operation on a worktree, registering its cancel token
+/// until the returned guard is dropped
+fn network_limits(operation: &str, worktree_id: &str, worktree_path: &str) -> (git::GitLimits, CancelRegistration) {
+    let cancel = git::CancelToken::new();
+    let key = (operation.to_string(), worktree_id.to_string());
+    GIT_CANCELLATIONS.lock().insert(key.clone(), cancel.clone());
+    let timeout = config::load_config_for_project(Some(worktree_path)).git.timeout();
+    (git::GitLimits { timeout, cancel: Some(cancel) }, CancelRegistration(key))
+}
+
+struct CancelRegistration((String, String));
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        GIT_CANCELLATIONS.lock().remove(&self.0);
+    }
+}
+
+/// Stop a worktree's in-flight fetch or pull, or both when `operation` is omitted.
+/// Returns false if none was running.
+#[tauri::command]
+fn cancel_git_operation(worktree_id: &str, operation: Option<&str>) -> bool {
+    let cancellations = GIT_CANCELLATIONS.lock();
+    let mut cancelled = false;
+    for ((op, id), cancel) in cancellations.iter() {
+        if id == worktree_id && operation.unwrap_or(op.as_str()) == op {
+            cancel.cancel();
+            cancelled = true;
+        }
+    }
+    cancelled
+}
+
+#[tauri::command]
+async fn fetch_worktree(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+    prune: bool,
+) -> GitResult<git::SyncResult> {
+    git::ensure_writable(Path::new(&worktree_path))?;
+    // Run off the main thread - fetches can take a while on slow remotes
+    tauri::async_runtime::spawn_blocking(move || {
+        let (limits, _registration) = network_limits("fetch", &worktree_id, &worktree_path);
+        let mut on_progress = |line: &str| {
+            let _ = app.emit(
+                "git-progress",
+                GitProgress {
+                    worktree_id: worktree_id.clone(),
+                    operation: "fetch".to_string(),
+                    message: line.to_string(),
+                },
+            );
+        };
+        let retries = config::load_config_for_project(Some(&worktree_path)).git.network_retries;
+        git::fetch_worktree(Path::new(&worktree_path), prune, retries, &limits, &mut on_progress)
+    })
+    .await
+    .map_err(|e| git::GitError::Other(e.to_string()))?
+}
+
+#[tauri::command]
+async fn pull_worktree(
+    app: AppHandle,
+    worktree_id: String,
+    worktree_path: String,
+) -> GitResult<git::SyncResult> {
+    git::ensure_writable(Path::new(&worktree_path))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (limits, _registration) = network_limits("pull", &worktree_id, &worktree_path);
+        let mut on_progress = |line: &str| {
+            let _ = app.emit(
+                "git-progress",
+                GitProgress {
+                    worktree_id: worktree_id.clone(),
+                    operation: "pull".to_string(),
+                    message: line.to_string(),
+                },
+            );
+        };
+        let retries = config::load_config_for_project(Some(&worktree_path)).git.network_retries;
+        git::pull_worktree(Path::new(&worktree_path), retries, &limits, &mut on_progress)
+    })
+    .await
+    .map_err(|e| git::GitError::Other(e.to_string()))?
+}
+
+/// The project's default branch (origin/HEAD, init.defaultBranch, then main/master)
+#[tauri::command]
+fn get_default_branch(project_path: &str) -> GitResult<String> {
+    git::default_branch(Path::new(project_path))
+}
+
+#[tauri::command]
+fn abort_merge(project_path: &str, worktree_id: Option<String>) -> Result<()> {
     let path = Path::new(project_path);
+    git::ensure_writable(path).map_err(map_err)?;
+    let _op_guard = worktree_id.as_deref().map(git::lock_worktree_op).transpose().map_err(map_err)?;
     git::abort_merge(path).map_err(map_err)
 }
 
 #[tauri::command]
 fn abort_rebase(project_path: &str) -> Result<()> {
     let path = Path::new(project_path);
+    git::ensure_writable(path).map_err(map_err)?;
     git::abort_rebase(path).map_err(map_err)
 }
 
@@ -1064,6 +2402,131 @@ fn stop_watching(worktree_id: String) {
     watcher::stop_watching(&worktree_id);
 }
 
+/// Push the worktree's recent commits as `git-log-changed` events whenever HEAD moves.
+/// Stopped by `stop_git_log_watcher`, or along with the worktree's file watcher.
+#[tauri::command]
+fn watch_git_log(app: AppHandle, worktree_id: String, worktree_path: String) {
+    watcher::watch_git_log(app, worktree_id, worktree_path);
+}
+
+#[tauri::command]
+fn stop_git_log_watcher(worktree_id: &str) {
+    watcher::stop_git_log_watcher(worktree_id);
+}
+
+/// Set a worktree's `suspended` flag, returning its path
+fn set_worktree_suspended(state: &AppState, worktree_id: &str, suspended: bool) -> Result<String> {
+    state
+        .update_worktree(worktree_id, |worktree| {
+            worktree.suspended = suspended;
+            worktree.path.clone()
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))
+}
+
+/// Pause a worktree: stop its watchers and terminate its PTY sessions (SIGTERM;
+/// sessions leave the PID file as they exit). It stays in the list, sessions can't
+/// be spawned in it, and watchers aren't started for it on launch until it's resumed.
+#[tauri::command]
+fn suspend_worktree(app: AppHandle, state: State<'_, Arc<AppState>>, worktree_id: &str) -> Result<()> {
+    // Flag it in memory first so no session can be spawned while the others are killed,
+    // but only persist the flag once they all were
+    let path = {
+        let mut persisted = state.persisted.write();
+        let worktree = persisted
+            .projects
+            .iter_mut()
+            .flat_map(|p| p.worktrees.iter_mut())
+            .find(|w| w.id == worktree_id)
+            .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+        worktree.suspended = true;
+        worktree.path.clone()
+    };
+
+    watcher::stop_watching(worktree_id);
+    watcher::stop_merge_watcher(worktree_id);
+    watcher::stop_rebase_watcher(worktree_id);
+
+    let pty_ids: Vec<String> = state
+        .pty_sessions
+        .read()
+        .iter()
+        .filter(|(_, session)| session.worktree_id == worktree_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    let killed = pty_ids.iter().try_for_each(|pty_id| pty::kill_pty(&state, pty_id));
+    if let Err(e) = killed {
+        set_worktree_suspended(&state, worktree_id, false)?;
+        watcher::watch_worktree(app.clone(), worktree_id.to_string(), path);
+        return Err(map_err(e));
+    }
+    set_worktree_suspended(&state, worktree_id, true)?;
+
+    info!("[suspend_worktree] Suspended {}", worktree_id);
+    let _ = app.emit("worktree-suspended", serde_json::json!({ "worktreeId": worktree_id }));
+    Ok(())
+}
+
+/// Resume a suspended worktree: restart its file watcher and emit
+/// `worktree-resumed` so the frontend respawns its main pane.
+#[tauri::command]
+fn resume_worktree(app: AppHandle, state: State<'_, Arc<AppState>>, worktree_id: &str) -> Result<()> {
+    let path = set_worktree_suspended(&state, worktree_id, false)?;
+    watcher::watch_worktree(app.clone(), worktree_id.to_string(), path);
+
+    info!("[resume_worktree] Resumed {}", worktree_id);
+    let _ = app.emit("worktree-resumed", serde_json::json!({ "worktreeId": worktree_id }));
+    Ok(())
+}
+
+/// Turn inspect mode on or off for a worktree. Entering it terminates the
+/// worktree's sessions and swaps its file watcher for status polling; leaving
+/// it restores the watcher. Emits `worktree-inspect-changed` either way.
+#[tauri::command]
+fn set_worktree_inspect(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    worktree_id: &str,
+    inspect: bool,
+) -> Result<()> {
+    let (path, suspended) = state
+        .update_worktree(worktree_id, |worktree| {
+            worktree.inspect = inspect;
+            (worktree.path.clone(), worktree.suspended)
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+
+    watcher::stop_watching(worktree_id);
+    if inspect {
+        git::enter_inspect_mode(worktree_id, Path::new(&path));
+        watcher::stop_merge_watcher(worktree_id);
+        watcher::stop_rebase_watcher(worktree_id);
+
+        let pty_ids: Vec<String> = state
+            .pty_sessions
+            .read()
+            .iter()
+            .filter(|(_, session)| session.worktree_id == worktree_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for pty_id in pty_ids {
+            pty::kill_pty(&state, &pty_id).map_err(map_err)?;
+        }
+    } else {
+        git::leave_inspect_mode(worktree_id);
+    }
+    if !suspended {
+        watcher::watch_worktree(app.clone(), worktree_id.to_string(), path);
+    }
+
+    info!("[set_worktree_inspect] {} inspect mode for {}", if inspect { "Entered" } else { "Left" }, worktree_id);
+    let _ = app.emit(
+        "worktree-inspect-changed",
+        serde_json::json!({ "worktreeId": worktree_id, "inspect": inspect }),
+    );
+    Ok(())
+}
+
 // Merge workflow commands
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1072,6 +2535,9 @@ pub struct MergeWorkflowOptions {
     pub delete_worktree: bool,
     pub delete_local_branch: bool,
     pub delete_remote_branch: bool,
+    /// Branch to merge into; defaults to the repository's default branch
+    #[serde(default)]
+    pub target_branch: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1089,11 +2555,42 @@ pub struct MergeProgress {
     pub message: String,
 }
 
+/// Ahead/behind counts against the worktree's base, and when it last synced
+#[tauri::command]
+fn divergence_summary(
+    state: State<'_, Arc<AppState>>,
+    worktree_path: &str,
+    project_path: Option<String>,
+) -> GitResult<git::DivergenceSummary> {
+    let cfg = config::load_config_for_project(project_path.as_deref());
+    let base_branch = worktree_base_branch(&state, worktree_path, &cfg.worktree.base_branch);
+    git::divergence_summary(Path::new(worktree_path), &base_branch)
+}
+
 #[tauri::command]
-fn check_merge_feasibility(worktree_path: &str, project_path: Option<String>) -> Result<MergeFeasibility> {
+fn check_merge_feasibility(
+    state: State<'_, Arc<AppState>>,
+    worktree_path: &str,
+    project_path: Option<String>,
+) -> Result<MergeFeasibility> {
     let path = Path::new(worktree_path);
     let cfg = config::load_config_for_project(project_path.as_deref());
-    git::check_merge_feasibility(path, &cfg.worktree.base_branch).map_err(map_err)
+    let base_branch = worktree_base_branch(&state, worktree_path, &cfg.worktree.base_branch);
+    git::check_merge_feasibility(path, &base_branch).map_err(map_err)
+}
+
+/// Trial-run a merge or rebase in memory to see whether it would conflict.
+/// Without a strategy, the configured `worktree.merge.strategy` is used.
+#[tauri::command]
+fn merge_preflight(
+    worktree_path: &str,
+    target_branch: &str,
+    strategy: Option<config::MergeStrategy>,
+    project_path: Option<String>,
+) -> GitResult<git::PreflightResult> {
+    let strategy = strategy
+        .unwrap_or_else(|| config::load_config_for_project(project_path.as_deref()).worktree.merge.strategy);
+    git::merge_preflight(Path::new(worktree_path), target_branch, strategy)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1102,8 +2599,13 @@ pub struct MergeCompleted {
     pub worktree_id: String,
     pub success: bool,
     pub branch_name: String,
+    /// Branch that was merged into; unset when no merge ran (cleanup only, or
+    /// the target couldn't be resolved)
+    pub target_branch: Option<String>,
     pub deleted_worktree: bool,
     pub error: Option<String>,
+    /// Which post-merge cleanup steps ran and whether they succeeded
+    pub cleanup: Option<git::CleanupOutcome>,
 }
 
 // Delete worktree workflow types
@@ -1136,13 +2638,13 @@ fn execute_merge_workflow(
     options: MergeWorkflowOptions,
 ) {
     // Find worktree and project - extract all data we need before spawning thread
-    let (worktree_path, project_path) = {
+    let (worktree_path, project_path, base_branch) = {
         let persisted = state.persisted.read();
         let mut found = None;
 
         for project in &persisted.projects {
             if let Some(worktree) = project.worktrees.iter().find(|w| w.id == worktree_id) {
-                found = Some((worktree.path.clone(), project.path.clone()));
+                found = Some((worktree.path.clone(), project.path.clone(), worktree.base_branch.clone()));
                 break;
             }
         }
@@ -1156,7 +2658,9 @@ fn execute_merge_workflow(
                         worktree_id: worktree_id.to_string(),
                         success: false,
                         branch_name: String::new(),
+                        target_branch: None,
                         deleted_worktree: false,
+                        cleanup: None,
                         error: Some(format!("Worktree not found: {}", worktree_id)),
                     },
                 );
@@ -1165,16 +2669,60 @@ fn execute_merge_workflow(
         }
     };
 
+    // Serialize mutating git operations on this worktree
+    let op_guard = match git::lock_worktree_op(worktree_id) {
+        Ok(guard) => guard,
+        Err(e) => {
+            let _ = app.emit(
+                "merge-completed",
+                MergeCompleted {
+                    worktree_id: worktree_id.to_string(),
+                    success: false,
+                    branch_name: String::new(),
+                    target_branch: None,
+                    deleted_worktree: false,
+                    cleanup: None,
+                    error: Some(e.to_string()),
+                },
+            );
+            return;
+        }
+    };
+
     // Clone data for the background thread
     let worktree_id = worktree_id.to_string();
     let app_state = Arc::clone(&*state);
-    let delete_worktree = options.delete_worktree;
 
     // Spawn background thread to avoid blocking UI
     std::thread::spawn(move || {
+        // Held until the workflow finishes
+        let _op_guard = op_guard;
         let worktree_path = Path::new(&worktree_path);
         let project_path = Path::new(&project_path);
 
+        // Without an explicit target, merge back into the recorded base if it still exists
+        let target = options.target_branch.or_else(|| {
+            base_branch.filter(|base| git::branch_exists(project_path, base).unwrap_or(false))
+        });
+        let target_branch = match git::resolve_merge_target(worktree_path, target.as_deref()) {
+            Ok(target) => target,
+            Err(e) => {
+                let _ = app.emit(
+                    "merge-completed",
+                    MergeCompleted {
+                        worktree_id,
+                        success: false,
+                        branch_name: String::new(),
+                        target_branch: None,
+                        deleted_worktree: false,
+                        cleanup: None,
+                        error: Some(e.to_string()),
+                    },
+                );
+                return;
+            }
+        };
+
         // Emit progress: starting merge
         let phase = if options.strategy == MergeStrategy::Rebase {
             "rebase"
@@ -1185,14 +2733,35 @@ fn execute_merge_workflow(
             "merge-progress",
             MergeProgress {
                 phase: phase.to_string(),
-                message: format!("{}...", if phase == "rebase" { "Rebasing" } else { "Merging" }),
+                message: if phase == "rebase" {
+                    format!("Rebasing onto {}...", target_branch)
+                } else {
+                    format!("Merging into {}...", target_branch)
+                },
             },
         );
 
         // Execute the merge/rebase
-        let branch_name = match git::execute_merge_workflow(worktree_path, project_path, options.strategy) {
+        let branch_name = match git::execute_merge_workflow(worktree_path, project_path, options.strategy, &target_branch) {
             Ok(name) => name,
             Err(e) => {
+                // Only `MergeConflict` leaves conflicts behind; a `MergeAborted` merge
+                // was already undone in the checkout it ran in
+                if matches!(e, git::GitError::MergeConflict { .. }) {
+                    // Watch for the conflicted merge/rebase to be concluded while the
+                    // worktree is still ours, so no second merge can start in between
+                    if phase == "rebase" {
+                        watcher::watch_rebase_state(app.clone(), worktree_id.clone(), worktree_path.display().to_string());
+                    } else {
+                        watcher::watch_merge_state(app.clone(), worktree_id.clone(), project_path.display().to_string());
+                    }
+                    notifications::notify(
+                        &app,
+                        notifications::NotificationEvent::MergeConflict,
+                        "Merge conflict",
+                        &format!("{} stopped on conflicts", if phase == "rebase" { "Rebase" } else { "Merge" }),
+                    );
+                }
                 let _ = app.emit(
                     "merge-progress",
                     MergeProgress {
@@ -1206,7 +2775,9 @@ fn execute_merge_workflow(
                         worktree_id,
                         success: false,
                         branch_name: String::new(),
+                        target_branch: Some(target_branch),
                         deleted_worktree: false,
+                        cleanup: None,
                         error: Some(e.to_string()),
                     },
                 );
@@ -1214,64 +2785,20 @@ fn execute_merge_workflow(
             }
         };
 
-        // Delete worktree if requested
-        if options.delete_worktree {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-worktree".to_string(),
-                    message: "Removing worktree...".to_string(),
-                },
-            );
-
-            // Stop watching first
-            watcher::stop_watching(&worktree_id);
-
-            // Delete the worktree
-            let mut persisted = app_state.persisted.write();
-            for project in &mut persisted.projects {
-                if project.worktrees.iter().any(|w| w.id == worktree_id) {
-                    if let Err(e) = worktree::delete_worktree(project, &worktree_id) {
-                        info!("Failed to delete worktree: {}", e);
-                    }
-                    break;
-                }
-            }
-            drop(persisted);
-            if let Err(e) = app_state.save() {
-                info!("Failed to save state: {}", e);
-            }
-        }
-
-        // Delete local branch if requested
-        if options.delete_local_branch {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-local-branch".to_string(),
-                    message: "Deleting local branch...".to_string(),
-                },
-            );
-
-            if let Err(e) = git::delete_local_branch(project_path, &branch_name) {
-                info!("Failed to delete local branch: {}", e);
-            }
-        }
-
-        // Delete remote branch if requested
-        if options.delete_remote_branch {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-remote-branch".to_string(),
-                    message: "Deleting remote branch...".to_string(),
-                },
-            );
-
-            if let Err(e) = git::delete_remote_branch(project_path, &branch_name) {
-                info!("Failed to delete remote branch: {}", e);
-            }
-        }
+        let cleanup = run_post_merge_cleanup(
+            &app,
+            &app_state,
+            &worktree_id,
+            project_path,
+            &branch_name,
+            &config::MergeConfig {
+                strategy: options.strategy,
+                delete_worktree: options.delete_worktree,
+                delete_local_branch: options.delete_local_branch,
+                delete_remote_branch: options.delete_remote_branch,
+            },
+        );
+        let deleted_worktree = cleanup.worktree == git::CleanupStep::Done;
 
         // Emit completion
         let _ = app.emit(
@@ -1282,14 +2809,23 @@ fn execute_merge_workflow(
             },
         );
 
+        notifications::notify(
+            &app,
+            notifications::NotificationEvent::MergeComplete,
+            "Merge complete",
+            &format!("{} was merged successfully", branch_name),
+        );
+
         let _ = app.emit(
             "merge-completed",
             MergeCompleted {
                 worktree_id,
                 success: true,
                 branch_name,
-                deleted_worktree: delete_worktree,
+                target_branch: Some(target_branch),
+                deleted_worktree,
                 error: None,
+                cleanup: Some(cleanup),
             },
         );
     });
@@ -1297,6 +2833,67 @@ fn execute_merge_workflow(
     info!("[execute_merge_workflow] spawned background thread");
 }
 
+/// Run the post-merge cleanup steps, reporting each as `merge-progress` and
+/// dropping the worktree from state once it has been removed
+fn run_post_merge_cleanup(
+    app: &AppHandle,
+    app_state: &AppState,
+    worktree_id: &str,
+    project_path: &Path,
+    branch_name: &str,
+    cfg: &config::MergeConfig,
+) -> git::CleanupOutcome {
+    if cfg.delete_worktree {
+        // Stop watching first
+        watcher::stop_watching(worktree_id);
+    }
+
+    let git_cfg = config::load_config_for_project(project_path.to_str()).git;
+    let emit = |phase: &str, message: &str| {
+        let _ = app.emit(
+            "merge-progress",
+            MergeProgress {
+                phase: phase.to_string(),
+                message: message.to_string(),
+            },
+        );
+    };
+    // Removes the directory and the worktree's record along with git's
+    let mut delete_worktree = || {
+        app_state.mutate(|persisted| {
+            let project = persisted
+                .projects
+                .iter_mut()
+                .find(|p| p.worktrees.iter().any(|w| w.id == worktree_id))
+                .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+            worktree::delete_worktree(project, worktree_id).map_err(map_err)
+        })
+    };
+    let outcome = git::post_merge_cleanup(
+        project_path,
+        &mut delete_worktree,
+        branch_name,
+        cfg,
+        git_cfg.network_retries,
+        &git::GitLimits::timeout(git_cfg.timeout()),
+        &mut |step| {
+            let message = match step {
+                "delete-worktree" => "Removing worktree...",
+                "delete-local-branch" => "Deleting local branch...",
+                _ => "Deleting remote branch...",
+            };
+            emit(step, message);
+        },
+        &mut |line| emit("delete-remote-branch", line),
+    );
+
+    if outcome.worktree == git::CleanupStep::Done {
+        git::leave_inspect_mode(worktree_id);
+    }
+
+    outcome
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CleanupOptions {
@@ -1333,7 +2930,9 @@ fn cleanup_worktree(
                         worktree_id: worktree_id.to_string(),
                         success: false,
                         branch_name: String::new(),
+                        target_branch: None,
                         deleted_worktree: false,
+                        cleanup: None,
                         error: Some(format!("Worktree not found: {}", worktree_id)),
                     },
                 );
@@ -1345,70 +2944,25 @@ fn cleanup_worktree(
     // Clone data for the background thread
     let worktree_id = worktree_id.to_string();
     let app_state = Arc::clone(&*state);
-    let delete_worktree = options.delete_worktree;
 
     // Spawn background thread to avoid blocking UI
     std::thread::spawn(move || {
         let project_path = Path::new(&project_path);
 
-        // Delete worktree if requested
-        if options.delete_worktree {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-worktree".to_string(),
-                    message: "Removing worktree...".to_string(),
-                },
-            );
-
-            // Stop watching first
-            watcher::stop_watching(&worktree_id);
-
-            // Delete the worktree
-            let mut persisted = app_state.persisted.write();
-            for project in &mut persisted.projects {
-                if project.worktrees.iter().any(|w| w.id == worktree_id) {
-                    if let Err(e) = worktree::delete_worktree(project, &worktree_id) {
-                        info!("Failed to delete worktree: {}", e);
-                    }
-                    break;
-                }
-            }
-            drop(persisted);
-            if let Err(e) = app_state.save() {
-                info!("Failed to save state: {}", e);
-            }
-        }
-
-        // Delete local branch if requested
-        if options.delete_local_branch {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-local-branch".to_string(),
-                    message: "Deleting local branch...".to_string(),
-                },
-            );
-
-            if let Err(e) = git::delete_local_branch(project_path, &branch_name) {
-                info!("Failed to delete local branch: {}", e);
-            }
-        }
-
-        // Delete remote branch if requested
-        if options.delete_remote_branch {
-            let _ = app.emit(
-                "merge-progress",
-                MergeProgress {
-                    phase: "delete-remote-branch".to_string(),
-                    message: "Deleting remote branch...".to_string(),
-                },
-            );
-
-            if let Err(e) = git::delete_remote_branch(project_path, &branch_name) {
-                info!("Failed to delete remote branch: {}", e);
-            }
-        }
+        let cleanup = run_post_merge_cleanup(
+            &app,
+            &app_state,
+            &worktree_id,
+            project_path,
+            &branch_name,
+            &config::MergeConfig {
+                strategy: MergeStrategy::Merge,
+                delete_worktree: options.delete_worktree,
+                delete_local_branch: options.delete_local_branch,
+                delete_remote_branch: options.delete_remote_branch,
+            },
+        );
+        let deleted_worktree = cleanup.worktree == git::CleanupStep::Done;
 
         // Emit completion
         let _ = app.emit(
@@ -1425,8 +2979,10 @@ fn cleanup_worktree(
                 worktree_id,
                 success: true,
                 branch_name,
-                deleted_worktree: delete_worktree,
+                target_branch: None,
+                deleted_worktree,
                 error: None,
+                cleanup: Some(cleanup),
             },
         );
     });
@@ -1573,6 +3129,101 @@ fn open_in_editor(
     open_with_app(path, &editor)
 }
 
+/// Where `open_worktree_in` should open a worktree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OpenTarget {
+    Editor,
+    Terminal,
+    FileManager,
+}
+
+/// Fail early with a readable error if a launcher command's program isn't on PATH.
+/// Only the first word is checked, so templates like `code {{ path }}` work.
+fn ensure_program_exists(command: &str) -> Result<()> {
+    let program = command.split_whitespace().next().unwrap_or(command);
+    if pty::find_program(program).is_none() {
+        return Err(format!("'{}' was not found on PATH", program));
+    }
+    Ok(())
+}
+
+/// Open a worktree in the configured editor, terminal or file manager.
+/// Uses the `apps` config section, falling back to platform defaults.
+#[tauri::command]
+fn open_worktree_in(worktree_path: &str, target: OpenTarget, project_path: Option<String>) -> Result<()> {
+    let cfg = config::load_config_for_project(project_path.as_deref());
+    let apps = &cfg.apps;
+
+    match target {
+        OpenTarget::Editor => {
+            let editor = apps
+                .editor_command()
+                .map(|s| s.to_string())
+                .or_else(|| std::env::var("VISUAL").ok())
+                .or_else(|| std::env::var("EDITOR").ok())
+                .ok_or_else(|| "No editor configured. Set $VISUAL or $EDITOR, or configure apps.editor.".to_string())?;
+            ensure_program_exists(&editor)?;
+
+            match apps.editor_target() {
+                config::AppTarget::Terminal => {
+                    open_terminal_with_command(&editor, worktree_path, apps.terminal_command())
+                }
+                config::AppTarget::External => open_with_app(worktree_path, &editor),
+                config::AppTarget::Drawer | config::AppTarget::Tab => Err(format!(
+                    "Editor target '{:?}' opens inside shellflow and can't be launched externally",
+                    apps.editor_target()
+                )),
+            }
+        }
+        OpenTarget::Terminal => {
+            let default = if cfg!(target_os = "macos") {
+                "open"
+            } else if cfg!(target_os = "windows") {
+                "wt"
+            } else {
+                "xdg-terminal-exec"
+            };
+            ensure_program_exists(apps.terminal_command().unwrap_or(default))?;
+            open_in_terminal(worktree_path, apps.terminal_command())
+        }
+        OpenTarget::FileManager => {
+            let default = if cfg!(target_os = "macos") {
+                "open"
+            } else if cfg!(target_os = "windows") {
+                "explorer"
+            } else {
+                "xdg-open"
+            };
+            ensure_program_exists(apps.file_manager_command().unwrap_or(default))?;
+            open_in_file_manager(worktree_path, apps.file_manager_command())
+        }
+    }
+}
+
+/// Open the global config directory in the file manager, creating it if needed
+#[tauri::command]
+fn reveal_config_dir(app: AppHandle) -> Result<()> {
+    let path = config::get_config_path();
+    reveal_dir(&app, path.parent().unwrap_or(&path))
+}
+
+/// Open the state directory in the file manager, creating it if needed
+#[tauri::command]
+fn reveal_state_dir(app: AppHandle) -> Result<()> {
+    let dir = state::state_dir().ok_or("Home directory not found")?;
+    reveal_dir(&app, &dir)
+}
+
+fn reveal_dir(app: &AppHandle, dir: &Path) -> Result<()> {
+    use tauri_plugin_opener::OpenerExt;
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
 /// Ensure a shellflow config file exists and return its path.
 /// Creates the file with a schema reference if it doesn't exist.
 /// - `file_type`: "settings" or "mappings"
@@ -1716,18 +3367,15 @@ fn rename_worktree(
         return Err(error);
     }
 
-    let mut persisted = state.persisted.write();
-
     // Find the worktree and its project
-    let mut found = None;
-    for project in &mut persisted.projects {
-        if let Some(worktree) = project.worktrees.iter_mut().find(|w| w.id == worktree_id) {
-            found = Some((project.path.clone(), worktree.branch.clone()));
-            break;
-        }
-    }
+    let found = state.persisted.read().projects.iter().find_map(|project| {
+        let worktree = project.worktrees.iter().find(|w| w.id == worktree_id)?;
+        Some((project.path.clone(), worktree.branch.clone(), worktree.path.clone()))
+    });
 
-    let (project_path, old_name) = found.ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+    let (project_path, old_name, worktree_path) =
+        found.ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+    git::ensure_writable(Path::new(&worktree_path)).map_err(map_err)?;
 
     // Check if new name is same as old name
     if old_name == new_name {
@@ -1744,26 +3392,128 @@ fn rename_worktree(
     git::rename_branch(project_path, &old_name, new_name).map_err(map_err)?;
 
     // Update the worktree state
-    for project in &mut persisted.projects {
-        if let Some(worktree) = project.worktrees.iter_mut().find(|w| w.id == worktree_id) {
-            worktree.name = new_name.to_string();
-            worktree.branch = new_name.to_string();
-            break;
-        }
+    state.update_worktree(worktree_id, |worktree| {
+        worktree.name = new_name.to_string();
+        worktree.branch = new_name.to_string();
+    });
+
+    info!("[rename_worktree] Renamed worktree {} from '{}' to '{}'", worktree_id, old_name, new_name);
+    Ok(())
+}
+
+/// Move a worktree's directory into `new_parent_dir` (e.g. on another drive)
+/// without recreating it, returning the new path. Its terminal sessions are
+/// closed, since a running shell can't be moved to another directory:
+/// `worktree-relocating` goes out first so the frontend doesn't treat their
+/// exit as the user closing them, and it respawns them at the new path on
+/// `worktree-relocated`. Moving a large worktree across drives can take a
+/// while, so it runs on the blocking pool.
+#[tauri::command]
+async fn relocate_worktree(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    worktree_id: String,
+    new_parent_dir: String,
+) -> Result<String> {
+    let state = Arc::clone(&state);
+    tauri::async_runtime::spawn_blocking(move || {
+        relocate_worktree_blocking(&app, &state, &worktree_id, &new_parent_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn relocate_worktree_blocking(
+    app: &AppHandle,
+    state: &AppState,
+    worktree_id: &str,
+    new_parent_dir: &str,
+) -> Result<String> {
+    let (repo_path, worktree_path, suspended) = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .find_map(|project| {
+            let worktree = project.worktrees.iter().find(|w| w.id == worktree_id)?;
+            Some((project.repo_path().to_string(), worktree.path.clone(), worktree.suspended))
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+    git::ensure_writable(Path::new(&worktree_path)).map_err(map_err)?;
+    let _op_guard = git::lock_worktree_op(worktree_id).map_err(map_err)?;
+    if git::merge_or_rebase_in_progress(Path::new(&worktree_path)) {
+        return Err("A merge or rebase is in progress; finish or abort it before moving the worktree".to_string());
+    }
+
+    let pty_ids: Vec<String> = state
+        .pty_sessions
+        .read()
+        .iter()
+        .filter(|(_, session)| session.worktree_id == worktree_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    let _ = app.emit("worktree-relocating", serde_json::json!({ "worktreeId": worktree_id, "ptyIds": pty_ids }));
+    for pty_id in &pty_ids {
+        pty::kill_pty(state, pty_id).map_err(map_err)?;
+    }
+    // The killed sessions may take a moment to exit; don't hand them out again
+    state.pane_sessions.write().retain(|(id, _), _| id != worktree_id);
+    watcher::stop_merge_watcher(worktree_id);
+    watcher::stop_rebase_watcher(worktree_id);
+
+    let result = watcher::relocate_worktree(app.clone(), worktree_id, &worktree_path, || {
+        git::move_worktree(Path::new(&repo_path), Path::new(&worktree_path), Path::new(new_parent_dir))
+            .map(|path| path.display().to_string())
+    });
+    let path = result.as_ref().map_or(worktree_path.clone(), String::clone);
+    if suspended {
+        watcher::stop_watching(worktree_id);
+    } else if git::merge_or_rebase_in_progress(Path::new(&path)) {
+        // Only an unfinished merge or rebase needs its watchers back, as on startup
+        watcher::watch_merge_state(app.clone(), worktree_id.to_string(), path.clone());
+        watcher::watch_rebase_state(app.clone(), worktree_id.to_string(), path.clone());
+    }
+    if result.is_ok() {
+        state.update_worktree(worktree_id, |worktree| worktree.path = path.clone());
+        info!("[relocate_worktree] Moved {} from {} to {}", worktree_id, worktree_path, path);
+    }
+    // Sent on failure too (with the old path), since the sessions were closed either way
+    let _ = app.emit(
+        "worktree-relocated",
+        serde_json::json!({ "worktreeId": worktree_id, "oldPath": worktree_path, "newPath": path }),
+    );
+    result.map_err(map_err)
+}
+
+/// Rename only a worktree's branch; its display name and directory stay as they are
+#[tauri::command]
+fn rename_worktree_branch(state: State<'_, Arc<AppState>>, worktree_id: &str, new_name: &str) -> Result<()> {
+    let (repo_path, old_name, worktree_path) = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .find_map(|project| {
+            let worktree = project.worktrees.iter().find(|w| w.id == worktree_id)?;
+            Some((project.repo_path().to_string(), worktree.branch.clone(), worktree.path.clone()))
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))?;
+    git::ensure_writable(Path::new(&worktree_path)).map_err(map_err)?;
+
+    if old_name == new_name {
+        return Ok(());
     }
 
-    drop(persisted);
-    state.save().map_err(map_err)?;
+    git::rename_worktree_branch(Path::new(&repo_path), &old_name, new_name).map_err(map_err)?;
+    state.update_worktree(worktree_id, |worktree| worktree.branch = new_name.to_string());
 
-    info!("[rename_worktree] Renamed worktree {} from '{}' to '{}'", worktree_id, old_name, new_name);
+    info!("[rename_worktree_branch] Renamed branch of {} from '{}' to '{}'", worktree_id, old_name, new_name);
     Ok(())
 }
 
 /// Reorder projects by providing the new order of project IDs
 #[tauri::command]
 fn reorder_projects(state: State<'_, Arc<AppState>>, project_ids: Vec<String>) -> Result<()> {
-    let mut persisted = state.persisted.write();
-
     // Create order map from the provided order
     let order_map: std::collections::HashMap<String, i32> = project_ids
         .iter()
@@ -1771,23 +3521,54 @@ fn reorder_projects(state: State<'_, Arc<AppState>>, project_ids: Vec<String>) -
         .map(|(i, id)| (id.clone(), i as i32))
         .collect();
 
-    // Update project orders
-    for project in &mut persisted.projects {
-        if let Some(&order) = order_map.get(&project.id) {
-            project.order = order;
+    state.mutate(|persisted| {
+        // Update project orders
+        for project in &mut persisted.projects {
+            if let Some(&order) = order_map.get(&project.id) {
+                project.order = order;
+            }
         }
-    }
-
-    // Sort projects by order
-    persisted.projects.sort_by_key(|p| p.order);
 
-    drop(persisted);
-    state.save().map_err(map_err)?;
+        // Sort projects by order
+        persisted.projects.sort_by_key(|p| p.order);
+    });
 
     info!("[reorder_projects] Reordered {} projects", project_ids.len());
     Ok(())
 }
 
+/// Set (or with `command: None`, clear) the main-pane command for one worktree.
+/// Takes effect the next time the main pane is spawned.
+#[tauri::command]
+fn set_worktree_command(
+    state: State<'_, Arc<AppState>>,
+    worktree_id: &str,
+    command: Option<String>,
+    args: Option<Vec<String>>,
+) -> Result<()> {
+    state
+        .update_worktree(worktree_id, |worktree| {
+            worktree.command = command.filter(|c| !c.trim().is_empty());
+            worktree.args = if worktree.command.is_some() { args.unwrap_or_default() } else { Vec::new() };
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))
+}
+
+/// Select (or with `profile: None`, clear) the config `environments` profile for
+/// one worktree. Takes effect for terminals spawned afterwards.
+#[tauri::command]
+fn set_worktree_environment(
+    state: State<'_, Arc<AppState>>,
+    worktree_id: &str,
+    profile: Option<String>,
+) -> Result<()> {
+    state
+        .update_worktree(worktree_id, |worktree| {
+            worktree.environment = profile.filter(|p| !p.trim().is_empty());
+        })
+        .ok_or_else(|| format!("Worktree not found: {}", worktree_id))
+}
+
 /// Reorder worktrees within a project by providing the new order of worktree IDs
 #[tauri::command]
 fn reorder_worktrees(
@@ -1795,14 +3576,6 @@ fn reorder_worktrees(
     project_id: &str,
     worktree_ids: Vec<String>,
 ) -> Result<()> {
-    let mut persisted = state.persisted.write();
-
-    let project = persisted
-        .projects
-        .iter_mut()
-        .find(|p| p.id == project_id)
-        .ok_or_else(|| format!("Project not found: {}", project_id))?;
-
     // Create order map from the provided order
     let order_map: std::collections::HashMap<String, i32> = worktree_ids
         .iter()
@@ -1810,18 +3583,19 @@ fn reorder_worktrees(
         .map(|(i, id)| (id.clone(), i as i32))
         .collect();
 
-    // Update worktree orders
-    for worktree in &mut project.worktrees {
-        if let Some(&order) = order_map.get(&worktree.id) {
-            worktree.order = order;
-        }
-    }
-
-    // Sort worktrees by order
-    project.worktrees.sort_by_key(|w| w.order);
+    state
+        .update_project(project_id, |project| {
+            // Update worktree orders
+            for worktree in &mut project.worktrees {
+                if let Some(&order) = order_map.get(&worktree.id) {
+                    worktree.order = order;
+                }
+            }
 
-    drop(persisted);
-    state.save().map_err(map_err)?;
+            // Sort worktrees by order
+            project.worktrees.sort_by_key(|w| w.order);
+        })
+        .ok_or_else(|| format!("Project not found: {}", project_id))?;
 
     info!("[reorder_worktrees] Reordered {} worktrees in project {}", worktree_ids.len(), project_id);
     Ok(())
@@ -1833,10 +3607,72 @@ fn update_action_availability(availability: HashMap<String, bool>) {
     menu::update_action_availability(availability);
 }
 
-// Shutdown command - gracefully terminates all PTY processes
-// Spawns a background thread and returns immediately so events can stream to frontend
+#[tauri::command]
+fn process_health(state: State<'_, Arc<AppState>>) -> cleanup::ProcessHealth {
+    cleanup::process_health(&state)
+}
+
+/// Answer an `orphans-found` prompt with the PIDs to kill; the others keep
+/// running. Returns false if no prompt is pending (e.g. it already timed out).
+#[tauri::command]
+fn confirm_orphan_cleanup(pids: Vec<u32>) -> bool {
+    cleanup::confirm_orphan_cleanup(pids)
+}
+
+/// Orphans awaiting confirmation, in case `orphans-found` fired before the
+/// frontend was listening
+#[tauri::command]
+fn pending_orphans() -> Vec<cleanup::OrphanInfo> {
+    cleanup::pending_orphans()
+}
+
+/// Check this instance's PID file, rebuilding it from live sessions if it's corrupt
+#[tauri::command]
+fn verify_pid_file(state: State<'_, Arc<AppState>>) -> cleanup::PidFileHealth {
+    cleanup::verify_pid_file(&state)
+}
+
+#[tauri::command]
+fn list_instances() -> Vec<cleanup::InstanceInfo> {
+    cleanup::list_instances()
+}
+
+/// Counts of sessions, watchers and tracked PIDs plus memory and uptime, for
+/// the status bar. Cheap enough to poll.
+#[tauri::command]
+fn app_metrics(state: State<'_, Arc<AppState>>) -> metrics::AppMetrics {
+    metrics::app_metrics(&state)
+}
+
+#[tauri::command]
+fn is_shutdown_in_progress() -> bool {
+    pty::is_shutdown_in_progress()
+}
+
+/// Change the log level for the rest of this run (off, error, warn, info, debug, trace).
+/// The startup level comes from `log.level` in the config.
+#[tauri::command]
+fn set_log_level(level: &str) -> Result<()> {
+    let filter = level
+        .parse::<log::LevelFilter>()
+        .map_err(|_| format!("Invalid log level: {}", level))?;
+    log::set_max_level(filter);
+    info!("[set_log_level] Log level set to {}", filter);
+    Ok(())
+}
+
+/// Graceful teardown for a normal quit: stop all watchers, terminate PTY sessions
+/// (SIGHUP, then SIGTERM, then SIGKILL after the grace period), save state, remove
+/// the PID file and IPC socket, then exit. Runs in a background thread so progress
+/// events stream to the frontend. Returns whether there were sessions to clean up
+/// (so the frontend knows whether to show the shutdown UI). Calling it again, or
+/// while a signal-triggered shutdown is running, does nothing.
 #[tauri::command]
 fn shutdown(app: AppHandle, state: State<'_, Arc<AppState>>) -> bool {
+    if pty::begin_shutdown(None) {
+        info!("[Shutdown] Shutdown already in progress");
+        return false;
+    }
     info!("[Shutdown] Starting graceful shutdown...");
 
     // Check if there are any active PTY sessions
@@ -1847,8 +3683,13 @@ fn shutdown(app: AppHandle, state: State<'_, Arc<AppState>>) -> bool {
 
     // Run shutdown in a background thread so events stream to frontend
     std::thread::spawn(move || {
-        pty::shutdown_all_ptys(&app_clone, &state_clone);
         watcher::stop_all_watchers();
+        pty::shutdown_all_ptys(&app_clone, &state_clone);
+        if let Err(e) = state_clone.save() {
+            info!("[Shutdown] Failed to save state: {}", e);
+        }
+        cleanup::delete_pid_file();
+        ipc::remove_socket();
         info!("[Shutdown] Shutdown complete, exiting app");
 
         // Only delay if we had processes to show in the UI
@@ -1868,8 +3709,19 @@ use tauri_plugin_log::{Target, TargetKind, TimezoneStrategy};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Clean up any orphaned processes from a previous crash
-    cleanup::cleanup_orphans();
+    metrics::mark_start();
+
+    // Clean up any orphaned processes from a previous crash, unless disabled
+    // (cleanup.onStartup or SHELLFLOW_NO_CLEANUP). With cleanup.confirm the user
+    // is asked first, which has to wait until the app is set up.
+    let cleanup_config = config::load_config().cleanup;
+    let startup_cleanup = cleanup::startup_cleanup_enabled(&cleanup_config);
+    let confirm_orphan_cleanup = startup_cleanup && cleanup_config.confirm;
+    if !startup_cleanup {
+        eprintln!("[startup] Skipping orphan cleanup (disabled by config or SHELLFLOW_NO_CLEANUP)");
+    } else if !confirm_orphan_cleanup {
+        cleanup::cleanup_orphans();
+    }
 
     let app_state = Arc::new(AppState::load_or_default());
 
@@ -1894,11 +3746,9 @@ pub fn run() {
                     Target::new(TargetKind::LogDir { file_name: None }),
                     Target::new(TargetKind::Webview),
                 ])
-                .level(if cfg!(debug_assertions) {
-                    log::LevelFilter::Debug
-                } else {
-                    log::LevelFilter::Info
-                })
+                // Let everything through the plugin; the effective level is the
+                // global max level, set from config in setup and by set_log_level
+                .level(log::LevelFilter::Trace)
                 .timezone_strategy(TimezoneStrategy::UseLocal)
                 .build(),
         )
@@ -1907,7 +3757,10 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .manage(app_state)
-        .setup(|app| {
+        .setup(move |app| {
+            let startup_config = config::load_config();
+            log::set_max_level(startup_config.log.level_filter());
+            git::set_audit_log(startup_config.git.audit_log);
             eprintln!("[setup] Shellflow starting...");
 
             // Apply platform-specific window effects for glassy appearance
@@ -1931,58 +3784,106 @@ pub fn run() {
                 }
             }
 
+            theme::set_resource_dir(app.path().resource_dir().ok());
+            pty::set_shutdown_app_handle(app.handle().clone());
+
+            if confirm_orphan_cleanup {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    cleanup::cleanup_orphans_with_confirmation(|orphans| {
+                        let _ = handle.emit("orphans-found", orphans);
+                    });
+                    // Dismisses a prompt still open after the timeout killed them all
+                    let _ = handle.emit("orphans-resolved", ());
+                });
+            }
+
             // Load config for menu shortcuts
             let config = config::load_config();
 
             // Set up application menu
             menu::setup_menu(app, &config.mappings)?;
 
-            // Start file watchers for worktrees in active projects only
-            // This enables detection of externally deleted worktree folders
             let app_state = app.state::<Arc<AppState>>();
             let persisted = app_state.persisted.read();
-            for project in persisted.projects.iter().filter(|p| p.is_active) {
-                for wt in &project.worktrees {
-                    watcher::watch_worktree(
-                        app.handle().clone(),
-                        wt.id.clone(),
-                        wt.path.clone(),
-                    );
-                }
+            for wt in persisted.projects.iter().flat_map(|p| &p.worktrees).filter(|wt| wt.inspect) {
+                git::enter_inspect_mode(&wt.id, Path::new(&wt.path));
+            }
+            let window_geometry = persisted.layout.as_ref().and_then(|l| l.window.clone());
+            drop(persisted);
+
+            // Start file watchers for worktrees in active projects only
+            // This enables detection of externally deleted worktree folders
+            watcher::start_all_watchers(app.handle().clone());
+
+            // Restore window size/position from the last session
+            if let (Some(geometry), Some(window)) = (window_geometry, app.get_webview_window("main")) {
+                restore_window_geometry(&window, &geometry);
             }
 
+            // Guess bases for worktrees recorded before they were stored
+            let backfill_state = Arc::clone(&*app_state);
+            std::thread::spawn(move || backfill_base_branches(&backfill_state));
+
+            // Accept requests from `shellflow --new-worktree <name>`
+            let ipc_app = app.handle().clone();
+            ipc::start_server(move |request| handle_ipc_request(&ipc_app, request));
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             log_to_terminal,
             add_project,
+            inspect_path,
             list_projects,
             hide_project,
             touch_project,
             create_worktree,
+            import_existing_worktrees,
+            get_worktree_init_status,
+            cancel_worktree_copy,
+            cancel_worktree_creation,
+            resolve_worktree_path,
+            get_command_log,
             list_worktrees,
             delete_worktree,
             check_worktree_delete_status,
+            is_worktree_locked,
+            lock_worktree,
+            unlock_worktree,
             execute_delete_worktree_workflow,
+            prune_merged_worktrees,
             remove_stale_worktree,
             rename_worktree,
+            rename_worktree_branch,
+            relocate_worktree,
             reorder_projects,
             reorder_worktrees,
+            set_worktree_command,
+            set_worktree_environment,
             get_home_dir,
             open_folder,
             open_with_app,
             open_in_terminal,
             open_in_editor,
+            open_worktree_in,
             get_config_file_path,
+            reveal_config_dir,
+            reveal_state_dir,
             open_in_file_manager,
             open_default,
             spawn_main,
             spawn_terminal,
+            focus_session,
             spawn_scratch_terminal,
             spawn_shell,
             spawn_command,
             spawn_action,
             watch_merge_state,
+            list_conflicts,
+            resolve_conflict,
+            mark_resolved,
+            continue_merge,
             stop_merge_watcher,
             watch_rebase_state,
             stop_rebase_watcher,
@@ -1992,37 +3893,93 @@ pub fn run() {
             pty_write,
             pty_resize,
             pty_interrupt,
+            signal_session,
+            test_command,
+            set_session_detached,
             pty_kill,
             pty_force_kill,
+            session_stats,
+            session_activity,
             get_changed_files,
             get_branch_info,
+            refresh_all_worktrees,
+            classify_worktrees,
             get_branch_changed_files,
+            list_branches,
+            get_submodule_status,
+            get_branch_diff,
+            get_branch_file_diff,
+            diff_worktrees,
+            diff_worktrees_file,
+            export_worktree_patch,
+            export_project_bundle,
+            run_git_maintenance,
+            worktree_disk_usage,
+            install_worktree_hooks,
+            uninstall_worktree_hooks,
             get_file_diff_content,
+            get_file_diff,
             has_uncommitted_changes,
             stash_changes,
             stash_pop,
+            stash_worktree,
+            unstash_worktree,
+            list_stashes,
+            get_recent_commits,
+            get_default_branch,
+            fetch_worktree,
+            pull_worktree,
+            cancel_git_operation,
             abort_merge,
             abort_rebase,
             start_watching,
             stop_watching,
+            watch_git_log,
+            stop_git_log_watcher,
+            suspend_worktree,
+            resume_worktree,
+            set_worktree_inspect,
             get_config,
+            validate_config,
+            list_command_presets,
+            read_config_raw,
+            write_config_raw,
+            set_font_size,
+            set_font_family,
             watch_config,
             stop_config_watcher,
             get_mappings,
+            get_keybindings,
             watch_mappings,
             stop_mappings_watcher,
             expand_action_prompt,
             check_merge_feasibility,
+            divergence_summary,
+            merge_preflight,
             execute_merge_workflow,
             cleanup_worktree,
             shutdown,
+            is_shutdown_in_progress,
+            set_log_level,
+            process_health,
+            confirm_orphan_cleanup,
+            pending_orphans,
+            verify_pid_file,
+            list_instances,
+            app_metrics,
             update_action_availability,
+            get_layout,
+            save_layout,
             theme::list_themes,
             theme::read_theme,
+            theme::read_theme_by_name,
+            theme::theme_colors,
+            theme::resolve_theme,
         ])
         .on_window_event(|window, event| {
             match event {
                 tauri::WindowEvent::CloseRequested { api, .. } => {
+                    save_window_geometry(window);
                     // Prevent default close - let frontend handle it
                     api.prevent_close();
                     // Emit event to frontend to trigger shutdown flow
@@ -2031,6 +3988,7 @@ pub fn run() {
                 tauri::WindowEvent::Destroyed => {
                     // Final cleanup (in case frontend didn't trigger shutdown)
                     watcher::stop_all_watchers();
+                    ipc::remove_socket();
                 }
                 _ => {}
             }
@@ -2043,6 +4001,159 @@ pub fn run() {
         });
 }
 
+#[tauri::command]
+fn get_layout(state: State<'_, Arc<AppState>>) -> Option<state::Layout> {
+    state.persisted.read().layout.clone()
+}
+
+/// Store the layout. Rapid updates while the user is dragging or resizing are
+/// coalesced into a single write by the state's debounced save.
+#[tauri::command]
+fn save_layout(state: State<'_, Arc<AppState>>, layout: state::Layout) -> Result<()> {
+    state.mutate(|persisted| {
+        // Window geometry is captured by the backend on close; keep it unless the frontend sends one
+        let window = persisted.layout.as_ref().and_then(|l| l.window.clone());
+        persisted.layout = Some(state::Layout {
+            window: layout.window.clone().or(window),
+            ..layout
+        });
+    });
+    Ok(())
+}
+
+/// Record the main window's geometry so it can be restored on next launch
+fn save_window_geometry(window: &tauri::Window) {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    let geometry = state::WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+    };
+
+    let app_state = window.state::<Arc<AppState>>();
+    app_state.mutate(|persisted| {
+        persisted.layout.get_or_insert_with(state::Layout::default).window = Some(geometry);
+    });
+    // The window is closing, so write now rather than waiting for the debounced save
+    if let Err(e) = app_state.save() {
+        info!("Failed to save window geometry: {}", e);
+    }
+}
+
+/// Restore the main window's geometry from the persisted layout
+fn restore_window_geometry(window: &tauri::WebviewWindow, geometry: &state::WindowGeometry) {
+    // Ignore obviously broken geometry rather than restoring an unusable window
+    if geometry.width < 200 || geometry.height < 200 {
+        return;
+    }
+    let _ = window.set_size(tauri::PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(geometry.x, geometry.y));
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Handle a request received on the IPC socket
+fn handle_ipc_request(app: &AppHandle, request: ipc::IpcRequest) -> ipc::IpcResponse {
+    match request {
+        ipc::IpcRequest::NewWorktree { project_path, name } => {
+            let state = app.state::<Arc<AppState>>();
+
+            // Accept any path inside a project (e.g. the caller's working directory)
+            let project = {
+                let persisted = state.persisted.read();
+                persisted
+                    .projects
+                    .iter()
+                    .filter(|p| Path::new(&project_path).starts_with(&p.path))
+                    .max_by_key(|p| p.path.len())
+                    .map(|p| (p.id.clone(), p.path.clone()))
+            };
+            let Some((project_id, project_path)) = project else {
+                return ipc::IpcResponse::error(format!("No project contains {}", project_path));
+            };
+
+            match create_worktree_blocking(app, state.inner(), &project_path, name, None) {
+                Ok(CreatedWorktree { worktree, .. }) => {
+                    let _ = app.emit(
+                        "ipc-worktree-created",
+                        serde_json::json!({ "projectId": project_id, "worktree": worktree }),
+                    );
+                    ipc::IpcResponse::worktree(worktree)
+                }
+                Err(e) => ipc::IpcResponse::error(e),
+            }
+        }
+        ipc::IpcRequest::Focus => {
+            let Some(window) = app.get_webview_window("main") else {
+                return ipc::IpcResponse::error("Main window not found");
+            };
+            let _ = window.unminimize();
+            let _ = window.show();
+            let _ = window.set_focus();
+            ipc::IpcResponse::ok()
+        }
+    }
+}
+
+/// Ask the running instance to create a worktree for the project containing the
+/// current directory (called from main.rs when --new-worktree is passed).
+/// Returns the process exit code.
+pub fn run_new_worktree_cli(name: &str) -> i32 {
+    let project_path = match std::env::current_dir() {
+        Ok(dir) => dir.to_string_lossy().to_string(),
+        Err(e) => {
+            eprintln!("Failed to get current directory: {}", e);
+            return 1;
+        }
+    };
+
+    let request = ipc::IpcRequest::NewWorktree {
+        project_path,
+        name: Some(name.to_string()),
+    };
+    match ipc::send_request(&request) {
+        Ok(ipc::IpcResponse { ok: true, worktree: Some(worktree), .. }) => {
+            println!("Created worktree {} at {}", worktree.name, worktree.path);
+            0
+        }
+        Ok(response) => {
+            eprintln!("{}", response.error.unwrap_or_else(|| "Request failed".to_string()));
+            1
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Use another global config file for this run (called from main.rs when
+/// --config is passed). Fails if the file's directory is missing or not writable.
+pub fn set_config_path(path: &str) -> Result<()> {
+    let path = config::set_config_path_override(Path::new(path))?;
+    eprintln!("[startup] Using config file {}", path.display());
+    Ok(())
+}
+
+/// If single-instance mode is on (`singleInstance`, the default) and another
+/// instance is already running, ask it to focus its window. Returns true when
+/// this process should exit instead of starting (called from main.rs).
+pub fn hand_off_to_running_instance() -> bool {
+    if !config::load_config().single_instance || cleanup::acquire_instance_lock() {
+        return false;
+    }
+
+    if let Err(e) = ipc::send_request(&ipc::IpcRequest::Focus) {
+        eprintln!("Shellflow is already running, but it couldn't be focused: {}", e);
+    }
+    true
+}
+
 /// Run as a watchdog process (called from main.rs when --watchdog flag is passed)
 pub fn run_watchdog(parent_pid: u32) {
     cleanup::run_watchdog(parent_pid);
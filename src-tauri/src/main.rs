@@ -12,5 +12,28 @@ fn main() {
         }
     }
 
+    // Check for CLI mode: --new-worktree <name> (sent to the running instance)
+    if args.len() == 3 && args[1] == "--new-worktree" {
+        std::process::exit(shellflow_lib::run_new_worktree_cli(&args[2]));
+    }
+
+    // Check for --config <path>: use another global config file (e.g. one per profile)
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        let Some(path) = args.get(index + 1) else {
+            eprintln!("--config requires a path");
+            std::process::exit(2);
+        };
+        if let Err(e) = shellflow_lib::set_config_path(path) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+
+    // Only one instance runs at a time (unless disabled in config): a second
+    // launch focuses the existing window and exits
+    if shellflow_lib::hand_off_to_running_instance() {
+        return;
+    }
+
     shellflow_lib::run()
 }
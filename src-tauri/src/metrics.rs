@@ -0,0 +1,92 @@
+//! Lightweight self-metrics for the status bar and for diagnosing leaks.
+//!
+//! Everything here is read from in-memory counters, this instance's PID file
+//! and the OS's accounting for our own process, so it's cheap to poll.
+
+use crate::cleanup;
+use crate::state::AppState;
+use crate::watcher;
+use serde::Serialize;
+use std::time::Instant;
+
+lazy_static::lazy_static! {
+    /// When the backend started; initialized from `run()`
+    static ref STARTED_AT: Instant = Instant::now();
+}
+
+/// Record the start time. Call once, as early as possible.
+pub fn mark_start() {
+    lazy_static::initialize(&STARTED_AT);
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMetrics {
+    pub pty_sessions: usize,
+    /// Worktrees with a file watcher (all served by one dispatch thread)
+    pub watched_worktrees: usize,
+    pub merge_watchers: usize,
+    pub rebase_watchers: usize,
+    pub git_log_watchers: usize,
+    /// PIDs in this instance's PID file
+    pub tracked_pids: usize,
+    /// Resident memory of the backend process; `None` where it can't be read
+    pub resident_memory_bytes: Option<u64>,
+    pub uptime_secs: u64,
+}
+
+pub fn app_metrics(state: &AppState) -> AppMetrics {
+    AppMetrics {
+        pty_sessions: state.pty_sessions.read().len(),
+        watched_worktrees: watcher::watched_worktree_count(),
+        merge_watchers: watcher::merge_watcher_count(),
+        rebase_watchers: watcher::rebase_watcher_count(),
+        git_log_watchers: watcher::git_log_watcher_count(),
+        tracked_pids: cleanup::tracked_pid_count(),
+        resident_memory_bytes: resident_memory_bytes(),
+        uptime_secs: STARTED_AT.elapsed().as_secs(),
+    }
+}
+
+/// Linux: the second field of /proc/self/statm is resident pages
+#[cfg(target_os = "linux")]
+fn resident_memory_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    (page_size > 0).then(|| pages * page_size as u64)
+}
+
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn resident_memory_bytes() -> Option<u64> {
+    let mut info: libc::mach_task_basic_info = unsafe { std::mem::zeroed() };
+    let mut count = (std::mem::size_of::<libc::mach_task_basic_info>() / std::mem::size_of::<libc::natural_t>())
+        as libc::mach_msg_type_number_t;
+    let result = unsafe {
+        libc::task_info(
+            libc::mach_task_self(),
+            libc::MACH_TASK_BASIC_INFO,
+            &mut info as *mut _ as libc::task_info_t,
+            &mut count,
+        )
+    };
+    (result == libc::KERN_SUCCESS).then_some(info.resident_size)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn resident_memory_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn resident_memory_is_reported() {
+        let bytes = resident_memory_bytes().expect("resident memory should be readable");
+        assert!(bytes > 0);
+    }
+}
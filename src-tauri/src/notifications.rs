@@ -0,0 +1,55 @@
+use crate::config;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Background events that can raise a desktop notification
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationEvent {
+    MergeComplete,
+    MergeConflict,
+    ProcessFailed,
+}
+
+impl NotificationEvent {
+    fn enabled(self, cfg: &config::NotificationsConfig) -> bool {
+        match self {
+            NotificationEvent::MergeComplete => cfg.merge_complete,
+            NotificationEvent::MergeConflict => cfg.merge_conflict,
+            NotificationEvent::ProcessFailed => cfg.process_failed,
+        }
+    }
+}
+
+/// Show a native notification for an event, if enabled in config.
+/// Suppressed while the main window is focused, since the in-app UI already reflects the event.
+pub fn notify(app: &AppHandle, event: NotificationEvent, title: &str, body: &str) {
+    let cfg = config::load_config();
+    if !event.enabled(&cfg.notifications) {
+        return;
+    }
+
+    let focused = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false);
+    if focused {
+        return;
+    }
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("[notifications] Failed to show {:?} notification: {}", event, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_enable_merge_events_only() {
+        let cfg = config::NotificationsConfig::default();
+        assert!(NotificationEvent::MergeComplete.enabled(&cfg));
+        assert!(NotificationEvent::MergeConflict.enabled(&cfg));
+        assert!(!NotificationEvent::ProcessFailed.enabled(&cfg));
+    }
+}
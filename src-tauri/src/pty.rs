@@ -1,4 +1,5 @@
-use crate::state::{AppState, PtySession};
+use crate::config::{Config, EnvMap, LoginShell};
+use crate::state::{AppState, Pane, PtySession};
 use parking_lot::Mutex;
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use std::collections::HashMap;
@@ -6,7 +7,7 @@ use std::io::{Read, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use thiserror::Error;
 use uuid::Uuid;
@@ -53,6 +54,14 @@ pub enum PtyError {
     Io(#[from] std::io::Error),
     #[error("Session not found: {0}")]
     SessionNotFound(String),
+    #[error("Unsupported signal: {0}")]
+    UnsupportedSignal(String),
+    #[error("Process stats unavailable: {0}")]
+    StatsUnavailable(String),
+    #[error("Worktree {0} is in inspect mode; sessions can't be started in it")]
+    InspectMode(String),
+    #[error("Worktree {0} is suspended; resume it to start sessions")]
+    Suspended(String),
 }
 
 impl From<Box<dyn std::error::Error + Send + Sync>> for PtyError {
@@ -94,7 +103,38 @@ lazy_static::lazy_static! {
     // Cache the user's shell
     static ref CACHED_USER_SHELL: Mutex<Option<String>> = Mutex::new(None);
     // Track if shutdown is already in progress
-    pub(crate) static ref SHUTDOWN_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+    static ref SHUTDOWN_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+    // App handle for emitting shutdown-started from the signal handler thread
+    static ref SHUTDOWN_APP_HANDLE: Mutex<Option<AppHandle>> = Mutex::new(None);
+}
+
+/// Whether a shutdown (from the UI or a signal) has started
+pub fn is_shutdown_in_progress() -> bool {
+    SHUTDOWN_IN_PROGRESS.load(Ordering::SeqCst)
+}
+
+/// Register the app handle used to emit `shutdown-started`.
+/// Signal handlers are installed before the app exists, so this is set during setup.
+pub fn set_shutdown_app_handle(app: AppHandle) {
+    *SHUTDOWN_APP_HANDLE.lock() = Some(app);
+}
+
+/// Mark shutdown as started, emitting `shutdown-started` the first time.
+/// Returns true if a shutdown was already in progress.
+pub(crate) fn begin_shutdown(signal: Option<i32>) -> bool {
+    if SHUTDOWN_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return true;
+    }
+    if let Some(app) = SHUTDOWN_APP_HANDLE.lock().as_ref() {
+        let _ = app.emit("shutdown-started", serde_json::json!({ "signal": signal }));
+    }
+    false
+}
+
+/// Clear the shutdown flag, e.g. between tests that exercise shutdown
+#[cfg(test)]
+pub(crate) fn reset_shutdown_flag() {
+    SHUTDOWN_IN_PROGRESS.store(false, Ordering::SeqCst);
 }
 
 /// Get the user's PATH, using cached value if available.
@@ -110,8 +150,28 @@ pub fn get_cached_user_path() -> String {
     path
 }
 
+/// Find an executable in a PATH-style search list.
+/// Programs given as a path (containing a separator) are checked directly.
+pub fn find_program_in(program: &str, search_path: &str) -> Option<std::path::PathBuf> {
+    let candidate = std::path::Path::new(program);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+
+    std::env::split_paths(search_path)
+        .flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", program, ext))))
+        .find(|path| path.is_file())
+}
+
+/// Find an executable on the user's login-shell PATH
+pub fn find_program(program: &str) -> Option<std::path::PathBuf> {
+    find_program_in(program, &get_cached_user_path())
+}
+
 /// Get the user's shell, using cached value if available
-fn get_cached_user_shell() -> String {
+pub fn get_cached_user_shell() -> String {
     let mut cache = CACHED_USER_SHELL.lock();
     if let Some(shell) = cache.as_ref() {
         return shell.clone();
@@ -121,41 +181,44 @@ fn get_cached_user_shell() -> String {
     shell
 }
 
-pub fn spawn_pty(
-    app: &AppHandle,
-    state: &AppState,
-    worktree_id: &str,
-    worktree_path: &str,
-    command: &str,
-    cols: Option<u16>,
-    rows: Option<u16>,
-    shell_override: Option<&str>,
-    env_vars: Option<&std::collections::HashMap<String, String>>,
-) -> Result<String, PtyError> {
-    let pty_system = native_pty_system();
-
-    let pair = pty_system.openpty(PtySize {
-        rows: rows.unwrap_or(24),
-        cols: cols.unwrap_or(80),
-        pixel_width: 0,
-        pixel_height: 0,
-    })?;
-
-    // Get user's PATH (cached after first call)
-    let user_path = get_cached_user_path();
-
-    eprintln!("[PTY] Spawning command: '{}' in '{}' (raw: '{}')", command.split_whitespace().next().unwrap_or(command), worktree_path, command);
-    eprintln!("[PTY] PATH length: {} chars", user_path.len());
-    eprintln!("[PTY] Worktree path exists: {}", std::path::Path::new(worktree_path).exists());
-
-    // "shell" is a special command that spawns the user's login shell
-    // Any other command is run through the shell with -c to support shell features
+/// Payload of the `environment-profile-missing` event
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentProfileMissing {
+    pub worktree_id: String,
+    pub profile: String,
+}
 
-    // Use shell override if provided, otherwise use cached user shell
-    let shell = shell_override
-        .map(|s| s.to_string())
-        .unwrap_or_else(get_cached_user_shell);
+/// Variables from config for a terminal: `main.env`, then the selected profile
+/// from `environments`. An unknown profile contributes nothing and is returned
+/// as the second value so the caller can warn about it.
+pub fn config_env(config: &Config, profile: Option<&str>) -> (EnvMap, Option<String>) {
+    let mut env = config.main.env.clone();
+    let Some(profile) = profile else {
+        return (env, None);
+    };
+    match config.environments.get(profile) {
+        Some(vars) => {
+            env.extend(vars.iter().map(|(key, value)| (key.clone(), value.clone())));
+            (env, None)
+        }
+        None => (env, Some(profile.to_string())),
+    }
+}
 
+/// Build the command line for a session. "shell" starts the user's shell; with
+/// an explicit shell other commands run through `shell -c` so shell features
+/// work, known shells go through /usr/bin/env, and anything else runs directly.
+/// `login` says whether the shell is started as a login shell (`-l`), and
+/// whether commands are too (or run through one), so PATH etc. from profile
+/// scripts apply.
+fn session_command(
+    command: &str,
+    worktree_path: &str,
+    shell: &str,
+    shell_override: bool,
+    login: LoginShell,
+) -> CommandBuilder {
     // Parse command into executable and arguments
     let parts: Vec<&str> = command.split_whitespace().collect();
     let (executable, args) = if parts.is_empty() {
@@ -164,32 +227,37 @@ pub fn spawn_pty(
         (parts[0], parts[1..].to_vec())
     };
 
-    // Known shell commands that should be run as login shells
+    // Known shell commands are run as given
     let shell_commands = ["fish", "bash", "zsh", "sh"];
     let is_shell_command = shell_commands.iter().any(|s| executable == *s || executable.ends_with(&format!("/{}", s)));
 
     let mut cmd = if command == "shell" {
-        let mut cmd = CommandBuilder::new(&shell);
-        cmd.arg("-l");
-        cmd.cwd(worktree_path);
+        let mut cmd = CommandBuilder::new(shell);
+        if login.shell {
+            cmd.arg("-l");
+        }
         cmd
-    } else if shell_override.is_some() {
-        // When shell is explicitly specified, run the command through that shell
-        let mut cmd = CommandBuilder::new(&shell);
+    } else if shell_override || (login.commands && !is_shell_command) {
+        // Run the command through the shell (-l -c for a login shell)
+        let mut cmd = CommandBuilder::new(shell);
+        if login.commands {
+            cmd.arg("-l");
+        }
         cmd.arg("-c");
         cmd.arg(command);
-        cmd.cwd(worktree_path);
-        eprintln!("[PTY] Running command via {} -c: {:?}", shell, command);
+        eprintln!("[PTY] Running command via {} -c (login: {}): {:?}", shell, login.commands, command);
         cmd
     } else if is_shell_command {
         // Run shell commands via /usr/bin/env to avoid exec issues with portable_pty
         // See: https://github.com/rust-lang/rust/issues/125952
         let mut cmd = CommandBuilder::new("/usr/bin/env");
         cmd.arg(executable);
+        if login.commands {
+            cmd.arg("-l");
+        }
         for arg in &args {
             cmd.arg(*arg);
         }
-        cmd.cwd(worktree_path);
         eprintln!("[PTY] Running {} via /usr/bin/env with args {:?} (shell mode)", executable, args);
         cmd
     } else {
@@ -198,9 +266,86 @@ pub fn spawn_pty(
         for arg in &args {
             cmd.arg(*arg);
         }
-        cmd.cwd(worktree_path);
         cmd
     };
+    cmd.cwd(worktree_path);
+    cmd
+}
+
+pub fn spawn_pty(
+    app: &AppHandle,
+    state: &AppState,
+    worktree_id: &str,
+    worktree_path: &str,
+    command: &str,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    shell_override: Option<&str>,
+    env_vars: Option<&std::collections::HashMap<String, String>>,
+    pane: Pane,
+) -> Result<String, PtyError> {
+    // The owning project, and for worktrees their inspect and suspended flags and
+    // environment profile
+    let (inspect, suspended, environment, project_path) = state
+        .persisted
+        .read()
+        .projects
+        .iter()
+        .find_map(|p| {
+            if p.id == worktree_id {
+                return Some((false, false, None, Some(p.path.clone())));
+            }
+            p.worktrees
+                .iter()
+                .find(|w| w.id == worktree_id)
+                .map(|w| (w.inspect, w.suspended, w.environment.clone(), Some(p.path.clone())))
+        })
+        .unwrap_or((false, false, None, None));
+    if inspect {
+        return Err(PtyError::InspectMode(worktree_id.to_string()));
+    }
+    if suspended {
+        return Err(PtyError::Suspended(worktree_id.to_string()));
+    }
+
+    let config = crate::config::load_config_for_project(project_path.as_deref());
+    let (profile_env, missing_profile) = config_env(&config, environment.as_deref());
+    if let Some(profile) = missing_profile {
+        eprintln!("[PTY] Unknown environment profile '{}' for {}, using none", profile, worktree_id);
+        let _ = app.emit(
+            "environment-profile-missing",
+            EnvironmentProfileMissing {
+                worktree_id: worktree_id.to_string(),
+                profile,
+            },
+        );
+    }
+
+    let pty_system = native_pty_system();
+
+    let pair = pty_system.openpty(PtySize {
+        rows: rows.unwrap_or(24),
+        cols: cols.unwrap_or(80),
+        pixel_width: 0,
+        pixel_height: 0,
+    })?;
+
+    // Get user's PATH (cached after first call)
+    let user_path = get_cached_user_path();
+
+    eprintln!("[PTY] Spawning command: '{}' in '{}' (raw: '{}')", command.split_whitespace().next().unwrap_or(command), worktree_path, command);
+    eprintln!("[PTY] PATH length: {} chars", user_path.len());
+    eprintln!("[PTY] Worktree path exists: {}", std::path::Path::new(worktree_path).exists());
+
+    // Use shell override if provided, otherwise use cached user shell
+    let shell = shell_override
+        .map(|s| s.to_string())
+        .unwrap_or_else(get_cached_user_shell);
+    let login = LoginShell::resolve(match pane {
+        Pane::Main => config.main.login_shell,
+        Pane::Terminal => config.terminal.login_shell,
+    });
+    let mut cmd = session_command(command, worktree_path, &shell, shell_override.is_some(), login);
 
     cmd.env("PATH", &user_path);
     cmd.env("TERM", "xterm-256color");
@@ -235,6 +380,11 @@ pub fn spawn_pty(
     // PWD is important for some shells
     cmd.env("PWD", worktree_path);
 
+    // Global and worktree profile variables from config
+    for (key, value) in &profile_env {
+        cmd.env(key, value);
+    }
+
     // Apply custom environment variables from task config
     if let Some(env) = env_vars {
         for (key, value) in env {
@@ -281,9 +431,12 @@ pub fn spawn_pty(
     let session = Arc::new(PtySession {
         worktree_id: worktree_id.to_string(),
         child_pid,
+        detached: AtomicBool::new(false),
     });
     state.pty_sessions.write().insert(pty_id.clone(), session);
 
+    start_activity_tracking(app.clone(), pty_id.clone());
+
     // Spawn reader thread
     let app_handle = app.clone();
     let pty_id_clone = pty_id.clone();
@@ -338,6 +491,9 @@ pub fn spawn_pty(
                         }));
                     }
 
+                    crate::command_log::append(&pty_id_clone, &buf[..n]);
+                    record_activity_output(&pty_id_clone, &buf[..n]);
+
                     // Combine any leftover bytes with new data
                     utf8_buf.extend_from_slice(&buf[..n]);
 
@@ -377,6 +533,8 @@ pub fn spawn_pty(
                 }
             }
         }
+        stop_activity_tracking(&pty_id_clone);
+
         // Wait for child process to get exit status
         let exit_code = match child.wait() {
             Ok(status) => {
@@ -394,6 +552,15 @@ pub fn spawn_pty(
             crate::cleanup::remove_pid(child_pid_for_cleanup);
         }
 
+        if let Some(code) = exit_code.filter(|code| *code != 0) {
+            crate::notifications::notify(
+                &app_handle,
+                crate::notifications::NotificationEvent::ProcessFailed,
+                "Process exited",
+                &format!("{} exited with code {}", command_name, code),
+            );
+        }
+
         eprintln!("[PTY:{}] Reader thread exiting, emitting pty-exit event", pty_id_clone);
         let _ = app_handle.emit("pty-exit", serde_json::json!({
             "ptyId": pty_id_clone,
@@ -401,11 +568,139 @@ pub fn spawn_pty(
             "command": command_name,
             "exitCode": exit_code,
         }));
+        crate::command_log::forget(&pty_id_clone);
     });
 
     Ok(pty_id)
 }
 
+/// Mark a session as detached so crash-recovery cleanup (orphan sweep, emergency
+/// cleanup, watchdog) leaves its process running. Detached processes outlive a
+/// crashed app and are the user's responsibility to stop.
+pub fn set_session_detached(state: &AppState, session_id: &str, detached: bool) -> Result<(), PtyError> {
+    let session = state
+        .pty_sessions
+        .read()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| PtyError::SessionNotFound(session_id.to_string()))?;
+
+    session.detached.store(detached, Ordering::SeqCst);
+    if session.child_pid > 0 {
+        crate::cleanup::set_pid_detached(session.child_pid, detached);
+    }
+    Ok(())
+}
+
+/// Outcome of a trial run of a command, for the settings screen
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResult {
+    pub spawned: bool,
+    /// Exit code, if the process exited within the trial window
+    pub exit_code: Option<i32>,
+    /// Start of the process output (lossy UTF-8)
+    pub output_preview: String,
+    pub error: Option<String>,
+}
+
+/// How long a trial run may produce output before it's killed
+const TEST_COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+/// Maximum bytes of output kept for the preview
+const TEST_COMMAND_PREVIEW_BYTES: usize = 4096;
+
+/// Check that a command launches: spawn it in a throwaway PTY in `cwd`, collect up to
+/// a second of output (or until it exits), then kill it. Nothing is registered as a
+/// session or tracked for crash recovery, and the child is always reaped.
+pub fn test_command(command: &str, args: &[String], cwd: &str) -> TestResult {
+    test_command_with(command, args, cwd, &get_cached_user_path(), TEST_COMMAND_TIMEOUT)
+}
+
+fn test_command_with(command: &str, args: &[String], cwd: &str, search_path: &str, timeout: Duration) -> TestResult {
+    let failed = |error: String| TestResult {
+        spawned: false,
+        exit_code: None,
+        output_preview: String::new(),
+        error: Some(error),
+    };
+
+    let Some(program) = find_program_in(command, search_path) else {
+        return failed(format!("Command not found on PATH: {}", command));
+    };
+
+    let pair = match native_pty_system().openpty(PtySize {
+        rows: 24,
+        cols: 80,
+        pixel_width: 0,
+        pixel_height: 0,
+    }) {
+        Ok(pair) => pair,
+        Err(e) => return failed(e.to_string()),
+    };
+
+    let mut cmd = CommandBuilder::new(&program);
+    cmd.args(args);
+    cmd.cwd(cwd);
+    cmd.env("PATH", search_path);
+    cmd.env("TERM", "xterm-256color");
+
+    let mut child = match pair.slave.spawn_command(cmd) {
+        Ok(child) => child,
+        Err(e) => return failed(e.to_string()),
+    };
+    // Close our copy of the slave so reads end once the child exits
+    drop(pair.slave);
+
+    let (tx, rx) = std::sync::mpsc::channel::<Vec<u8>>();
+    if let Ok(mut reader) = pair.master.try_clone_reader() {
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let deadline = std::time::Instant::now() + timeout;
+    let mut output = Vec::new();
+    let mut exit_code = None;
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            exit_code = Some(status.exit_code() as i32);
+            // Pick up whatever was written just before exit
+            while let Ok(chunk) = rx.recv_timeout(Duration::from_millis(50)) {
+                output.extend_from_slice(&chunk);
+            }
+            break;
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            break;
+        }
+        if let Ok(chunk) = rx.recv_timeout((deadline - now).min(Duration::from_millis(20))) {
+            output.extend_from_slice(&chunk);
+        }
+    }
+
+    if exit_code.is_none() {
+        // Still running after the trial window - that counts as launching fine
+        let _ = child.kill();
+    }
+    // Always reap the child so it doesn't linger as a zombie
+    let _ = child.wait();
+    drop(pair.master);
+
+    output.truncate(TEST_COMMAND_PREVIEW_BYTES);
+    TestResult {
+        spawned: true,
+        exit_code,
+        output_preview: String::from_utf8_lossy(&output).to_string(),
+        error: None,
+    }
+}
+
 pub fn write_to_pty(_state: &AppState, pty_id: &str, data: &str) -> Result<(), PtyError> {
     let writers = PTY_WRITERS.lock();
     let writer = writers
@@ -439,8 +734,6 @@ pub fn resize_pty(_state: &AppState, pty_id: &str, cols: u16, rows: u16) -> Resu
 /// Send SIGINT to interrupt the foreground process in a PTY session
 #[cfg(unix)]
 pub fn interrupt_pty(state: &AppState, pty_id: &str) -> Result<(), PtyError> {
-    use libc::SIGINT;
-
     // Get the child PID
     let child_pid = state
         .pty_sessions
@@ -449,21 +742,73 @@ pub fn interrupt_pty(state: &AppState, pty_id: &str) -> Result<(), PtyError> {
         .map(|s| s.child_pid);
 
     if let Some(pid) = child_pid {
-        // Send SIGINT to the entire process group by using negative PID.
-        // The shell spawned by the PTY is the process group leader, so -pid
-        // sends the signal to the shell and all its children (like `yes`).
-        // This is instant, unlike using pgrep to find children.
-        let result = unsafe { libc::kill(-(pid as i32), SIGINT) == 0 };
+        signal_process_group(pid, libc::SIGINT);
+    }
 
-        // Fallback: send directly to the process in case it's not in the same group
-        if !result {
-            send_signal(pid, SIGINT);
-        }
+    Ok(())
+}
+
+/// Signals that can be sent to sessions by name
+pub const ALLOWED_SIGNALS: &[&str] = &["TERM", "INT", "HUP", "USR1", "USR2", "KILL"];
+
+/// The allowlisted name (`TERM`) for a signal name (`TERM`, `SIGTERM`, case-insensitive)
+pub fn canonical_signal_name(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    ALLOWED_SIGNALS.iter().copied().find(|allowed| *allowed == name)
+}
+
+/// Map an allowlisted signal name (`TERM`, `SIGTERM`, case-insensitive) to its number
+#[cfg(unix)]
+fn parse_signal(name: &str) -> Option<i32> {
+    match canonical_signal_name(name)? {
+        "TERM" => Some(libc::SIGTERM),
+        "INT" => Some(libc::SIGINT),
+        "HUP" => Some(libc::SIGHUP),
+        "USR1" => Some(libc::SIGUSR1),
+        "USR2" => Some(libc::SIGUSR2),
+        "KILL" => Some(libc::SIGKILL),
+        _ => None,
+    }
+}
+
+/// Send a signal to a session's process group, so it reaches foreground children too.
+/// Falls back to the process itself if it doesn't lead its own group.
+#[cfg(unix)]
+pub(crate) fn signal_process_group(pid: u32, signal: i32) -> bool {
+    // The shell spawned by the PTY is normally the process group leader, so -pid
+    // signals the shell and all its children (like `yes`). This is instant,
+    // unlike using pgrep to find children.
+    let leads_group = unsafe { libc::getpgid(pid as i32) } == pid as i32;
+    if leads_group && unsafe { libc::kill(-(pid as i32), signal) == 0 } {
+        return true;
     }
+    send_signal(pid, signal)
+}
+
+/// Send a named signal (one of TERM, INT, HUP, USR1, USR2, KILL) to a PTY session
+#[cfg(unix)]
+pub fn signal_session(state: &AppState, session_id: &str, signal: &str) -> Result<(), PtyError> {
+    let signal_number = parse_signal(signal).ok_or_else(|| PtyError::UnsupportedSignal(signal.to_string()))?;
+
+    let child_pid = state
+        .pty_sessions
+        .read()
+        .get(session_id)
+        .map(|s| s.child_pid)
+        .ok_or_else(|| PtyError::SessionNotFound(session_id.to_string()))?;
 
+    if !signal_process_group(child_pid, signal_number) {
+        return Err(PtyError::Io(std::io::Error::last_os_error()));
+    }
     Ok(())
 }
 
+#[cfg(not(unix))]
+pub fn signal_session(_state: &AppState, _session_id: &str, signal: &str) -> Result<(), PtyError> {
+    Err(PtyError::UnsupportedSignal(format!("{} (signals are not supported on this platform)", signal)))
+}
+
 #[cfg(not(unix))]
 pub fn interrupt_pty(_state: &AppState, _pty_id: &str) -> Result<(), PtyError> {
     // On non-Unix, this is a no-op (Ctrl+C should work via terminal)
@@ -493,6 +838,7 @@ pub fn kill_pty(state: &AppState, pty_id: &str) -> Result<(), PtyError> {
 
     // Note: Don't clean up state here - wait for pty-exit event
     // The process might still be running if it ignores SIGTERM
+    crate::command_log::forget(pty_id);
     Ok(())
 }
 
@@ -502,6 +848,7 @@ pub fn kill_pty(state: &AppState, pty_id: &str) -> Result<(), PtyError> {
     state.pty_sessions.write().remove(pty_id);
     PTY_WRITERS.lock().remove(pty_id);
     PTY_MASTERS.lock().remove(pty_id);
+    crate::command_log::forget(pty_id);
     Ok(())
 }
 
@@ -530,6 +877,7 @@ pub fn force_kill_pty(state: &AppState, pty_id: &str) -> Result<(), PtyError> {
     state.pty_sessions.write().remove(pty_id);
     PTY_WRITERS.lock().remove(pty_id);
     PTY_MASTERS.lock().remove(pty_id);
+    crate::command_log::forget(pty_id);
     Ok(())
 }
 
@@ -578,12 +926,43 @@ pub(crate) fn get_process_name(pid: u32) -> Option<String> {
         })
 }
 
+/// When a process started, as `ps` reports it. Together with the PID this
+/// tells a process apart from a later one that reused its PID.
+#[cfg(unix)]
+pub(crate) fn get_process_start(pid: u32) -> Option<String> {
+    Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "lstart="])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
 /// Send a signal to a process
 #[cfg(unix)]
 pub(crate) fn send_signal(pid: u32, signal: i32) -> bool {
     unsafe { libc::kill(pid as i32, signal) == 0 }
 }
 
+/// Get the direct (non-recursive) child PIDs of a process
+#[cfg(unix)]
+pub(crate) fn get_direct_child_pids(pid: u32) -> Vec<u32> {
+    Command::new("pgrep")
+        .args(["-P", &pid.to_string()])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.trim().parse::<u32>().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Get all child PIDs of a process (recursive)
 #[cfg(unix)]
 pub(crate) fn get_child_pids(pid: u32) -> Vec<u32> {
@@ -610,17 +989,361 @@ pub(crate) fn get_child_pids(pid: u32) -> Vec<u32> {
     children
 }
 
+/// Resource usage of a PTY session's process tree
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStats {
+    /// CPU use over the sampling interval; 100 is one fully busy core, so
+    /// multi-threaded trees can exceed it
+    pub cpu_percent: f64,
+    /// Resident memory summed over the processes
+    pub rss_bytes: u64,
+    pub num_processes: u32,
+}
+
+/// How long session_stats waits between its two CPU time readings
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const STATS_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Cumulative CPU time and current resident memory of one process
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+struct ProcessSample {
+    cpu_time: Duration,
+    rss_bytes: u64,
+}
+
+/// User and system time (in clock ticks) from the contents of `/proc/<pid>/stat`.
+/// The command name is parenthesised and may itself contain spaces or parens,
+/// so fields are counted from the last `)`.
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_stat_times(stat: &str) -> Option<(u64, u64)> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // After the name: state is field 3, utime field 14 and stime field 15
+    Some((fields.get(11)?.parse().ok()?, fields.get(12)?.parse().ok()?))
+}
+
+#[cfg(target_os = "linux")]
+fn sample_process(pid: u32) -> Option<ProcessSample> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (utime, stime) = parse_proc_stat_times(&stat)?;
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+    Some(ProcessSample {
+        cpu_time: Duration::from_secs_f64((utime + stime) as f64 / ticks_per_second),
+        rss_bytes: resident_pages * page_size,
+    })
+}
+
+/// Numerator and denominator converting Mach absolute time to nanoseconds. libc
+/// deprecates its mach bindings in favour of the mach2 crate; this is the only use.
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn mach_timebase() -> (u64, u64) {
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+    unsafe { libc::mach_timebase_info(&mut timebase) };
+    if timebase.denom == 0 { (1, 1) } else { (timebase.numer as u64, timebase.denom as u64) }
+}
+
+#[cfg(target_os = "macos")]
+fn sample_process(pid: u32) -> Option<ProcessSample> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let read = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            &mut info as *mut _ as *mut libc::c_void,
+            size,
+        )
+    };
+    if read != size {
+        return None;
+    }
+
+    // Task times are in Mach absolute time units, which are only nanoseconds on Intel
+    let (numer, denom) = mach_timebase();
+    let nanos = (info.pti_total_user + info.pti_total_system) as u128 * numer as u128 / denom as u128;
+    Some(ProcessSample {
+        cpu_time: Duration::from_nanos(nanos as u64),
+        rss_bytes: info.pti_resident_size,
+    })
+}
+
+/// CPU and memory use of a PTY session's process and, with `include_children`,
+/// everything it spawned. CPU time is cumulative, so it's read twice
+/// `STATS_SAMPLE_INTERVAL` apart and the difference divided by the wall time in
+/// between; this call blocks for that long. Processes that exit in between are
+/// left out. Memory is the RSS at the second reading.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn session_stats(state: &AppState, pty_id: &str, include_children: bool) -> Result<SessionStats, PtyError> {
+    let pid = {
+        let sessions = state.pty_sessions.read();
+        let session = sessions
+            .get(pty_id)
+            .ok_or_else(|| PtyError::SessionNotFound(pty_id.to_string()))?;
+        session.child_pid
+    };
+    if pid == 0 {
+        return Err(PtyError::StatsUnavailable(format!("session {} has no process", pty_id)));
+    }
+
+    let mut pids = vec![pid];
+    if include_children {
+        pids.extend(get_child_pids(pid));
+    }
+
+    let start = std::time::Instant::now();
+    let first: HashMap<u32, ProcessSample> =
+        pids.iter().filter_map(|&pid| Some((pid, sample_process(pid)?))).collect();
+    if first.is_empty() {
+        return Err(PtyError::StatsUnavailable(format!("process {} is not running", pid)));
+    }
+    thread::sleep(STATS_SAMPLE_INTERVAL);
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let mut stats = SessionStats { cpu_percent: 0.0, rss_bytes: 0, num_processes: 0 };
+    let mut cpu_seconds = 0.0;
+    for (pid, before) in &first {
+        let Some(after) = sample_process(*pid) else { continue };
+        cpu_seconds += after.cpu_time.saturating_sub(before.cpu_time).as_secs_f64();
+        stats.rss_bytes += after.rss_bytes;
+        stats.num_processes += 1;
+    }
+    stats.cpu_percent = cpu_seconds / elapsed * 100.0;
+    Ok(stats)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn session_stats(_state: &AppState, _pty_id: &str, _include_children: bool) -> Result<SessionStats, PtyError> {
+    Err(PtyError::StatsUnavailable("not supported on this platform".to_string()))
+}
+
+/// What a session appears to be doing, judged from its output.
+///
+/// This is a heuristic: it only sees output timing and the last line printed.
+/// Programs that redraw constantly (progress bars, clocks, `top`) look busy,
+/// programs that compute silently look idle, and prompts that don't end in a
+/// familiar marker are reported as idle rather than waiting for input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Activity {
+    /// No recent output and no recognisable prompt
+    Idle,
+    /// Producing output continuously
+    Busy,
+    /// Quiet, with a shell or agent prompt as the last line
+    WaitingForInput,
+}
+
+/// Payload of the `session-activity-changed` event
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionActivityChanged {
+    pub session_id: String,
+    pub activity: Activity,
+}
+
+/// How often every session's activity is re-evaluated, so quiet periods are noticed
+const ACTIVITY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Characters of recent (escape-stripped) output kept to find the last line
+const ACTIVITY_TAIL_CHARS: usize = 512;
+
+/// Output timing of one session
+struct ActivityTracker {
+    busy_after: Duration,
+    idle_after: Duration,
+    /// When the current run of output started
+    burst_start: Option<Instant>,
+    last_output: Option<Instant>,
+    /// Recent output with escape sequences removed
+    tail: String,
+    at_prompt: bool,
+    current: Activity,
+}
+
+impl ActivityTracker {
+    fn new(busy_after: Duration, idle_after: Duration) -> Self {
+        Self {
+            busy_after,
+            idle_after,
+            burst_start: None,
+            last_output: None,
+            tail: String::new(),
+            at_prompt: false,
+            current: Activity::Idle,
+        }
+    }
+
+    fn record_output(&mut self, data: &[u8], now: Instant) {
+        let continues_burst = self.last_output.is_some_and(|last| now.duration_since(last) < self.idle_after);
+        if !continues_burst {
+            self.burst_start = Some(now);
+        }
+        self.last_output = Some(now);
+
+        self.tail.push_str(&strip_escape_sequences(&String::from_utf8_lossy(data)));
+        let excess = self.tail.chars().count().saturating_sub(ACTIVITY_TAIL_CHARS);
+        if excess > 0 {
+            let cut = self.tail.char_indices().nth(excess).map_or(self.tail.len(), |(i, _)| i);
+            self.tail.drain(..cut);
+        }
+        self.at_prompt = last_visible_line(&self.tail).is_some_and(looks_like_prompt);
+    }
+
+    /// The activity at `now`. Short bursts (a keystroke echo, a single
+    /// status line) leave the previous classification unchanged.
+    fn classify(&self, now: Instant) -> Activity {
+        let (Some(burst_start), Some(last_output)) = (self.burst_start, self.last_output) else {
+            return self.current;
+        };
+        if now.duration_since(last_output) >= self.idle_after {
+            if self.at_prompt {
+                Activity::WaitingForInput
+            } else {
+                Activity::Idle
+            }
+        } else if last_output.duration_since(burst_start) >= self.busy_after {
+            Activity::Busy
+        } else {
+            self.current
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SESSION_ACTIVITY: Mutex<HashMap<String, ActivityTracker>> = Mutex::new(HashMap::new());
+}
+
+/// Starts the one thread that re-evaluates all tracked sessions
+static ACTIVITY_TICKER: std::sync::Once = std::sync::Once::new();
+
+/// Remove ANSI escape sequences (CSI, OSC and two-byte escapes) and other
+/// control characters except newlines and carriage returns
+fn strip_escape_sequences(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters then a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: terminated by BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' {
+                            chars.next_if_eq(&'\\');
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The last non-blank line of output as it would appear on screen, taking the
+/// text after a line's final carriage return
+fn last_visible_line(text: &str) -> Option<&str> {
+    text.split('\n')
+        .filter_map(|line| line.rsplit('\r').find(|part| !part.trim().is_empty()))
+        .next_back()
+}
+
+/// Whether a line looks like a shell prompt, an agent's input prompt or a
+/// yes/no question
+fn looks_like_prompt(line: &str) -> bool {
+    const PROMPT_ENDINGS: [char; 7] = ['$', '#', '%', '>', '❯', '›', '➜'];
+    const PROMPT_MARKERS: [&str; 4] = ["? for shortcuts", "(y/n)", "[y/N]", "[Y/n]"];
+
+    // Agent TUIs draw their input line inside a box
+    let line = line.trim().trim_start_matches(['│', '┃']).trim();
+    line.ends_with(PROMPT_ENDINGS)
+        || line.starts_with(['>', '❯', '›'])
+        || PROMPT_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+/// Start tracking a session's activity and emit `session-activity-changed`
+/// whenever its classification changes. Thresholds come from
+/// `terminal.activity` at spawn time. Tracking stops once the session's
+/// reader thread calls `stop_activity_tracking`. All sessions share one ticker
+/// thread, started with the first session and kept for the app's lifetime.
+fn start_activity_tracking(app: AppHandle, session_id: String) {
+    let config = crate::config::load_config().terminal.activity;
+    let tracker = ActivityTracker::new(
+        Duration::from_millis(config.busy_after_ms),
+        Duration::from_millis(config.idle_after_ms),
+    );
+    SESSION_ACTIVITY.lock().insert(session_id, tracker);
+
+    ACTIVITY_TICKER.call_once(|| {
+        thread::spawn(move || loop {
+            thread::sleep(ACTIVITY_POLL_INTERVAL);
+            for (session_id, activity) in tick_activity(Instant::now()) {
+                let _ = app.emit("session-activity-changed", SessionActivityChanged { session_id, activity });
+            }
+        });
+    });
+}
+
+/// Re-evaluate every tracked session at `now`, returning those whose activity changed
+fn tick_activity(now: Instant) -> Vec<(String, Activity)> {
+    let mut trackers = SESSION_ACTIVITY.lock();
+    trackers
+        .iter_mut()
+        .filter_map(|(session_id, tracker)| {
+            let activity = tracker.classify(now);
+            let changed = activity != tracker.current;
+            tracker.current = activity;
+            changed.then(|| (session_id.clone(), activity))
+        })
+        .collect()
+}
+
+fn record_activity_output(session_id: &str, data: &[u8]) {
+    if let Some(tracker) = SESSION_ACTIVITY.lock().get_mut(session_id) {
+        tracker.record_output(data, Instant::now());
+    }
+}
+
+fn stop_activity_tracking(session_id: &str) {
+    SESSION_ACTIVITY.lock().remove(session_id);
+}
+
+/// Heuristic activity of a running session (see `Activity` for its limits)
+pub fn session_activity(state: &AppState, session_id: &str) -> Result<Activity, PtyError> {
+    if !state.pty_sessions.read().contains_key(session_id) {
+        return Err(PtyError::SessionNotFound(session_id.to_string()));
+    }
+    Ok(SESSION_ACTIVITY.lock().get(session_id).map_or(Activity::Idle, |tracker| tracker.current))
+}
+
 /// Shutdown all PTY sessions gracefully with cascading signals
-/// Returns when all processes have been terminated
+/// Returns when all processes have been terminated.
+/// Callers start the shutdown with `begin_shutdown` first, so this runs at most once.
 #[cfg(unix)]
 pub fn shutdown_all_ptys(app: &AppHandle, state: &AppState) {
     use libc::{SIGHUP, SIGKILL, SIGTERM};
 
-    // Prevent double-shutdown
-    if SHUTDOWN_IN_PROGRESS.swap(true, Ordering::SeqCst) {
-        return;
-    }
-
     let emit_progress = |phase: &str, message: &str, process_name: Option<String>, pid: Option<u32>, signal: Option<&str>| {
         let _ = app.emit("shutdown-progress", ShutdownProgress {
             phase: phase.to_string(),
@@ -683,12 +1406,13 @@ pub fn shutdown_all_ptys(app: &AppHandle, state: &AppState) {
     // Wait for processes to exit
     thread::sleep(Duration::from_millis(500));
 
-    // Phase 2: Send SIGTERM to remaining processes
+    // Phase 2: Send `terminal.shutdownSignal` (SIGTERM by default) to remaining processes
     let remaining: Vec<_> = all_pids.iter().filter(|(pid, _)| is_process_alive(*pid)).cloned().collect();
     if !remaining.is_empty() {
+        let shutdown_signal = parse_signal(&crate::config::load_config().terminal.shutdown_signal).unwrap_or(SIGTERM);
         for (pid, _) in &remaining {
             if is_process_alive(*pid) {
-                send_signal(*pid, SIGTERM);
+                send_signal(*pid, shutdown_signal);
             }
         }
         thread::sleep(Duration::from_millis(500));
@@ -710,6 +1434,7 @@ pub fn shutdown_all_ptys(app: &AppHandle, state: &AppState) {
         state.pty_sessions.write().remove(pty_id);
         PTY_WRITERS.lock().remove(pty_id);
         PTY_MASTERS.lock().remove(pty_id);
+        crate::command_log::forget(pty_id);
     }
 
     // Delete PID file on clean shutdown
@@ -734,6 +1459,7 @@ pub fn shutdown_all_ptys(app: &AppHandle, state: &AppState) {
         state.pty_sessions.write().remove(&pty_id);
         PTY_WRITERS.lock().remove(&pty_id);
         PTY_MASTERS.lock().remove(&pty_id);
+        crate::command_log::forget(&pty_id);
     }
 }
 
@@ -742,6 +1468,75 @@ mod tests {
     use super::*;
     use crate::state::AppState;
 
+    #[test]
+    fn parses_proc_stat_times_with_odd_command_names() {
+        let stat = "4242 (my (weird) cmd) S 1 4242 4242 0 -1 4194304 100 0 0 0 37 12 0 0 20 0 1 0 100 0 0";
+        assert_eq!(parse_proc_stat_times(stat), Some((37, 12)));
+        assert_eq!(parse_proc_stat_times("4242 (truncated) S 1"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn samples_the_current_process() {
+        let sample = sample_process(std::process::id()).unwrap();
+        assert!(sample.rss_bytes > 0);
+        assert!(sample_process(u32::MAX).is_none());
+    }
+
+    #[test]
+    fn shutdown_flag_is_observable_and_resettable() {
+        reset_shutdown_flag();
+        assert!(!is_shutdown_in_progress());
+        assert!(!begin_shutdown(Some(15)));
+        assert!(is_shutdown_in_progress());
+        // A second signal sees the shutdown already running
+        assert!(begin_shutdown(Some(15)));
+        reset_shutdown_flag();
+        assert!(!is_shutdown_in_progress());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program_in_searches_path_entries() {
+        let found = find_program_in("sh", "/nonexistent-dir:/bin:/usr/bin");
+        assert!(found.is_some(), "sh should be found in /bin or /usr/bin");
+        assert_eq!(find_program_in("definitely-not-a-real-program", "/bin:/usr/bin"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_program_in_accepts_explicit_paths() {
+        assert!(find_program_in("/bin/sh", "").is_some());
+        assert_eq!(find_program_in("/nonexistent-dir/sh", "/bin"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_reports_exit_and_output() {
+        let args = vec!["-c".to_string(), "echo hello; exit 3".to_string()];
+        let result = test_command_with("sh", &args, "/", "/bin:/usr/bin", Duration::from_secs(5));
+        assert!(result.spawned);
+        assert_eq!(result.exit_code, Some(3));
+        assert!(result.output_preview.contains("hello"), "output: {:?}", result.output_preview);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_kills_long_running_process() {
+        let start = std::time::Instant::now();
+        let result = test_command_with("sleep", &["30".to_string()], "/", "/bin:/usr/bin", Duration::from_millis(200));
+        assert!(result.spawned);
+        assert_eq!(result.exit_code, None);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_command_reports_missing_program() {
+        let result = test_command_with("definitely-not-a-real-program", &[], "/", "/bin:/usr/bin", Duration::from_millis(200));
+        assert!(!result.spawned);
+        assert!(result.error.unwrap().contains("not found"));
+    }
+
     #[test]
     fn test_interrupt_pty_returns_ok_for_missing_session() {
         // interrupt_pty should not panic or error when session doesn't exist
@@ -750,6 +1545,37 @@ mod tests {
         assert!(result.is_ok(), "interrupt_pty should succeed even for missing session");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_signal_accepts_allowlist_only() {
+        assert_eq!(parse_signal("HUP"), Some(libc::SIGHUP));
+        assert_eq!(parse_signal("sigusr1"), Some(libc::SIGUSR1));
+        assert_eq!(parse_signal("KILL"), Some(libc::SIGKILL));
+        assert_eq!(parse_signal("STOP"), None);
+        assert_eq!(parse_signal("9"), None);
+    }
+
+    #[test]
+    fn test_canonical_signal_name() {
+        assert_eq!(canonical_signal_name(" sigint "), Some("INT"));
+        assert_eq!(canonical_signal_name("Term"), Some("TERM"));
+        assert_eq!(canonical_signal_name("SIGSTOP"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_session_rejects_unknown_signal_and_session() {
+        let state = AppState::new();
+        assert!(matches!(
+            signal_session(&state, "nonexistent-pty-id", "SEGV"),
+            Err(PtyError::UnsupportedSignal(_))
+        ));
+        assert!(matches!(
+            signal_session(&state, "nonexistent-pty-id", "HUP"),
+            Err(PtyError::SessionNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_kill_pty_returns_ok_for_missing_session() {
         // kill_pty should not panic or error when session doesn't exist
@@ -845,4 +1671,203 @@ mod tests {
         assert!(!shell.is_empty(), "User shell should not be empty");
         assert!(shell.contains("sh"), "Shell should contain 'sh': {}", shell);
     }
+
+    fn test_tracker() -> ActivityTracker {
+        ActivityTracker::new(Duration::from_millis(1000), Duration::from_millis(2000))
+    }
+
+    #[test]
+    fn test_activity_is_busy_after_continuous_output() {
+        let mut tracker = test_tracker();
+        let start = Instant::now();
+        for i in 0..=12 {
+            tracker.record_output(b"thinking...\n", start + Duration::from_millis(i * 100));
+        }
+        assert_eq!(tracker.classify(start + Duration::from_millis(1300)), Activity::Busy);
+    }
+
+    #[test]
+    fn test_activity_short_burst_keeps_previous_state() {
+        let mut tracker = test_tracker();
+        let start = Instant::now();
+        tracker.record_output(b"l", start);
+        assert_eq!(tracker.classify(start + Duration::from_millis(100)), Activity::Idle);
+
+        tracker.current = Activity::WaitingForInput;
+        assert_eq!(tracker.classify(start + Duration::from_millis(100)), Activity::WaitingForInput);
+    }
+
+    #[test]
+    fn test_activity_tick_reports_only_changed_sessions() {
+        let start = Instant::now();
+        let mut busy = test_tracker();
+        busy.record_output(b"building...", start);
+        busy.record_output(b"still building...", start + Duration::from_millis(1200));
+        let quiet = test_tracker();
+        SESSION_ACTIVITY.lock().insert("tick-busy".to_string(), busy);
+        SESSION_ACTIVITY.lock().insert("tick-quiet".to_string(), quiet);
+
+        let changed = tick_activity(start + Duration::from_millis(1300));
+        assert!(changed.contains(&("tick-busy".to_string(), Activity::Busy)));
+        assert!(!changed.iter().any(|(id, _)| id == "tick-quiet"));
+        assert!(!tick_activity(start + Duration::from_millis(1400)).iter().any(|(id, _)| id == "tick-busy"));
+
+        stop_activity_tracking("tick-busy");
+        stop_activity_tracking("tick-quiet");
+    }
+
+    #[test]
+    fn test_activity_quiet_period_is_idle_or_waiting() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(2500);
+
+        let mut tracker = test_tracker();
+        tracker.record_output(b"Compiling shellflow\n", start);
+        assert_eq!(tracker.classify(later), Activity::Idle);
+
+        let mut tracker = test_tracker();
+        tracker.record_output(b"done\r\n\x1b[1;32m~/repo\x1b[0m $ ", start);
+        assert_eq!(tracker.classify(later), Activity::WaitingForInput);
+    }
+
+    #[test]
+    fn test_strip_escape_sequences() {
+        assert_eq!(strip_escape_sequences("\x1b[1;32mok\x1b[0m\r\n"), "ok\r\n");
+        assert_eq!(strip_escape_sequences("\x1b]0;title\x07$ "), "$ ");
+        assert_eq!(strip_escape_sequences("\x1b]0;title\x1b\\a\x08b"), "ab");
+    }
+
+    #[test]
+    fn test_last_visible_line_uses_text_after_carriage_return() {
+        assert_eq!(last_visible_line("one\ntwo\n\n"), Some("two"));
+        assert_eq!(last_visible_line("50%\r100%\r\n"), Some("100%"));
+        assert_eq!(last_visible_line("\n  \n"), None);
+    }
+
+    #[test]
+    fn test_looks_like_prompt() {
+        assert!(looks_like_prompt("user@host:~/repo$ "));
+        assert!(looks_like_prompt("~/repo ❯"));
+        assert!(looks_like_prompt("│ > "));
+        assert!(looks_like_prompt("  ? for shortcuts"));
+        assert!(looks_like_prompt("Overwrite file? [y/N]"));
+        assert!(!looks_like_prompt("Compiling shellflow v0.1.0"));
+    }
+
+    fn argv(cmd: &CommandBuilder) -> Vec<String> {
+        cmd.get_argv().iter().map(|arg| arg.to_string_lossy().to_string()).collect()
+    }
+
+    #[test]
+    fn test_session_command_uses_login_shell_when_enabled() {
+        let on = LoginShell { shell: true, commands: true };
+        let off = LoginShell { shell: false, commands: false };
+
+        let shell = session_command("shell", "/tmp", "/bin/zsh", false, on);
+        assert_eq!(argv(&shell), vec!["/bin/zsh", "-l"]);
+        let shell = session_command("shell", "/tmp", "/bin/zsh", false, off);
+        assert_eq!(argv(&shell), vec!["/bin/zsh"]);
+
+        // Plain commands go through the login shell so profile PATH applies
+        let claude = session_command("claude --resume", "/tmp", "/bin/zsh", false, on);
+        assert_eq!(argv(&claude), vec!["/bin/zsh", "-l", "-c", "claude --resume"]);
+        let claude = session_command("claude --resume", "/tmp", "/bin/zsh", false, off);
+        assert_eq!(argv(&claude), vec!["claude", "--resume"]);
+
+        let explicit = session_command("npm run dev", "/tmp", "/bin/bash", true, on);
+        assert_eq!(argv(&explicit), vec!["/bin/bash", "-l", "-c", "npm run dev"]);
+        let explicit = session_command("npm run dev", "/tmp", "/bin/bash", true, off);
+        assert_eq!(argv(&explicit), vec!["/bin/bash", "-c", "npm run dev"]);
+
+        // A shell given as the command honours the setting too
+        let zsh = session_command("/bin/zsh", "/tmp", "/bin/zsh", false, on);
+        assert_eq!(argv(&zsh), vec!["/usr/bin/env", "/bin/zsh", "-l"]);
+        let zsh = session_command("/bin/zsh", "/tmp", "/bin/zsh", false, off);
+        assert_eq!(argv(&zsh), vec!["/usr/bin/env", "/bin/zsh"]);
+    }
+
+    #[test]
+    fn test_unset_login_shell_keeps_the_shell_a_login_shell() {
+        let unset = LoginShell::resolve(None);
+        assert!(unset.shell);
+        assert_eq!(unset.commands, cfg!(target_os = "macos"));
+        assert_eq!(LoginShell::resolve(Some(false)), LoginShell { shell: false, commands: false });
+        assert_eq!(LoginShell::resolve(Some(true)), LoginShell { shell: true, commands: true });
+
+        let shell = session_command("shell", "/tmp", "/bin/zsh", false, unset);
+        assert_eq!(argv(&shell), vec!["/bin/zsh", "-l"]);
+    }
+
+    fn profile_config() -> Config {
+        let mut config = Config::default();
+        config.main.env = EnvMap::from([
+            ("EDITOR".to_string(), "vim".to_string()),
+            ("AWS_PROFILE".to_string(), "default".to_string()),
+        ]);
+        config.environments.insert(
+            "work".to_string(),
+            EnvMap::from([
+                ("AWS_PROFILE".to_string(), "work".to_string()),
+                ("GH_HOST".to_string(), "github.example.com".to_string()),
+            ]),
+        );
+        config
+    }
+
+    #[test]
+    fn test_config_env_applies_profile_after_main_env() {
+        let config = profile_config();
+
+        let (env, missing) = config_env(&config, Some("work"));
+        assert_eq!(missing, None);
+        assert_eq!(env["EDITOR"], "vim");
+        assert_eq!(env["AWS_PROFILE"], "work");
+        assert_eq!(env["GH_HOST"], "github.example.com");
+
+        let (env, missing) = config_env(&config, None);
+        assert_eq!(missing, None);
+        assert_eq!(env, config.main.env);
+    }
+
+    #[test]
+    fn test_config_env_falls_back_to_main_env_for_unknown_profile() {
+        let config = profile_config();
+        let (env, missing) = config_env(&config, Some("personal"));
+        assert_eq!(missing.as_deref(), Some("personal"));
+        assert_eq!(env, config.main.env);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_child_sees_profile_variables() {
+        let (env, _) = config_env(&profile_config(), Some("work"));
+
+        let pair = native_pty_system()
+            .openpty(PtySize { rows: 24, cols: 80, pixel_width: 0, pixel_height: 0 })
+            .unwrap();
+        let mut cmd = CommandBuilder::new("/bin/sh");
+        cmd.args(["-c", "echo \"$EDITOR $AWS_PROFILE $GH_HOST\""]);
+        for (key, value) in &env {
+            cmd.env(key, value);
+        }
+        let mut child = pair.slave.spawn_command(cmd).unwrap();
+        drop(pair.slave);
+
+        let mut reader = pair.master.try_clone_reader().unwrap();
+        let mut output = Vec::new();
+        let mut buf = [0u8; 1024];
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            output.extend_from_slice(&buf[..n]);
+            if String::from_utf8_lossy(&output).contains('\n') {
+                break;
+            }
+        }
+        let _ = child.wait();
+
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("vim work github.example.com"), "output: {:?}", output);
+    }
 }
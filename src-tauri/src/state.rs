@@ -1,6 +1,7 @@
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 fn default_true() -> bool {
@@ -19,6 +20,34 @@ pub struct Project {
     pub is_active: bool,
     #[serde(default, rename = "lastAccessedAt")]
     pub last_accessed_at: Option<String>,
+    /// Root of the git repository containing `path`; differs from `path` when the
+    /// project is a subfolder of a monorepo
+    #[serde(default, rename = "repoRoot", skip_serializing_if = "Option::is_none")]
+    pub repo_root: Option<String>,
+}
+
+impl Project {
+    /// Path to run repository-wide git operations against
+    pub fn repo_path(&self) -> &str {
+        self.repo_root.as_deref().unwrap_or(&self.path)
+    }
+
+    /// Detect and store the repository root, if not already known
+    pub fn detect_repo_root(&mut self) -> bool {
+        if self.repo_root.is_some() {
+            return false;
+        }
+        self.repo_root = crate::git::find_repo_root(std::path::Path::new(&self.path))
+            .map(|root| root.to_string_lossy().to_string());
+        self.repo_root.is_some()
+    }
+
+    /// Let git operations on the project path open its repository by the stored root
+    pub fn register_repo_root(&self) {
+        if let Some(root) = &self.repo_root {
+            crate::git::register_repo_root(std::path::Path::new(&self.path), std::path::Path::new(root));
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,21 +56,56 @@ pub struct Worktree {
     pub name: String,
     pub path: String,
     pub branch: String,
+    /// Branch the worktree was created from: the default target for merges and branch diffs
+    #[serde(default, rename = "baseBranch", skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(default)]
     pub order: i32,
+    /// Command for this worktree's main pane, overriding `main.command`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Extra arguments appended to `command`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Paused by the user: no processes or watchers until resumed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool,
+    /// Read-only review (e.g. of a mounted checkout): no PTYs, status by polling
+    /// instead of a file watcher, and mutating git commands are refused
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inspect: bool,
+    /// Name of the config `environments` profile applied to this worktree's terminals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Worktree {
+    /// The main-pane command override, with its arguments quoted for the shell and appended
+    pub fn command_line(&self) -> Option<String> {
+        let command = self.command.clone()?;
+        let args = self.args.iter().map(|arg| shell_escape::escape(arg.as_str().into()).into_owned());
+        Some(std::iter::once(command).chain(args).collect::<Vec<_>>().join(" "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileChange {
     pub path: String,
     pub status: FileStatus,
     pub insertions: Option<usize>,
     pub deletions: Option<usize>,
+    /// File mode (e.g. the executable bit) changed. Only detected when
+    /// `git.detectModeChanges` is enabled.
+    #[serde(default, rename = "modeChanged", skip_serializing_if = "std::ops::Not::not")]
+    pub mode_changed: bool,
+    /// Where a renamed or copied file came from
+    #[serde(default, rename = "oldPath", skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FileStatus {
     Added,
@@ -49,6 +113,11 @@ pub enum FileStatus {
     Deleted,
     Renamed,
     Untracked,
+    /// Unmerged during a merge, rebase or cherry-pick
+    Conflicted,
+    Copied,
+    /// Switched between regular file, symlink or submodule
+    TypeChanged,
 }
 
 /// Information about the current branch relative to base branch
@@ -75,68 +144,300 @@ pub struct DiffContent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedState {
     pub projects: Vec<Project>,
+    /// Window and pane layout, restored on next launch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
 }
 
 impl Default for PersistedState {
     fn default() -> Self {
-        Self { projects: vec![] }
+        Self {
+            projects: vec![],
+            layout: None,
+        }
+    }
+}
+
+/// Current layout format version
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// Persisted window geometry and pane split positions.
+/// Every field is optional/defaulted so older or partial layouts still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Layout {
+    pub version: u32,
+    pub window: Option<WindowGeometry>,
+    /// Split sizes (percentages) keyed by panel group id
+    pub splits: HashMap<String, Vec<f64>>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            window: None,
+            splits: HashMap::new(),
+        }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
 #[allow(dead_code)]
 pub struct PtySession {
     pub worktree_id: String,
     pub child_pid: u32,
+    /// Excluded from crash-recovery cleanup; the process is the user's responsibility
+    pub detached: std::sync::atomic::AtomicBool,
+}
+
+/// A worktree pane that runs its own PTY session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pane {
+    Main,
+    Terminal,
+}
+
+/// Transient (not persisted) status of a worktree's init command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum InitStatus {
+    Running,
+    Succeeded {
+        #[serde(rename = "durationMs")]
+        duration_ms: u64,
+    },
+    Failed {
+        #[serde(rename = "exitCode")]
+        exit_code: Option<i32>,
+        #[serde(rename = "durationMs")]
+        duration_ms: u64,
+    },
+    /// Long-running init command tracked as a PTY session
+    Persistent {
+        #[serde(rename = "ptyId")]
+        pty_id: String,
+    },
+}
+
+/// A worktree creation that can still be cancelled, from before `git worktree add` until
+/// its background work (file copy, init command) finishes
+pub struct PendingCreation {
+    /// Set once the git worktree exists
+    pub worktree_id: std::sync::OnceLock<String>,
+    pub cancel: crate::git::CancelToken,
+    /// PID of the running one-shot init command, if any
+    pub init_pid: parking_lot::Mutex<Option<u32>>,
+}
+
+/// Mutations made within this long of the first one are persisted by a single save
+const SAVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Writes the persisted state somewhere durable
+pub type StateWriter = dyn Fn(&PersistedState) -> std::io::Result<()> + Send + Sync;
+
+/// Debounces state saves: the first mutation in a window schedules a save on a
+/// background thread, and later mutations in that window are covered by it.
+/// Writes are serialized, so scheduled and immediate saves never share the temp file.
+struct SaveScheduler {
+    delay: std::time::Duration,
+    pending: Arc<std::sync::atomic::AtomicBool>,
+    writer: Arc<StateWriter>,
+    write_lock: Arc<parking_lot::Mutex<()>>,
+}
+
+impl SaveScheduler {
+    fn schedule(&self, persisted: &Arc<RwLock<PersistedState>>) {
+        use std::sync::atomic::Ordering;
+
+        if self.pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let delay = self.delay;
+        let pending = Arc::clone(&self.pending);
+        let writer = Arc::clone(&self.writer);
+        let write_lock = Arc::clone(&self.write_lock);
+        let persisted = Arc::clone(persisted);
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let _write = write_lock.lock();
+            // Cleared before reading so a mutation made during the write schedules
+            // another save; already cleared if an immediate save covered this one
+            if !pending.swap(false, Ordering::SeqCst) {
+                return;
+            }
+            if let Err(e) = writer(&persisted.read()) {
+                log::warn!("[State] Failed to save state: {}", e);
+            }
+        });
+    }
+
+    /// Write now, covering any scheduled save that hasn't written yet
+    fn flush(&self, persisted: &RwLock<PersistedState>) -> std::io::Result<()> {
+        let _write = self.write_lock.lock();
+        self.pending.store(false, std::sync::atomic::Ordering::SeqCst);
+        (self.writer)(&persisted.read())
+    }
 }
 
 pub struct AppState {
-    pub persisted: RwLock<PersistedState>,
+    /// Read freely; change only through `mutate` and the other mutation methods,
+    /// which schedule a save
+    pub persisted: Arc<RwLock<PersistedState>>,
     pub pty_sessions: RwLock<HashMap<String, Arc<PtySession>>>,
+    pub init_status: RwLock<HashMap<String, InitStatus>>,
+    /// Stop flags for in-flight gitignored-file copies, keyed by worktree ID
+    pub copy_cancellations: RwLock<HashMap<String, Arc<std::sync::atomic::AtomicBool>>>,
+    /// In-flight worktree creations, keyed by creation operation ID
+    pub worktree_creations: RwLock<HashMap<String, Arc<PendingCreation>>>,
+    /// PTY session of each spawned worktree pane, keyed by worktree ID and pane
+    pub pane_sessions: RwLock<HashMap<(String, Pane), String>>,
+    saver: SaveScheduler,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        Self::with_writer(SAVE_DEBOUNCE, Arc::new(write_home_state))
+    }
+
+    /// State whose debounced saves go through `writer` after `delay`
+    pub fn with_writer(delay: std::time::Duration, writer: Arc<StateWriter>) -> Self {
         Self {
-            persisted: RwLock::new(PersistedState::default()),
+            persisted: Arc::new(RwLock::new(PersistedState::default())),
             pty_sessions: RwLock::new(HashMap::new()),
+            init_status: RwLock::new(HashMap::new()),
+            copy_cancellations: RwLock::new(HashMap::new()),
+            worktree_creations: RwLock::new(HashMap::new()),
+            pane_sessions: RwLock::new(HashMap::new()),
+            saver: SaveScheduler {
+                delay,
+                pending: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                writer,
+                write_lock: Arc::new(parking_lot::Mutex::new(())),
+            },
         }
     }
 
+    /// Change the persisted state under the write lock and schedule a save
+    pub fn mutate<R>(&self, f: impl FnOnce(&mut PersistedState) -> R) -> R {
+        let result = f(&mut self.persisted.write());
+        self.saver.schedule(&self.persisted);
+        result
+    }
+
+    pub fn add_project(&self, project: Project) {
+        project.register_repo_root();
+        self.mutate(|persisted| persisted.projects.push(project));
+    }
+
+    /// Change a project, if it exists, and schedule a save
+    pub fn update_project<R>(&self, project_id: &str, f: impl FnOnce(&mut Project) -> R) -> Option<R> {
+        self.mutate(|persisted| persisted.projects.iter_mut().find(|p| p.id == project_id).map(f))
+    }
+
+    /// Change a worktree, if it exists, and schedule a save
+    pub fn update_worktree<R>(&self, worktree_id: &str, f: impl FnOnce(&mut Worktree) -> R) -> Option<R> {
+        self.mutate(|persisted| {
+            persisted
+                .projects
+                .iter_mut()
+                .flat_map(|p| p.worktrees.iter_mut())
+                .find(|w| w.id == worktree_id)
+                .map(f)
+        })
+    }
+
+    /// Remove a worktree from its project, returning the project's path and the worktree
+    pub fn remove_worktree(&self, worktree_id: &str) -> Option<(String, Worktree)> {
+        self.mutate(|persisted| {
+            persisted.projects.iter_mut().find_map(|project| {
+                let idx = project.worktrees.iter().position(|w| w.id == worktree_id)?;
+                Some((project.path.clone(), project.worktrees.remove(idx)))
+            })
+        })
+    }
+
+    /// Remove a project's worktrees at any of `paths`, which may be reported
+    /// with symlinks resolved (as git does), returning the removed worktrees
+    pub fn remove_worktrees_at(&self, project_id: &str, paths: &[String]) -> Vec<Worktree> {
+        let wanted: Vec<PathBuf> = paths.iter().map(|path| resolve_missing_path(Path::new(path))).collect();
+        self.mutate(|persisted| {
+            let Some(project) = persisted.projects.iter_mut().find(|p| p.id == project_id) else {
+                return Vec::new();
+            };
+            let (removed, kept): (Vec<Worktree>, Vec<Worktree>) = std::mem::take(&mut project.worktrees)
+                .into_iter()
+                .partition(|w| wanted.contains(&resolve_missing_path(Path::new(&w.path))));
+            project.worktrees = kept;
+            removed
+        })
+    }
+
+    /// Live PTY session of a worktree pane. A recorded session whose process has
+    /// since exited is forgotten, so the pane reads as not spawned.
+    pub fn pane_session(&self, worktree_id: &str, pane: Pane) -> Option<String> {
+        let key = (worktree_id.to_string(), pane);
+        let pty_id = self.pane_sessions.read().get(&key).cloned()?;
+        if self.pty_sessions.read().contains_key(&pty_id) {
+            return Some(pty_id);
+        }
+        self.pane_sessions.write().remove(&key);
+        None
+    }
+
+    /// Record the PTY session spawned for a worktree pane
+    pub fn set_pane_session(&self, worktree_id: &str, pane: Pane, pty_id: &str) {
+        self.pane_sessions
+            .write()
+            .insert((worktree_id.to_string(), pane), pty_id.to_string());
+    }
+
     pub fn load_or_default() -> Self {
         let state = Self::new();
 
-        if let Some(config_dir) = dirs::home_dir() {
-            let state_file = config_dir.join(".shellflow").join("state.json");
-            if state_file.exists() {
-                if let Ok(content) = std::fs::read_to_string(&state_file) {
-                    if let Ok(mut persisted) = serde_json::from_str::<PersistedState>(&content) {
-                        // Clean up stale worktrees whose directories no longer exist
-                        let mut cleaned = false;
-                        for project in &mut persisted.projects {
-                            let before_count = project.worktrees.len();
-                            project.worktrees.retain(|w| {
-                                let exists = std::path::Path::new(&w.path).exists();
-                                if !exists {
-                                    eprintln!(
-                                        "[State] Removing stale worktree '{}' - path no longer exists: {}",
-                                        w.name, w.path
-                                    );
-                                }
-                                exists
-                            });
-                            if project.worktrees.len() != before_count {
-                                cleaned = true;
-                            }
+        if let Some(dir) = state_dir() {
+            if let Some(mut persisted) = read_state_file(&dir) {
+                // Clean up stale worktrees whose directories no longer exist
+                let mut cleaned = false;
+                for project in &mut persisted.projects {
+                    // Projects saved before the repo root was tracked
+                    if project.detect_repo_root() {
+                        cleaned = true;
+                    }
+                    project.register_repo_root();
+                    let before_count = project.worktrees.len();
+                    project.worktrees.retain(|w| {
+                        let exists = std::path::Path::new(&w.path).exists();
+                        if !exists {
+                            eprintln!(
+                                "[State] Removing stale worktree '{}' - path no longer exists: {}",
+                                w.name, w.path
+                            );
                         }
+                        exists
+                    });
+                    if project.worktrees.len() != before_count {
+                        cleaned = true;
+                    }
+                }
 
-                        *state.persisted.write() = persisted;
+                *state.persisted.write() = persisted;
 
-                        // Save cleaned state if any worktrees were removed
-                        if cleaned {
-                            if let Err(e) = state.save() {
-                                eprintln!("[State] Failed to save cleaned state: {}", e);
-                            }
-                        }
+                // Save cleaned state if any worktrees were removed
+                if cleaned {
+                    if let Err(e) = state.save() {
+                        eprintln!("[State] Failed to save cleaned state: {}", e);
                     }
                 }
             }
@@ -145,18 +446,318 @@ impl AppState {
         state
     }
 
+    /// Save immediately, e.g. on shutdown where a pending debounced save could be
+    /// lost. A save already scheduled is covered by this one and skips its write.
     pub fn save(&self) -> Result<(), std::io::Error> {
-        if let Some(home_dir) = dirs::home_dir() {
-            let config_dir = home_dir.join(".shellflow");
-            std::fs::create_dir_all(&config_dir)?;
+        self.saver.flush(&self.persisted)
+    }
+}
+
+/// Directory holding the persisted state (`~/.shellflow`)
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".shellflow"))
+}
+
+/// Write the persisted state to `~/.shellflow/state.json`
+fn write_home_state(persisted: &PersistedState) -> std::io::Result<()> {
+    if let Some(dir) = state_dir() {
+        let content = serde_json::to_string_pretty(persisted)?;
+        write_state_file(&dir, &content)?;
+    }
+    Ok(())
+}
+
+const STATE_FILE: &str = "state.json";
+const STATE_BACKUP_FILE: &str = "state.json.bak";
 
-            let state_file = config_dir.join("state.json");
-            let content = serde_json::to_string_pretty(&*self.persisted.read())?;
-            std::fs::write(state_file, content)?;
+/// Path of the checksum sidecar kept next to a state file (`state.json.sha256`)
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sha256");
+    path.with_file_name(name)
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Read and parse a state file, checking it against its checksum sidecar. A
+/// missing sidecar leaves the file unverified rather than corrupt.
+fn read_verified(path: &Path) -> Result<PersistedState, String> {
+    let content = std::fs::read(path).map_err(|e| e.to_string())?;
+    if let Ok(expected) = std::fs::read_to_string(checksum_path(path)) {
+        if expected.trim() != sha256_hex(&content) {
+            return Err("checksum mismatch".to_string());
         }
-        Ok(())
     }
+    serde_json::from_slice(&content).map_err(|e| e.to_string())
+}
+
+/// Load the persisted state from `dir`, falling back to the backup when the main
+/// file is missing, unparseable or fails its checksum. `None` means start fresh.
+fn read_state_file(dir: &Path) -> Option<PersistedState> {
+    for name in [STATE_FILE, STATE_BACKUP_FILE] {
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        match read_verified(&path) {
+            Ok(persisted) => {
+                eprintln!("[State] Loaded state from {}", path.display());
+                return Some(persisted);
+            }
+            Err(e) => eprintln!("[State] Ignoring {}: {}", path.display(), e),
+        }
+    }
+    None
+}
+
+/// Write the state to `dir` atomically (temp file, then rename) along with its
+/// checksum sidecar. The previous state is kept as the backup if it still verifies.
+fn write_state_file(dir: &Path, content: &str) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(STATE_FILE);
+    let backup = dir.join(STATE_BACKUP_FILE);
+
+    // Never replace a good backup with a corrupt main file
+    if read_verified(&path).is_ok() {
+        write_with_checksum(&backup, &std::fs::read(&path)?)?;
+    }
+
+    write_with_checksum(&path, content.as_bytes())
+}
+
+/// Write a state file, then its sidecar. A crash in between leaves a mismatch,
+/// which sends the next load to the backup.
+fn write_with_checksum(path: &Path, content: &[u8]) -> Result<(), std::io::Error> {
+    write_atomic(path, content)?;
+    write_atomic(&checksum_path(path), sha256_hex(content).as_bytes())
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), std::io::Error> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
 }
 
 unsafe impl Send for AppState {}
 unsafe impl Sync for AppState {}
+
+/// Resolve symlinks in a path whose last component may no longer exist
+fn resolve_missing_path(path: &Path) -> PathBuf {
+    match (path.parent().and_then(|parent| parent.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{git_cmd, init_test_repo_at, worktree};
+
+    #[test]
+    fn loads_worktrees_without_command_override() {
+        let worktree: Worktree = serde_json::from_str(
+            r#"{"id":"1","name":"a","path":"/a","branch":"a","createdAt":"now"}"#,
+        )
+        .unwrap();
+        assert_eq!(worktree.command, None);
+        assert_eq!(worktree.command_line(), None);
+        assert!(!worktree.suspended);
+        assert!(!worktree.inspect);
+    }
+
+    #[test]
+    fn command_line_appends_args() {
+        let worktree: Worktree = serde_json::from_str(
+            r#"{"id":"1","name":"a","path":"/a","branch":"a","createdAt":"now","command":"aider","args":["--model","sonnet"]}"#,
+        )
+        .unwrap();
+        assert_eq!(worktree.command_line().as_deref(), Some("aider --model sonnet"));
+
+        let worktree = Worktree { args: vec!["--message".to_string(), "fix it's bug".to_string()], ..worktree };
+        assert_eq!(worktree.command_line().as_deref(), Some(r"aider --message 'fix it'\''s bug'"));
+    }
+
+    #[test]
+    fn pane_session_forgets_exited_sessions() {
+        let state = AppState::new();
+        assert_eq!(state.pane_session("wt", Pane::Terminal), None);
+
+        state.set_pane_session("wt", Pane::Terminal, "pty-1");
+        state.pty_sessions.write().insert(
+            "pty-1".to_string(),
+            Arc::new(PtySession {
+                worktree_id: "wt".to_string(),
+                child_pid: 1,
+                detached: std::sync::atomic::AtomicBool::new(false),
+            }),
+        );
+        assert_eq!(state.pane_session("wt", Pane::Terminal).as_deref(), Some("pty-1"));
+        assert_eq!(state.pane_session("wt", Pane::Main), None);
+
+        state.pty_sessions.write().remove("pty-1");
+        assert_eq!(state.pane_session("wt", Pane::Terminal), None);
+        assert!(state.pane_sessions.read().is_empty());
+    }
+
+    #[test]
+    fn rapid_mutations_share_one_debounced_save() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let saves = Arc::new(AtomicUsize::new(0));
+        let saved_orders = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let writer: Arc<StateWriter> = {
+            let saves = Arc::clone(&saves);
+            let saved_orders = Arc::clone(&saved_orders);
+            Arc::new(move |persisted: &PersistedState| {
+                saves.fetch_add(1, Ordering::SeqCst);
+                saved_orders.lock().push(persisted.projects[0].worktrees[0].order);
+                Ok(())
+            })
+        };
+        let state = AppState::with_writer(std::time::Duration::from_millis(100), writer);
+        let worktree = worktree("wt", "a", Path::new("/a"));
+        state.add_project(Project {
+            id: "p".to_string(),
+            name: "p".to_string(),
+            path: "/p".to_string(),
+            worktrees: vec![worktree],
+            order: 0,
+            is_active: true,
+            last_accessed_at: None,
+            repo_root: None,
+        });
+        for order in 1..=5 {
+            assert_eq!(state.update_worktree("wt", |w| w.order = order), Some(()));
+        }
+        assert_eq!(state.update_worktree("missing", |w| w.order = 0), None);
+        assert_eq!(saves.load(Ordering::SeqCst), 0);
+
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+        assert_eq!(*saved_orders.lock(), vec![5]);
+
+        // A mutation after the window schedules a new save
+        state.update_worktree("wt", |w| w.order = 9);
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        assert_eq!(saves.load(Ordering::SeqCst), 2);
+        assert_eq!(*saved_orders.lock(), vec![5, 9]);
+
+        // An immediate save covers the save a mutation scheduled
+        state.update_worktree("wt", |w| w.order = 10);
+        state.save().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        assert_eq!(saves.load(Ordering::SeqCst), 3);
+        assert_eq!(*saved_orders.lock(), vec![5, 9, 10]);
+    }
+
+    #[test]
+    fn pruning_a_deleted_worktree_cleans_up_git_and_state() {
+        let dir = std::env::temp_dir().join(format!("shellflow-state-test-{}", uuid::Uuid::new_v4()));
+        let repo = dir.join("repo");
+        init_test_repo_at(&repo);
+        let gone = dir.join("gone");
+        let kept = dir.join("kept");
+        git_cmd(&repo, &["worktree", "add", "-q", "-b", "gone", gone.to_str().unwrap()]);
+        git_cmd(&repo, &["worktree", "add", "-q", "-b", "kept", kept.to_str().unwrap()]);
+
+        let state = AppState::with_writer(std::time::Duration::from_secs(60), Arc::new(|_: &PersistedState| Ok(())));
+        state.add_project(Project {
+            id: "p".to_string(),
+            name: "p".to_string(),
+            path: repo.display().to_string(),
+            worktrees: vec![worktree("gone", "gone", &gone), worktree("kept", "kept", &kept)],
+            order: 0,
+            is_active: true,
+            last_accessed_at: None,
+            repo_root: None,
+        });
+
+        // Deleted by hand, so git still lists it
+        std::fs::remove_dir_all(&gone).unwrap();
+        let summary = crate::git::run_git_maintenance(
+            &repo,
+            &[crate::git::MaintenanceTask::WorktreePrune],
+            &crate::git::GitLimits::default(),
+            &mut |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(summary.tasks[0].error, None);
+        assert_eq!(summary.pruned_worktrees.len(), 1);
+        assert!(!git_cmd(&repo, &["worktree", "list"]).contains("gone"));
+
+        let removed = state.remove_worktrees_at("p", &summary.pruned_worktrees);
+        assert_eq!(removed.iter().map(|w| w.id.as_str()).collect::<Vec<_>>(), vec!["gone"]);
+        let ids: Vec<String> = state.persisted.read().projects[0].worktrees.iter().map(|w| w.id.clone()).collect();
+        assert_eq!(ids, vec!["kept"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn loads_state_without_layout() {
+        let state: PersistedState = serde_json::from_str(r#"{"projects": []}"#).unwrap();
+        assert!(state.layout.is_none());
+    }
+
+    #[test]
+    fn tolerates_partial_layout() {
+        let state: PersistedState =
+            serde_json::from_str(r#"{"projects": [], "layout": {"splits": {"main": [70, 30]}}}"#).unwrap();
+        let layout = state.layout.unwrap();
+        assert_eq!(layout.version, LAYOUT_VERSION);
+        assert!(layout.window.is_none());
+        assert_eq!(layout.splits["main"], vec![70.0, 30.0]);
+    }
+
+    #[test]
+    fn recovers_from_backup_when_checksum_mismatches() {
+        let dir = std::env::temp_dir().join(format!("shellflow-state-test-{}", uuid::Uuid::new_v4()));
+        write_state_file(&dir, r#"{"projects": [], "layout": {"splits": {"main": [70, 30]}}}"#).unwrap();
+        write_state_file(&dir, r#"{"projects": []}"#).unwrap();
+        assert!(read_state_file(&dir).unwrap().layout.is_none());
+
+        // Still valid JSON, but not what was written
+        let tampered = std::fs::read_to_string(dir.join(STATE_FILE))
+            .unwrap()
+            .replace(r#""projects": []"#, r#""projects": [], "garbage": 1"#);
+        std::fs::write(dir.join(STATE_FILE), tampered).unwrap();
+        let recovered = read_state_file(&dir).unwrap();
+        assert_eq!(recovered.layout.unwrap().splits["main"], vec![70.0, 30.0]);
+
+        // A save over a corrupt main file keeps the good backup
+        write_state_file(&dir, r#"{"projects": []}"#).unwrap();
+        std::fs::write(dir.join(STATE_FILE), "{").unwrap();
+        assert!(read_state_file(&dir).unwrap().layout.is_some());
+
+        std::fs::write(dir.join(STATE_BACKUP_FILE), "{").unwrap();
+        assert!(read_state_file(&dir).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn checksum_is_written_to_a_sidecar() {
+        let dir = std::env::temp_dir().join(format!("shellflow-state-test-{}", uuid::Uuid::new_v4()));
+        write_state_file(&dir, r#"{"projects": []}"#).unwrap();
+
+        assert_eq!(std::fs::read(dir.join(STATE_FILE)).unwrap(), br#"{"projects": []}"#);
+        let checksum = std::fs::read_to_string(checksum_path(&dir.join(STATE_FILE))).unwrap();
+        assert_eq!(checksum, sha256_hex(br#"{"projects": []}"#));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn accepts_state_without_checksum() {
+        let dir = std::env::temp_dir().join(format!("shellflow-state-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(STATE_FILE), r#"{"projects": []}"#).unwrap();
+        assert!(read_state_file(&dir).is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
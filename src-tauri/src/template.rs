@@ -41,6 +41,13 @@ impl TemplateContext {
         }
     }
 
+    /// Context whose `repo_directory` is the stored repository root of
+    /// `project_path`, which may be a monorepo subfolder
+    pub fn for_project(project_path: &str) -> Self {
+        let repo_directory = crate::git::repo_root(std::path::Path::new(project_path));
+        Self::new(repo_directory.to_string_lossy())
+    }
+
     pub fn with_branch(mut self, branch: impl Into<String>) -> Self {
         self.branch = Some(branch.into());
         self
@@ -0,0 +1,64 @@
+//! Fixtures shared by the unit tests: throwaway git repositories and state records.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::state::Worktree;
+
+/// Run a git command in `dir` as a fixed test identity, panicking on failure.
+/// Returns its stdout.
+pub fn git_cmd(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Create a throwaway repository on `main` with a single committed file
+pub fn init_test_repo() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shellflow-git-test-{}", uuid::Uuid::new_v4()));
+    init_test_repo_at(&dir);
+    dir
+}
+
+/// Like `init_test_repo`, at `dir` (created if needed)
+pub fn init_test_repo_at(dir: &Path) {
+    std::fs::create_dir_all(dir).unwrap();
+    git_cmd(dir, &["init", "-q", "-b", "main"]);
+    git_cmd(dir, &["config", "user.name", "Test"]);
+    git_cmd(dir, &["config", "user.email", "test@example.com"]);
+    git_cmd(dir, &["config", "commit.gpgsign", "false"]);
+    std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+    git_cmd(dir, &["add", "."]);
+    git_cmd(dir, &["commit", "-q", "-m", "initial"]);
+}
+
+/// A worktree record named after its id, with every optional field unset
+pub fn worktree(id: &str, branch: &str, path: &Path) -> Worktree {
+    Worktree {
+        id: id.to_string(),
+        name: id.to_string(),
+        path: path.display().to_string(),
+        branch: branch.to_string(),
+        base_branch: None,
+        created_at: String::new(),
+        order: 0,
+        command: None,
+        args: Vec::new(),
+        suspended: false,
+        inspect: false,
+        environment: None,
+    }
+}
@@ -1,5 +1,12 @@
+use crate::config::{self, ThemeOverride};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Theme used when a configured theme can't be found
+const FALLBACK_THEME: &str = "Catppuccin Mocha";
 
 /// Information about an available theme
 #[derive(Debug, Clone, Serialize)]
@@ -8,7 +15,7 @@ pub struct ThemeInfo {
     pub name: String,
     /// Full path to the theme file
     pub path: String,
-    /// Source location: "bundled" or "user"
+    /// Source location: "bundled", "user", or the name of a `themes.extraDirectories` entry
     pub source: String,
     /// Theme type if detected from filename or content
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
@@ -23,45 +30,85 @@ struct ThemeMetadata {
     theme_type: Option<String>,
 }
 
-/// Get the path to bundled themes directory
-fn get_bundled_themes_dir() -> Option<PathBuf> {
-    // In development, themes are in the project root
-    // In production, they're bundled as resources
+/// Environment variable that overrides where bundled themes are loaded from
+const THEMES_DIR_ENV: &str = "SHELLFLOW_THEMES_DIR";
+
+lazy_static::lazy_static! {
+    /// Tauri's resource directory, recorded at startup
+    static ref RESOURCE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+    /// Last bundled themes directory that was logged, to log only when it changes
+    static ref LOGGED_THEMES_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Record Tauri's resource directory so bundled themes can be found in
+/// installs where the executable isn't next to its resources (AppImage, symlinks)
+pub fn set_resource_dir(dir: Option<PathBuf>) {
+    *RESOURCE_DIR.lock() = dir;
+}
 
-    // Try resource path first (production)
-    if let Ok(exe_path) = std::env::current_exe() {
+/// Candidate locations for bundled themes, in order of preference
+fn bundled_themes_dir_candidates(
+    env_override: Option<PathBuf>,
+    exe_path: Option<PathBuf>,
+    resource_dir: Option<PathBuf>,
+) -> Vec<(&'static str, PathBuf)> {
+    let mut candidates = Vec::new();
+
+    if let Some(dir) = env_override {
+        candidates.push((THEMES_DIR_ENV, dir));
+    }
+
+    if let Some(exe_path) = exe_path {
         // On macOS, resources are in Contents/Resources
         #[cfg(target_os = "macos")]
-        {
-            if let Some(contents) = exe_path.parent().and_then(|p| p.parent()) {
-                let resources = contents.join("Resources").join("themes");
-                if resources.exists() {
-                    return Some(resources);
-                }
-            }
+        if let Some(contents) = exe_path.parent().and_then(|p| p.parent()) {
+            candidates.push(("executable", contents.join("Resources").join("themes")));
         }
 
         // On Linux/Windows, resources are next to the executable
         #[cfg(not(target_os = "macos"))]
-        {
-            if let Some(exe_dir) = exe_path.parent() {
-                let resources = exe_dir.join("themes");
-                if resources.exists() {
-                    return Some(resources);
-                }
-            }
+        if let Some(exe_dir) = exe_path.parent() {
+            candidates.push(("executable", exe_dir.join("themes")));
         }
     }
 
-    // Fallback to development path (project root)
-    let manifest_dir = env!("CARGO_MANIFEST_DIR");
-    let project_root = Path::new(manifest_dir).parent()?;
-    let themes_dir = project_root.join("themes");
-    if themes_dir.exists() {
-        return Some(themes_dir);
+    if let Some(dir) = resource_dir {
+        candidates.push(("resource dir", dir.join("themes")));
     }
 
-    None
+    // Development path (project root)
+    if let Some(project_root) = Path::new(env!("CARGO_MANIFEST_DIR")).parent() {
+        candidates.push(("development", project_root.join("themes")));
+    }
+
+    candidates
+}
+
+/// Get the path to bundled themes directory
+fn get_bundled_themes_dir() -> Option<PathBuf> {
+    bundled_themes_dir_with(std::env::var_os(THEMES_DIR_ENV).map(PathBuf::from))
+}
+
+fn bundled_themes_dir_with(env_override: Option<PathBuf>) -> Option<PathBuf> {
+    // Resolve symlinks so symlinked installs find resources next to the real binary
+    let exe_path = std::env::current_exe().ok().map(|exe| exe.canonicalize().unwrap_or(exe));
+    let resource_dir = RESOURCE_DIR.lock().clone();
+
+    let Some((source, dir)) = bundled_themes_dir_candidates(env_override, exe_path, resource_dir)
+        .into_iter()
+        .find(|(_, dir)| dir.is_dir())
+    else {
+        log::warn!("[theme] No bundled themes directory found");
+        return None;
+    };
+
+    let mut logged = LOGGED_THEMES_DIR.lock();
+    if logged.as_ref() != Some(&dir) {
+        log::info!("[theme] Using bundled themes from {:?} ({})", dir, source);
+        *logged = Some(dir.clone());
+    }
+
+    Some(dir)
 }
 
 /// Get the path to user themes directory
@@ -70,15 +117,44 @@ fn get_user_themes_dir() -> Option<PathBuf> {
     Some(config_dir)
 }
 
+/// Existing `themes.extraDirectories` entries with their source label (the
+/// directory's name). Missing entries are skipped with a warning.
+fn extra_themes_dirs(directories: &[String]) -> Vec<(String, PathBuf)> {
+    directories
+        .iter()
+        .filter_map(|entry| {
+            let dir = match entry.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir()?.join(rest),
+                None => PathBuf::from(entry),
+            };
+            if !dir.is_dir() {
+                log::warn!("[theme] themes.extraDirectories entry {:?} is not a directory, skipping", entry);
+                return None;
+            }
+            let source = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| entry.clone());
+            Some((source, dir))
+        })
+        .collect()
+}
+
+/// Parse the content of a theme file. `.json5` files go through the JSON5 parser
+/// (unquoted keys, single quotes, trailing commas); anything else is JSON with comments.
+fn parse_theme_content<T: serde::de::DeserializeOwned>(path: &Path, content: &str) -> Result<T, String> {
+    if path.extension().is_some_and(|ext| ext == "json5") {
+        return json5::from_str(content).map_err(|e| e.to_string());
+    }
+    let mut json = content.to_string();
+    json_strip_comments::strip(&mut json).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
 /// Extract theme name from a theme file
 fn extract_theme_name(path: &Path) -> Option<String> {
     let content = std::fs::read_to_string(path).ok()?;
-
-    // Strip JSON comments before parsing
-    let mut json = content;
-    json_strip_comments::strip(&mut json).ok()?;
-
-    let metadata: ThemeMetadata = serde_json::from_str(&json).ok()?;
+    let metadata: ThemeMetadata = parse_theme_content(path, &content).ok()?;
     metadata.name
 }
 
@@ -95,18 +171,13 @@ fn extract_theme_type(path: &Path, content: Option<&str>) -> Option<String> {
 
     // Try to parse from content
     if let Some(content) = content {
-        let mut json = content.to_string();
-        if json_strip_comments::strip(&mut json).is_ok() {
-            if let Ok(metadata) = serde_json::from_str::<ThemeMetadata>(&json) {
-                return metadata.theme_type;
-            }
+        if let Ok(metadata) = parse_theme_content::<ThemeMetadata>(path, content) {
+            return metadata.theme_type;
         }
-    } else if let Ok(content) = std::fs::read_to_string(path) {
-        let mut json = content;
-        if json_strip_comments::strip(&mut json).is_ok() {
-            if let Ok(metadata) = serde_json::from_str::<ThemeMetadata>(&json) {
-                return metadata.theme_type;
-            }
+    } else if let Ok(theme) = load_theme_with_includes(path, &mut Vec::new()) {
+        // Derived themes often inherit their type from an included base
+        if let Ok(metadata) = serde_json::from_value::<ThemeMetadata>(theme) {
+            return metadata.theme_type;
         }
     }
 
@@ -183,21 +254,40 @@ fn scan_themes_dir(dir: &Path, source: &str) -> Vec<ThemeInfo> {
     themes
 }
 
-/// Check if a path is a theme file
+/// JSON files that commonly sit next to themes but are never themes themselves
+const NON_THEME_FILES: &[&str] = &["package.json", "package-lock.json", "tsconfig.json", "jsconfig.json"];
+
+/// Top-level keys of a theme file; `include` covers themes that only derive from a base
+#[derive(Deserialize)]
+struct ThemeKeys {
+    colors: Option<serde::de::IgnoredAny>,
+    #[serde(rename = "tokenColors")]
+    token_colors: Option<serde::de::IgnoredAny>,
+    include: Option<serde::de::IgnoredAny>,
+}
+
+/// Check if a path is a theme file: a `.json`/`.jsonc`/`.json5` file that isn't a known
+/// non-theme and defines `colors`, `tokenColors` or an `include`
 fn is_theme_file(path: &Path) -> bool {
-    if let Some(ext) = path.extension() {
-        let ext = ext.to_str().unwrap_or("");
-        if ext == "json" || ext == "jsonc" {
-            // Exclude non-theme JSON files
-            let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            return !filename.starts_with("package")
-                && !filename.starts_with("tsconfig")
-                && !filename.starts_with(".")
-                && !filename.contains("settings")
-                && !filename.contains("config");
-        }
+    is_theme_filename(path) && has_theme_keys(path)
+}
+
+fn is_theme_filename(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if !matches!(ext, "json" | "jsonc" | "json5") {
+        return false;
     }
-    false
+    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    !filename.starts_with('.') && !NON_THEME_FILES.contains(&filename)
+}
+
+fn has_theme_keys(path: &Path) -> bool {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    parse_theme_content::<ThemeKeys>(path, &content)
+        .map(|keys| keys.colors.is_some() || keys.token_colors.is_some() || keys.include.is_some())
+        .unwrap_or(false)
 }
 
 /// Create ThemeInfo from a theme file path
@@ -280,9 +370,11 @@ fn scan_vscode_extension(dir: &Path, source: &str) -> Option<Vec<ThemeInfo>> {
     }
 }
 
-/// List all available themes from bundled and user directories
+/// List all available themes from bundled, user and extra directories. With a
+/// `project_path`, the project's own `themes.extraDirectories` are scanned too.
 #[tauri::command]
-pub fn list_themes() -> Vec<ThemeInfo> {
+pub fn list_themes(project_path: Option<String>) -> Vec<ThemeInfo> {
+    let cfg = config::load_config_for_project(project_path.as_deref());
     let mut themes = Vec::new();
 
     // Scan bundled themes
@@ -295,30 +387,565 @@ pub fn list_themes() -> Vec<ThemeInfo> {
         themes.extend(scan_themes_dir(&user_dir, "user"));
     }
 
+    // Scan configured extra directories
+    for (source, dir) in extra_themes_dirs(&cfg.themes.extra_directories) {
+        themes.extend(scan_themes_dir(&dir, &source));
+    }
+
     // Sort by name
     themes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
 
+    apply_type_overrides(&mut themes, &cfg.theme_overrides);
+
+    themes
+}
+
+/// Find the override for a theme (case-insensitive, like theme lookup)
+fn find_override<'a>(overrides: &'a HashMap<String, ThemeOverride>, name: &str) -> Option<&'a ThemeOverride> {
+    overrides
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, theme_override)| theme_override)
+}
+
+/// Force the configured type onto listed themes. Overrides naming a theme that
+/// doesn't exist are ignored with a warning.
+fn apply_type_overrides(themes: &mut [ThemeInfo], overrides: &HashMap<String, ThemeOverride>) {
+    for (name, theme_override) in overrides {
+        let Some(theme) = themes.iter_mut().find(|t| t.name.eq_ignore_ascii_case(name)) else {
+            log::warn!("[theme] themeOverrides entry {:?} doesn't match any theme, ignoring", name);
+            continue;
+        };
+        if let Some(theme_type) = theme_override.theme_type {
+            theme.theme_type = Some(theme_type.as_str().to_string());
+        }
+    }
+}
+
+/// Find a theme by display name (case-insensitive). When several files share the
+/// name, a user theme wins over one from an extra directory, which wins over a bundled one.
+fn find_theme(themes: &[ThemeInfo], name: &str) -> Option<ThemeInfo> {
+    let precedence = |theme: &ThemeInfo| match theme.source.as_str() {
+        "user" => 0,
+        "bundled" => 2,
+        _ => 1,
+    };
     themes
+        .iter()
+        .filter(|t| t.name.eq_ignore_ascii_case(name))
+        .min_by_key(|t| precedence(t))
+        .cloned()
+}
+
+/// Names of themes resembling `name`, closest first, for "not found" errors
+fn close_matches(themes: &[ThemeInfo], name: &str) -> Vec<String> {
+    let wanted = name.to_lowercase();
+    let mut scored: Vec<(usize, &str)> = themes
+        .iter()
+        .filter_map(|t| {
+            let candidate = t.name.to_lowercase();
+            let distance = edit_distance(&wanted, &candidate);
+            let related = candidate.contains(&wanted) || wanted.contains(&candidate);
+            (related || distance <= 3).then_some((if related { 0 } else { distance }, t.name.as_str()))
+        })
+        .collect();
+    scored.sort();
+    let mut names: Vec<String> = scored.into_iter().map(|(_, name)| name.to_string()).collect();
+    names.dedup();
+    names.truncate(5);
+    names
+}
+
+/// Levenshtein distance between two strings, by character
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Payload for the theme-fallback warning event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThemeFallback {
+    requested: String,
+    resolved: String,
+}
+
+/// Resolve a configured theme name to its ThemeInfo so the frontend can load the right
+/// colors for each pane. If the theme no longer exists, falls back to `fallback` (or the
+/// default theme) and emits a `theme-fallback` warning event.
+#[tauri::command]
+pub fn resolve_theme(
+    app: AppHandle,
+    name: &str,
+    fallback: Option<String>,
+    project_path: Option<String>,
+) -> Result<ThemeInfo, String> {
+    let themes = list_themes(project_path);
+    if let Some(theme) = find_theme(&themes, name) {
+        return Ok(theme);
+    }
+
+    let theme = fallback
+        .as_deref()
+        .and_then(|fallback| find_theme(&themes, fallback))
+        .or_else(|| find_theme(&themes, FALLBACK_THEME))
+        .ok_or_else(|| format!("Theme not found: {}", name))?;
+
+    log::warn!("[theme] Theme {:?} not found, falling back to {:?}", name, theme.name);
+    let _ = app.emit(
+        "theme-fallback",
+        ThemeFallback {
+            requested: name.to_string(),
+            resolved: theme.name.clone(),
+        },
+    );
+
+    Ok(theme)
+}
+
+/// Parse a theme file (JSON with comments, or JSON5) into a JSON value
+fn parse_theme_file(path: &Path) -> Result<serde_json::Value, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read theme file: {}", e))?;
+    parse_theme_content(path, &content).map_err(|e| format!("Failed to parse theme file: {}", e))
 }
 
-/// Read a theme file and return its contents
+/// Merge a derived theme over its base. Objects (like `colors`) merge key by key,
+/// `tokenColors` rules accumulate with the child's last so they take precedence,
+/// and any other value from the child replaces the base's.
+fn merge_theme(base: &mut serde_json::Value, child: serde_json::Value) {
+    use serde_json::Value;
+    let (Value::Object(base_obj), Value::Object(child_obj)) = (base, child) else {
+        return;
+    };
+    for (key, child_val) in child_obj {
+        match (base_obj.get_mut(&key), child_val) {
+            (Some(base_val @ Value::Object(_)), child_val @ Value::Object(_)) => merge_theme(base_val, child_val),
+            (Some(Value::Array(base_arr)), Value::Array(child_arr)) if key == "tokenColors" => base_arr.extend(child_arr),
+            (_, child_val) => {
+                base_obj.insert(key, child_val);
+            }
+        }
+    }
+}
+
+/// Load a theme, resolving `include` chains (paths relative to the including file).
+/// A missing base or an include cycle is logged and the chain is cut there,
+/// so the theme still loads with whatever colors it defines itself.
+fn load_theme_with_includes(path: &Path, visited: &mut Vec<PathBuf>) -> Result<serde_json::Value, String> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    visited.push(canonical);
+
+    let mut theme = parse_theme_file(path)?;
+    let Some(include) = theme
+        .as_object_mut()
+        .and_then(|obj| obj.remove("include"))
+        .and_then(|include| include.as_str().map(String::from))
+    else {
+        return Ok(theme);
+    };
+
+    let base_path = path.parent().unwrap_or(Path::new(".")).join(&include);
+    let base_canonical = base_path.canonicalize().unwrap_or_else(|_| base_path.clone());
+    if visited.contains(&base_canonical) {
+        log::warn!("[theme] Include cycle in {:?} via {:?}, ignoring include", path, include);
+        return Ok(theme);
+    }
+    if !base_path.exists() {
+        log::warn!("[theme] Base theme {:?} included by {:?} not found", base_path, path);
+        return Ok(theme);
+    }
+
+    let mut merged = load_theme_with_includes(&base_path, visited)?;
+    merge_theme(&mut merged, theme);
+    Ok(merged)
+}
+
+/// Read a theme file and return its contents.
+/// Themes that `include` a base theme are returned merged over their base, and
+/// colors from the theme's `themeOverrides` entry replace the theme's own.
 #[tauri::command]
 pub fn read_theme(path: &str) -> Result<String, String> {
-    std::fs::read_to_string(path).map_err(|e| format!("Failed to read theme file: {}", e))
+    read_theme_with_overrides(path, &config::load_config().theme_overrides)
+}
+
+/// Read a theme by display name rather than path. The name is resolved through
+/// `list_themes`, so a user theme shadows a bundled one of the same name.
+#[tauri::command]
+pub fn read_theme_by_name(name: &str, project_path: Option<String>) -> Result<String, String> {
+    let themes = list_themes(project_path);
+    let theme = find_theme(&themes, name).ok_or_else(|| not_found_message(&themes, name))?;
+    read_theme(&theme.path)
+}
+
+fn not_found_message(themes: &[ThemeInfo], name: &str) -> String {
+    let matches = close_matches(themes, name);
+    if matches.is_empty() {
+        format!("Theme not found: {}", name)
+    } else {
+        format!("Theme not found: {}. Did you mean: {}?", name, matches.join(", "))
+    }
+}
+
+fn read_theme_with_overrides(path: &str, overrides: &HashMap<String, ThemeOverride>) -> Result<String, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read theme file: {}", e))?;
+    let color_overrides = create_theme_info(Path::new(path), "")
+        .and_then(|info| find_override(overrides, &info.name))
+        .map(|theme_override| &theme_override.colors)
+        .filter(|colors| !colors.is_empty());
+    // JSON5 is always re-serialized, since the frontend only reads plain JSON
+    let is_json5 = Path::new(path).extension().is_some_and(|ext| ext == "json5");
+    if !is_json5 && !content.contains("\"include\"") && color_overrides.is_none() {
+        return Ok(content);
+    }
+
+    let mut theme = load_theme_with_includes(Path::new(path), &mut Vec::new())?;
+    if let Some(colors) = color_overrides {
+        merge_theme(&mut theme, serde_json::json!({ "colors": colors }));
+    }
+    serde_json::to_string_pretty(&theme).map_err(|e| e.to_string())
+}
+
+/// A theme's `colors` as a flat name -> hex map, for previews.
+/// Includes and `themeOverrides` are applied as in `read_theme`, and each
+/// `tokenColors` foreground is added under a synthetic `tokenColors.<scope>` key
+/// (later rules win). Values that aren't hex colors are left out; the rest are
+/// normalized to lowercase `#rrggbb`, or `#rrggbbaa` when translucent.
+#[tauri::command]
+pub fn theme_colors(path: &str) -> Result<HashMap<String, String>, String> {
+    theme_colors_with_overrides(path, &config::load_config().theme_overrides)
+}
+
+fn theme_colors_with_overrides(
+    path: &str,
+    overrides: &HashMap<String, ThemeOverride>,
+) -> Result<HashMap<String, String>, String> {
+    let mut theme = load_theme_with_includes(Path::new(path), &mut Vec::new())?;
+    let color_overrides = create_theme_info(Path::new(path), "")
+        .and_then(|info| find_override(overrides, &info.name))
+        .map(|theme_override| &theme_override.colors);
+    if let Some(colors) = color_overrides {
+        merge_theme(&mut theme, serde_json::json!({ "colors": colors }));
+    }
+
+    let mut colors = HashMap::new();
+    if let Some(theme_colors) = theme.get("colors") {
+        flatten_colors("", theme_colors, &mut colors);
+    }
+    for rule in theme.get("tokenColors").and_then(|rules| rules.as_array()).into_iter().flatten() {
+        let Some(foreground) = rule
+            .pointer("/settings/foreground")
+            .and_then(|foreground| foreground.as_str())
+            .and_then(normalize_hex_color)
+        else {
+            continue;
+        };
+        let scopes: Vec<&str> = match rule.get("scope") {
+            Some(serde_json::Value::String(scope)) => scope.split(',').collect(),
+            Some(serde_json::Value::Array(scopes)) => scopes.iter().filter_map(|scope| scope.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        for scope in scopes.iter().map(|scope| scope.trim()).filter(|scope| !scope.is_empty()) {
+            colors.insert(format!("tokenColors.{}", scope), foreground.clone());
+        }
+    }
+    Ok(colors)
+}
+
+/// Collect hex colors from a (possibly nested) `colors` object under dotted keys
+fn flatten_colors(prefix: &str, value: &serde_json::Value, colors: &mut HashMap<String, String>) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            for (key, value) in obj {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_colors(&key, value, colors);
+            }
+        }
+        serde_json::Value::String(color) => {
+            if let Some(color) = normalize_hex_color(color) {
+                colors.insert(prefix.to_string(), color);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Normalize `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa` to lowercase `#rrggbb`,
+/// keeping the alpha byte only when it isn't fully opaque
+fn normalize_hex_color(value: &str) -> Option<String> {
+    let hex = value.trim().strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut expanded: String = match hex.len() {
+        3 | 4 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 | 8 => hex.to_string(),
+        _ => return None,
+    };
+    expanded.make_ascii_lowercase();
+    if expanded.len() == 8 && expanded.ends_with("ff") {
+        expanded.truncate(6);
+    }
+    Some(format!("#{}", expanded))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn env_override_takes_precedence_over_other_theme_dirs() {
+        let candidates = bundled_themes_dir_candidates(
+            Some(PathBuf::from("/custom/themes")),
+            Some(PathBuf::from("/opt/shellflow/bin/shellflow")),
+            Some(PathBuf::from("/opt/shellflow/resources")),
+        );
+        assert_eq!(candidates[0], (THEMES_DIR_ENV, PathBuf::from("/custom/themes")));
+        assert!(candidates
+            .iter()
+            .any(|(source, dir)| *source == "resource dir" && dir == Path::new("/opt/shellflow/resources/themes")));
+
+        let candidates = bundled_themes_dir_candidates(None, None, Some(PathBuf::from("/opt/shellflow/resources")));
+        assert_eq!(candidates[0].0, "resource dir");
+    }
+
+    #[test]
+    fn env_override_is_used_when_it_exists() {
+        let dir = std::env::temp_dir().join(format!("shellflow-themes-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(bundled_themes_dir_with(Some(dir.clone())), Some(dir.clone()));
+        // A missing override falls through to the other candidates
+        assert_ne!(bundled_themes_dir_with(Some(dir.join("missing"))), Some(dir.join("missing")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_theme_merges_included_base() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("shared")).unwrap();
+        std::fs::write(
+            dir.join("shared/base.json"),
+            r##"{ "name": "Base", "type": "dark", "colors": { "editor.background": "#000000", "editor.foreground": "#ffffff" }, "tokenColors": [{ "scope": "comment" }] }"##,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("child.json"),
+            r##"{
+                // Derived theme
+                "name": "Child",
+                "include": "./shared/base.json",
+                "colors": { "editor.background": "#111111" },
+                "tokenColors": [{ "scope": "string" }]
+            }"##,
+        )
+        .unwrap();
+
+        let merged: serde_json::Value =
+            serde_json::from_str(&read_theme(&dir.join("child.json").to_string_lossy()).unwrap()).unwrap();
+        assert_eq!(merged["name"], "Child");
+        assert_eq!(merged["type"], "dark");
+        assert_eq!(merged["colors"]["editor.background"], "#111111");
+        assert_eq!(merged["colors"]["editor.foreground"], "#ffffff");
+        assert_eq!(merged["tokenColors"].as_array().unwrap().len(), 2);
+        assert!(merged.get("include").is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn normalizes_hex_colors() {
+        assert_eq!(normalize_hex_color("#ABC").as_deref(), Some("#aabbcc"));
+        assert_eq!(normalize_hex_color("#abc8").as_deref(), Some("#aabbcc88"));
+        assert_eq!(normalize_hex_color("#1E1E2E").as_deref(), Some("#1e1e2e"));
+        assert_eq!(normalize_hex_color("#1e1e2e80").as_deref(), Some("#1e1e2e80"));
+        assert_eq!(normalize_hex_color("#1e1e2eFF").as_deref(), Some("#1e1e2e"));
+        assert_eq!(normalize_hex_color("#abcf").as_deref(), Some("#aabbcc"));
+        assert_eq!(normalize_hex_color(" #fff ").as_deref(), Some("#ffffff"));
+        assert_eq!(normalize_hex_color("fff"), None);
+        assert_eq!(normalize_hex_color("#ggg"), None);
+        assert_eq!(normalize_hex_color("#12345"), None);
+        assert_eq!(normalize_hex_color("red"), None);
+    }
+
+    #[test]
+    fn theme_colors_flattens_colors_and_token_foregrounds() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("base.json"),
+            r##"{ "name": "Base", "colors": { "editor.foreground": "#FFF" }, "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#888" } }] }"##,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("child.json"),
+            r##"{
+                // Derived theme
+                "name": "Child",
+                "include": "base.json",
+                "colors": { "editor.background": "#1E1E2E", "terminal.ansiRed": "#f00a", "editor.border": null, "bogus": "red" },
+                "tokenColors": [
+                    { "scope": "string, constant", "settings": { "foreground": "#a6e3a1" } },
+                    { "scope": ["comment"], "settings": { "foreground": "#6c7086", "fontStyle": "italic" } },
+                    { "scope": "keyword", "settings": { "fontStyle": "bold" } }
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        let colors = theme_colors_with_overrides(&dir.join("child.json").to_string_lossy(), &HashMap::new()).unwrap();
+        assert_eq!(colors["editor.background"], "#1e1e2e");
+        assert_eq!(colors["editor.foreground"], "#ffffff");
+        assert_eq!(colors["terminal.ansiRed"], "#ff0000aa");
+        assert_eq!(colors["tokenColors.string"], "#a6e3a1");
+        assert_eq!(colors["tokenColors.constant"], "#a6e3a1");
+        assert_eq!(colors["tokenColors.comment"], "#6c7086");
+        assert!(!colors.contains_key("editor.border"));
+        assert!(!colors.contains_key("bogus"));
+        assert!(!colors.contains_key("tokenColors.keyword"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_theme_survives_include_cycles_and_missing_bases() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.json"), r##"{ "name": "A", "include": "b.json", "colors": { "a": "#aaaaaa" } }"##).unwrap();
+        std::fs::write(dir.join("b.json"), r##"{ "name": "B", "include": "a.json", "colors": { "b": "#bbbbbb" } }"##).unwrap();
+        std::fs::write(dir.join("orphan.json"), r##"{ "name": "Orphan", "include": "missing.json", "colors": { "c": "#cccccc" } }"##).unwrap();
+
+        let merged: serde_json::Value =
+            serde_json::from_str(&read_theme(&dir.join("a.json").to_string_lossy()).unwrap()).unwrap();
+        assert_eq!(merged["name"], "A");
+        assert_eq!(merged["colors"]["a"], "#aaaaaa");
+        assert_eq!(merged["colors"]["b"], "#bbbbbb");
+
+        let orphan: serde_json::Value =
+            serde_json::from_str(&read_theme(&dir.join("orphan.json").to_string_lossy()).unwrap()).unwrap();
+        assert_eq!(orphan["colors"]["c"], "#cccccc");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn theme_overrides_force_type_and_replace_colors() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("night-light.json");
+        std::fs::write(
+            &path,
+            r##"{ "name": "Night Light", "colors": { "terminal.ansiRed": "#aa0000", "terminal.background": "#000000" } }"##,
+        )
+        .unwrap();
+
+        let overrides: HashMap<String, ThemeOverride> = serde_json::from_str(
+            r##"{
+                "night light": { "type": "dark", "colors": { "terminal.ansiRed": "#ff5555" } },
+                "Missing Theme": { "type": "light" }
+            }"##,
+        )
+        .unwrap();
+
+        let mut themes = vec![create_theme_info(&path, "user").unwrap()];
+        assert_eq!(themes[0].theme_type.as_deref(), Some("light"));
+        apply_type_overrides(&mut themes, &overrides);
+        assert_eq!(themes[0].theme_type.as_deref(), Some("dark"));
+
+        let theme: serde_json::Value =
+            serde_json::from_str(&read_theme_with_overrides(&path.to_string_lossy(), &overrides).unwrap()).unwrap();
+        assert_eq!(theme["colors"]["terminal.ansiRed"], "#ff5555");
+        assert_eq!(theme["colors"]["terminal.background"], "#000000");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extra_themes_dirs_skip_missing_and_use_dir_name() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        let extensions = dir.join("extensions");
+        std::fs::create_dir_all(&extensions).unwrap();
+
+        let dirs = extra_themes_dirs(&[
+            extensions.to_string_lossy().to_string(),
+            dir.join("missing").to_string_lossy().to_string(),
+        ]);
+        assert_eq!(dirs, vec![("extensions".to_string(), extensions.clone())]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_is_theme_file() {
-        assert!(is_theme_file(Path::new("mocha.json")));
-        assert!(is_theme_file(Path::new("theme.jsonc")));
-        assert!(!is_theme_file(Path::new("package.json")));
-        assert!(!is_theme_file(Path::new("tsconfig.json")));
-        assert!(!is_theme_file(Path::new(".hidden.json")));
-        assert!(!is_theme_file(Path::new("settings.json")));
+        assert!(is_theme_filename(Path::new("mocha.json")));
+        assert!(is_theme_filename(Path::new("theme.jsonc")));
+        assert!(is_theme_filename(Path::new("theme.json5")));
+        assert!(!is_theme_filename(Path::new("package.json")));
+        assert!(!is_theme_filename(Path::new("tsconfig.json")));
+        assert!(!is_theme_filename(Path::new(".hidden.json")));
+        assert!(!is_theme_filename(Path::new("theme.toml")));
+
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+
+        assert!(is_theme_file(&write("my-config-dark.json", r##"{ "colors": { "terminal.background": "#000000" } }"##)));
+        assert!(is_theme_file(&write("tokens.jsonc", "// tokens only\n{ \"tokenColors\": [] }")));
+        assert!(is_theme_file(&write("derived.json", r#"{ "include": "./mocha.json" }"#)));
+        assert!(!is_theme_file(&write("settings.json", r#"{ "editor.fontSize": 14 }"#)));
+        assert!(!is_theme_file(&write("broken.json", "{ not json")));
+        assert!(!is_theme_file(&dir.join("missing.json")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_json5_themes_with_unquoted_keys() {
+        let dir = std::env::temp_dir().join(format!("shellflow-theme-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("harbor.json5");
+        std::fs::write(
+            &path,
+            r##"// JSON5 theme
+{
+  name: 'Harbor',
+  type: 'light',
+  colors: {
+    'terminal.background': '#fafafa',
+    'terminal.foreground': "#202020",
+  },
+  tokenColors: [],
+}
+"##,
+        )
+        .unwrap();
+
+        assert!(is_theme_file(&path));
+        let info = create_theme_info(&path, "user").unwrap();
+        assert_eq!(info.name, "Harbor");
+        assert_eq!(info.theme_type.as_deref(), Some("light"));
+
+        let theme: serde_json::Value = serde_json::from_str(&read_theme(&path.to_string_lossy()).unwrap()).unwrap();
+        assert_eq!(theme["colors"]["terminal.background"], "#fafafa");
+
+        // The comment-stripping path for .json stays strict about JSON5 syntax
+        let json = dir.join("harbor.json");
+        std::fs::copy(&path, &json).unwrap();
+        assert!(!is_theme_file(&json));
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -340,4 +967,59 @@ mod tests {
             Some("light".to_string())
         );
     }
+
+    #[test]
+    fn test_find_theme_is_case_insensitive() {
+        let themes = vec![ThemeInfo {
+            name: "Catppuccin Mocha".to_string(),
+            path: "/themes/mocha.json".to_string(),
+            source: "bundled".to_string(),
+            theme_type: Some("dark".to_string()),
+        }];
+        assert_eq!(
+            find_theme(&themes, "catppuccin mocha").map(|t| t.path),
+            Some("/themes/mocha.json".to_string())
+        );
+        assert!(find_theme(&themes, "Dracula").is_none());
+    }
+
+    #[test]
+    fn find_theme_prefers_user_over_extra_over_bundled() {
+        let theme = |path: &str, source: &str| ThemeInfo {
+            name: "Dracula".to_string(),
+            path: path.to_string(),
+            source: source.to_string(),
+            theme_type: None,
+        };
+        let themes = vec![theme("/bundled.json", "bundled"), theme("/extra.json", "vscode")];
+        assert_eq!(find_theme(&themes, "dracula").unwrap().path, "/extra.json");
+
+        let mut themes = themes;
+        themes.push(theme("/user.json", "user"));
+        assert_eq!(find_theme(&themes, "dracula").unwrap().path, "/user.json");
+    }
+
+    #[test]
+    fn not_found_message_lists_close_matches() {
+        let themes: Vec<ThemeInfo> = ["Catppuccin Mocha", "Catppuccin Latte", "Dracula", "Nord"]
+            .iter()
+            .map(|name| ThemeInfo {
+                name: name.to_string(),
+                path: String::new(),
+                source: "bundled".to_string(),
+                theme_type: None,
+            })
+            .collect();
+
+        assert_eq!(close_matches(&themes, "catppuccin"), vec!["Catppuccin Latte", "Catppuccin Mocha"]);
+        assert_eq!(close_matches(&themes, "Dracla"), vec!["Dracula"]);
+        assert_eq!(
+            not_found_message(&themes, "Solarized"),
+            "Theme not found: Solarized"
+        );
+        assert_eq!(
+            not_found_message(&themes, "nordd"),
+            "Theme not found: nordd. Did you mean: Nord?"
+        );
+    }
 }
@@ -1,38 +1,16 @@
 use crate::config;
 use crate::git;
-use crate::state::FileChange;
+use crate::state::{AppState, FileChange, Project};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Sender};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
-
-/// Resolve the actual git directory for a given repo/worktree path.
-/// For regular repos, this is <path>/.git
-/// For worktrees, .git is a file containing "gitdir: <actual-path>"
-fn resolve_git_dir(repo_path: &Path) -> Option<PathBuf> {
-    let git_path = repo_path.join(".git");
-
-    if git_path.is_dir() {
-        // Regular repo - .git is a directory
-        Some(git_path)
-    } else if git_path.is_file() {
-        // Worktree - .git is a file pointing to the actual git dir
-        if let Ok(content) = std::fs::read_to_string(&git_path) {
-            // Format: "gitdir: /path/to/actual/.git/worktrees/<name>"
-            if let Some(path) = content.strip_prefix("gitdir: ") {
-                let path = path.trim();
-                return Some(PathBuf::from(path));
-            }
-        }
-        None
-    } else {
-        None
-    }
-}
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Clone, serde::Serialize)]
 pub struct FilesChanged {
@@ -45,139 +23,910 @@ pub struct WorktreeRemoved {
     pub worktree_path: String,
 }
 
-// Track active watchers so we can stop them
-lazy_static::lazy_static! {
-    static ref WATCHERS: Mutex<HashMap<String, Sender<()>>> = Mutex::new(HashMap::new());
+/// A watched worktree's folder disappeared but may come back (emitted as
+/// `worktree-unavailable`)
+#[derive(Clone, serde::Serialize)]
+pub struct WorktreeUnavailable {
+    pub worktree_path: String,
+    /// The parent folder is gone too, as when a network mount drops, so removal
+    /// is never assumed; otherwise the worktree looks deleted and is reported
+    /// removed unless it's back within `watcher.removalGraceSecs`
+    pub mount_unavailable: bool,
 }
 
-pub fn watch_worktree(app: AppHandle, worktree_id: String, worktree_path: String) {
-    // Check if already watching this worktree
-    if WATCHERS.lock().contains_key(&worktree_id) {
-        return;
+/// A worktree reported unavailable is back (emitted as `worktree-available`)
+#[derive(Clone, serde::Serialize)]
+pub struct WorktreeAvailable {
+    pub worktree_path: String,
+}
+
+/// A raw filesystem event as the watcher received it (emitted as
+/// `watcher-event` with `watcher.debug`)
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherEvent {
+    pub worktree_path: String,
+    pub kind: String,
+    pub paths: Vec<String>,
+}
+
+/// What changed in a worktree's changed-files list since the last update
+/// (emitted as `files-changed-delta` with `watcher.incremental`)
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesChangedDelta {
+    pub worktree_path: String,
+    /// Files that are new to the list or whose status or stats changed
+    pub changed: Vec<FileChange>,
+    /// Paths that dropped off the list
+    pub removed: Vec<String>,
+}
+
+/// Most distinct paths an incremental update asks git about before falling
+/// back to a full scan
+const MAX_INCREMENTAL_PATHS: usize = 256;
+
+/// Longest an incremental watcher goes without a full scan
+const FULL_SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Last known changed files of a worktree, keyed by path
+#[derive(Default)]
+struct StatusCache {
+    files: HashMap<String, FileChange>,
+}
+
+impl StatusCache {
+    /// Replace the whole list with a full scan's result
+    fn replace_all(&mut self, files: Vec<FileChange>) -> (Vec<FileChange>, Vec<String>) {
+        let fresh: HashMap<String, FileChange> = files.into_iter().map(|f| (f.path.clone(), f)).collect();
+        let mut removed: Vec<String> = self.files.keys().filter(|p| !fresh.contains_key(*p)).cloned().collect();
+        let mut changed: Vec<FileChange> =
+            fresh.values().filter(|f| self.files.get(&f.path) != Some(*f)).cloned().collect();
+        self.files = fresh;
+        changed.sort_by(|a, b| a.path.cmp(&b.path));
+        removed.sort();
+        (changed, removed)
     }
 
-    // Create stop channel
-    let (stop_tx, stop_rx) = channel::<()>();
-    WATCHERS.lock().insert(worktree_id.clone(), stop_tx);
+    /// Replace the entries at or under `scopes` with a scan limited to them
+    fn update_paths(&mut self, scopes: &[String], files: Vec<FileChange>) -> (Vec<FileChange>, Vec<String>) {
+        let covered = |path: &str| {
+            scopes.iter().any(|scope| {
+                path == scope || path.strip_prefix(scope.as_str()).is_some_and(|rest| rest.starts_with('/'))
+            })
+        };
+        let fresh: HashMap<String, FileChange> = files.into_iter().map(|f| (f.path.clone(), f)).collect();
+
+        let mut removed: Vec<String> = self
+            .files
+            .keys()
+            .filter(|p| covered(p) && !fresh.contains_key(*p))
+            .cloned()
+            .collect();
+        for path in &removed {
+            self.files.remove(path);
+        }
 
-    let worktree_id_clone = worktree_id.clone();
+        let mut changed = Vec::new();
+        for (path, file) in fresh {
+            if self.files.get(&path) != Some(&file) {
+                changed.push(file.clone());
+                self.files.insert(path, file);
+            }
+        }
+        changed.sort_by(|a, b| a.path.cmp(&b.path));
+        removed.sort();
+        (changed, removed)
+    }
 
-    thread::spawn(move || {
-        let (tx, rx) = channel::<notify::Result<Event>>();
+    fn snapshot(&self) -> Vec<FileChange> {
+        let mut files: Vec<FileChange> = self.files.values().cloned().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+}
+
+/// A changed path relative to the worktree, in git's `/`-separated form.
+/// None for the root itself, anything outside it and anything under `.git`,
+/// since those need a full scan.
+fn worktree_relative(roots: &[&Path], changed: &Path) -> Option<String> {
+    let relative = roots.iter().find_map(|root| changed.strip_prefix(root).ok())?;
+    let parts: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    if parts.is_empty() || parts[0] == ".git" {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+/// Sends a named event and its payload to the frontend
+type Emit = Arc<dyn Fn(&str, serde_json::Value) + Send + Sync>;
+
+fn app_emitter(app: AppHandle) -> Emit {
+    Arc::new(move |event, payload| {
+        let _ = app.emit(event, payload);
+    })
+}
+
+/// Per-worktree watcher settings. Read once per watch: checking config for
+/// every raw event would be too costly.
+#[derive(Clone, Copy, Default)]
+struct WatchOptions {
+    /// Emit a `watcher-event` for every raw event (`watcher.debug`)
+    debug: bool,
+    /// How long a deleted-looking worktree may stay missing before it's reported
+    /// removed (`watcher.removalGraceSecs`)
+    removal_grace: Duration,
+    /// Rescan only the paths touched since the last update (`watcher.incremental`)
+    incremental: bool,
+    /// Flag files whose mode changed (`git.detectModeChanges`)
+    detect_mode_changes: bool,
+    /// Timeout for each git process of a status read (`git.commandTimeout`)
+    git_timeout: Option<Duration>,
+}
+
+impl WatchOptions {
+    fn for_worktree(worktree_path: &str) -> Self {
+        let cfg = config::load_config_for_project(Some(worktree_path));
+        Self {
+            debug: cfg.watcher.debug,
+            removal_grace: Duration::from_secs(cfg.watcher.removal_grace_secs),
+            incremental: cfg.watcher.incremental,
+            detect_mode_changes: cfg.git.detect_mode_changes,
+            git_timeout: cfg.git.timeout(),
+        }
+    }
+}
+
+/// Messages handled by the worktree watcher's dispatch thread
+enum WatchMessage {
+    Watch(Box<WatchedWorktree>),
+    Unwatch(String),
+    UnwatchAll,
+    Fs(notify::Result<Event>),
+    /// A worker finished a worktree's existence check or status read
+    Done {
+        worktree_id: String,
+        result: WorkResult,
+    },
+    /// Replies with the watched worktrees (ID and path) once earlier messages are handled
+    Flush(Sender<Vec<(String, String)>>),
+}
+
+/// What a worker found out for the dispatch thread
+enum WorkResult {
+    Existence(Existence),
+    /// An inspect-mode poll's changed files
+    Polled(Vec<FileChange>),
+    /// A status update ran; whether the next one must be a full scan
+    Refreshed { needs_full_scan: bool },
+}
+
+// All worktrees share one notify watcher, owned by a single dispatch thread
+// that routes each event to the worktrees whose paths contain it
+lazy_static::lazy_static! {
+    static ref DISPATCHER: Mutex<Option<Sender<WatchMessage>>> = Mutex::new(None);
+}
+
+/// Dispatch threads started so far; the first watch starts the only one
+static DISPATCH_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Worktrees the dispatch thread is watching, refreshed after every message and tick
+static WATCHED_WORKTREES: AtomicUsize = AtomicUsize::new(0);
+
+/// How long the dispatch thread waits for a message before checking debounce
+/// deadlines, existence and inspect-mode polls
+const DISPATCH_TICK: Duration = Duration::from_millis(100);
+
+/// Trailing-edge debounce: wait until a worktree has had no events for this long
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often each worktree is checked for having been deleted externally
+const EXISTENCE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often a worktree in inspect mode has its status re-read
+const INSPECT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Worker threads running status reads and existence checks for the dispatch thread
+const STATUS_WORKERS: usize = 4;
 
+/// Send a message to the dispatch thread, starting it if needed
+fn dispatch(message: WatchMessage) {
+    let mut dispatcher = DISPATCHER.lock();
+    let sender = dispatcher.get_or_insert_with(start_dispatcher);
+    let _ = sender.send(message);
+}
+
+fn start_dispatcher() -> Sender<WatchMessage> {
+    let (tx, rx) = channel::<WatchMessage>();
+    let fs_tx = tx.clone();
+    let reply = tx.clone();
+    DISPATCH_THREADS.fetch_add(1, Ordering::SeqCst);
+
+    thread::spawn(move || {
         let config = Config::default()
             .with_poll_interval(Duration::from_secs(2))
             .with_compare_contents(false);
-
-        let mut watcher: RecommendedWatcher = match Watcher::new(tx, config) {
-            Ok(w) => w,
+        let handler = move |event: notify::Result<Event>| {
+            let _ = fs_tx.send(WatchMessage::Fs(event));
+        };
+        let watcher = match RecommendedWatcher::new(handler, config) {
+            Ok(watcher) => Some(watcher),
             Err(e) => {
-                eprintln!("Failed to create watcher: {}", e);
-                WATCHERS.lock().remove(&worktree_id_clone);
-                return;
+                log::warn!("[Watcher] Failed to create watcher: {}", e);
+                None
             }
         };
+        Dispatcher {
+            watcher,
+            worktrees: HashMap::new(),
+            workers: WorkerPool::new(STATUS_WORKERS),
+            reply,
+        }
+        .run(rx);
+    });
+    tx
+}
 
-        let path = Path::new(&worktree_path);
-        if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
-            eprintln!("Failed to watch path: {}", e);
-            WATCHERS.lock().remove(&worktree_id_clone);
-            return;
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of threads running jobs in the order they're queued. Anything
+/// that touches the disk or runs git goes through here, so a slow worktree
+/// (a huge repo, a hung mount) never holds up event routing for the rest.
+struct WorkerPool {
+    jobs: Sender<Job>,
+}
+
+impl WorkerPool {
+    fn new(size: usize) -> Self {
+        let (jobs, rx) = channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..size {
+            let rx = Arc::clone(&rx);
+            thread::spawn(move || loop {
+                let job = rx.lock().recv();
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            });
+        }
+        Self { jobs }
+    }
+
+    fn run(&self, job: impl FnOnce() + Send + 'static) {
+        let _ = self.jobs.send(Box::new(job));
+    }
+}
+
+/// State of the dispatch thread. It only routes events and keeps the debounce
+/// bookkeeping; checks and status reads run on `workers` and report back as
+/// `WatchMessage::Done`.
+struct Dispatcher {
+    watcher: Option<RecommendedWatcher>,
+    worktrees: HashMap<String, WatchedWorktree>,
+    workers: WorkerPool,
+    /// The dispatch thread's own channel, for workers to report back on
+    reply: Sender<WatchMessage>,
+}
+
+impl Dispatcher {
+    fn run(mut self, rx: Receiver<WatchMessage>) {
+        loop {
+            match rx.recv_timeout(DISPATCH_TICK) {
+                Ok(message) => self.handle(message),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            self.tick();
+            WATCHED_WORKTREES.store(self.worktrees.len(), Ordering::Relaxed);
+        }
+    }
+
+    fn handle(&mut self, message: WatchMessage) {
+        match message {
+            WatchMessage::Watch(worktree) => {
+                if self.worktrees.contains_key(&worktree.worktree_id) {
+                    return;
+                }
+                let mut worktree = *worktree;
+                if !worktree.inspect && !self.add_watches(&mut worktree) {
+                    return;
+                }
+                self.worktrees.insert(worktree.worktree_id.clone(), worktree);
+            }
+            WatchMessage::Unwatch(worktree_id) => {
+                if let Some(worktree) = self.worktrees.remove(&worktree_id) {
+                    log::info!("[Watcher] Stopping watcher for {}", worktree_id);
+                    self.remove_watches(&worktree);
+                }
+            }
+            WatchMessage::UnwatchAll => {
+                for (_, worktree) in std::mem::take(&mut self.worktrees) {
+                    self.remove_watches(&worktree);
+                }
+            }
+            WatchMessage::Fs(Ok(event)) => {
+                for worktree in self.worktrees.values_mut() {
+                    let paths: Vec<&PathBuf> = event.paths.iter().filter(|p| worktree.owns(p)).collect();
+                    if !paths.is_empty() || (event.paths.is_empty() && event.need_rescan()) {
+                        worktree.on_event(&event, &paths);
+                    }
+                }
+            }
+            WatchMessage::Fs(Err(e)) => {
+                log::warn!("[Watcher] Watch error: {}", e);
+                for worktree in self.worktrees.values_mut() {
+                    if e.paths.is_empty() || e.paths.iter().any(|p| worktree.owns(p)) {
+                        worktree.needs_full_scan = true;
+                    }
+                }
+            }
+            WatchMessage::Done { worktree_id, result } => {
+                // The worktree may have been unwatched while the worker ran
+                let Some(worktree) = self.worktrees.get_mut(&worktree_id) else {
+                    return;
+                };
+                match result {
+                    WorkResult::Existence(existence) => {
+                        worktree.checking = false;
+                        let outcome = worktree.on_existence(Instant::now(), existence);
+                        self.apply(worktree_id, outcome);
+                    }
+                    WorkResult::Polled(files) => {
+                        worktree.busy = false;
+                        worktree.on_polled(files);
+                    }
+                    WorkResult::Refreshed { needs_full_scan } => {
+                        worktree.busy = false;
+                        worktree.needs_full_scan |= needs_full_scan;
+                    }
+                }
+            }
+            WatchMessage::Flush(reply) => {
+                let watched = self
+                    .worktrees
+                    .values()
+                    .map(|worktree| (worktree.worktree_id.clone(), worktree.worktree_path.clone()))
+                    .collect();
+                let _ = reply.send(watched);
+            }
         }
+    }
+
+    /// Watch a worktree's files and its git directory (for index changes).
+    /// Returns false if the worktree itself can't be watched.
+    fn add_watches(&mut self, worktree: &mut WatchedWorktree) -> bool {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return false;
+        };
+        if let Err(e) = watcher.watch(&worktree.path, RecursiveMode::Recursive) {
+            log::warn!("[Watcher] Failed to watch path: {}", e);
+            return false;
+        }
+        worktree.watched.push(worktree.path.clone());
 
         // Also watch the git index file to detect staging/unstaging changes.
         // For regular repos, .git is a directory; for worktrees, .git is a file
         // pointing to the actual git directory (e.g., .git/worktrees/<name>).
         // The index file is in the git directory.
-        if let Some(git_dir) = resolve_git_dir(path) {
-            // Watch the git directory (non-recursive) to catch index changes
-            if let Err(e) = watcher.watch(&git_dir, RecursiveMode::NonRecursive) {
+        if let Some(git_dir) = worktree.git_dir.clone() {
+            match watcher.watch(&git_dir, RecursiveMode::NonRecursive) {
+                Ok(()) => worktree.watched.push(git_dir),
                 // Non-fatal: we can still watch file changes even if we can't watch the index
-                eprintln!("[Watcher] Failed to watch git dir {:?}: {}", git_dir, e);
+                Err(e) => log::warn!("[Watcher] Failed to watch git dir {:?}: {}", git_dir, e),
             }
         }
+        true
+    }
 
-        // Trailing-edge debounce: wait until no events for this duration
-        let debounce_duration = Duration::from_millis(500);
-        let mut pending_update = false;
-        let mut last_event_time = std::time::Instant::now();
+    fn remove_watches(&mut self, worktree: &WatchedWorktree) {
+        if let Some(watcher) = self.watcher.as_mut() {
+            for path in &worktree.watched {
+                let _ = watcher.unwatch(path);
+            }
+        }
+    }
 
-        // Check for external folder deletion every 2 seconds (not every loop iteration)
-        let existence_check_interval = Duration::from_secs(2);
-        let mut last_existence_check = std::time::Instant::now();
+    /// Hand due existence checks and status updates to the workers
+    fn tick(&mut self) {
+        let now = Instant::now();
+        for worktree in self.worktrees.values_mut() {
+            worktree.tick(now, &self.workers, &self.reply);
+        }
+    }
 
-        loop {
-            // Check for stop signal
-            if stop_rx.try_recv().is_ok() {
-                eprintln!("[Watcher] Stopping watcher for {}", worktree_id_clone);
-                break;
+    /// Drop a worktree whose folder was deleted, or re-watch one whose folder came back
+    fn apply(&mut self, worktree_id: String, outcome: TickOutcome) {
+        if outcome == TickOutcome::Watching {
+            return;
+        }
+        let Some(mut worktree) = self.worktrees.remove(&worktree_id) else {
+            return;
+        };
+        self.remove_watches(&worktree);
+        if outcome == TickOutcome::Restored {
+            worktree.watched.clear();
+            if worktree.inspect || self.add_watches(&mut worktree) {
+                self.worktrees.insert(worktree_id, worktree);
             }
+        }
+    }
+}
 
-            // Use short timeout to check for debounce expiry
-            match rx.recv_timeout(Duration::from_millis(100)) {
-                Ok(Ok(_event)) => {
-                    // New event: mark pending and reset timer
-                    pending_update = true;
-                    last_event_time = std::time::Instant::now();
-                }
-                Ok(Err(e)) => {
-                    eprintln!("Watch error: {}", e);
-                }
-                Err(_) => {
-                    // Timeout - check if we should process pending update
+/// What the dispatch thread should do with a worktree after an existence check
+#[derive(Debug, PartialEq)]
+enum TickOutcome {
+    Watching,
+    /// The folder came back after being unavailable, so its watches need re-adding
+    Restored,
+    /// The folder was deleted; stop watching it
+    Removed,
+}
+
+/// Whether a worktree's folder, and the folder it's in, are there
+#[derive(Clone, Copy)]
+struct Existence {
+    exists: bool,
+    parent_exists: bool,
+}
+
+impl Existence {
+    /// Look at the disk, which may block for a while on a dropped network mount
+    fn probe(path: &Path) -> Self {
+        Self {
+            exists: path.exists(),
+            parent_exists: path.parent().is_none_or(Path::exists),
+        }
+    }
+}
+
+/// One watched worktree and its pending status update
+struct WatchedWorktree {
+    worktree_id: String,
+    worktree_path: String,
+    path: PathBuf,
+    /// The path as given and canonicalized, since events may use either
+    roots: Vec<PathBuf>,
+    git_dir: Option<PathBuf>,
+    /// Paths registered with the shared notify watcher
+    watched: Vec<PathBuf>,
+    options: WatchOptions,
+    /// Poll the status instead of relying on events (inspect mode)
+    inspect: bool,
+    emit: Emit,
+    pending_update: bool,
+    last_event_time: Instant,
+    // Incremental mode state: paths touched since the last update, and the
+    // cached list they're merged into. Anything that can't be narrowed to
+    // paths in the worktree (git dir, overflow, errors) forces a full scan.
+    touched: HashSet<String>,
+    needs_full_scan: bool,
+    last_full_scan: Instant,
+    /// Shared with the worker running a status update; only one runs at a time
+    cache: Arc<Mutex<StatusCache>>,
+    /// A status update or inspect-mode poll is running on a worker
+    busy: bool,
+    /// An existence check is running on a worker
+    checking: bool,
+    last_existence_check: Instant,
+    /// When the folder was first found missing, while it still is
+    missing_since: Option<Instant>,
+    /// Last inspect-mode poll and its result
+    last_poll: Option<(Instant, Vec<FileChange>)>,
+}
+
+impl WatchedWorktree {
+    /// Resolves the worktree's canonical path and git directory, so call it off
+    /// the dispatch thread
+    fn new(worktree_id: String, worktree_path: String, options: WatchOptions, inspect: bool, emit: Emit) -> Self {
+        let path = PathBuf::from(&worktree_path);
+        let mut roots = vec![path.clone()];
+        roots.extend(path.canonicalize().ok().filter(|canonical| *canonical != path));
+        let git_dir = git::resolve_git_dir(&path).ok();
+        let now = Instant::now();
+        Self {
+            worktree_id,
+            worktree_path,
+            path,
+            roots,
+            git_dir,
+            watched: Vec::new(),
+            options,
+            inspect,
+            emit,
+            pending_update: false,
+            last_event_time: now,
+            touched: HashSet::new(),
+            needs_full_scan: true,
+            last_full_scan: now,
+            cache: Arc::new(Mutex::new(StatusCache::default())),
+            busy: false,
+            checking: false,
+            last_existence_check: now,
+            missing_since: None,
+            last_poll: None,
+        }
+    }
+
+    fn emit(&self, event: &str, payload: impl serde::Serialize) {
+        (self.emit)(event, serde_json::to_value(payload).unwrap_or_default());
+    }
+
+    /// Whether an event path belongs to this worktree or its git directory
+    fn owns(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+            || self.git_dir.as_ref().is_some_and(|git_dir| path.starts_with(git_dir))
+    }
+
+    /// Record an event whose `paths` (already filtered to this worktree) changed
+    fn on_event(&mut self, event: &Event, paths: &[&PathBuf]) {
+        if self.options.debug {
+            self.emit(
+                "watcher-event",
+                WatcherEvent {
+                    worktree_path: self.worktree_path.clone(),
+                    kind: format!("{:?}", event.kind),
+                    paths: paths.iter().map(|p| p.to_string_lossy().into_owned()).collect(),
+                },
+            );
+        }
+
+        // New event: mark pending and reset timer
+        self.pending_update = true;
+        self.last_event_time = Instant::now();
+
+        if event.need_rescan() {
+            self.needs_full_scan = true;
+        }
+        if !self.needs_full_scan {
+            let roots: Vec<&Path> = self.roots.iter().map(PathBuf::as_path).collect();
+            for changed in paths {
+                match worktree_relative(&roots, changed) {
+                    // Ignore rules changed: any path's status may have
+                    Some(relative) if relative.rsplit('/').next() == Some(".gitignore") => {
+                        self.needs_full_scan = true;
+                    }
+                    Some(relative) => {
+                        self.touched.insert(relative);
+                    }
+                    None => self.needs_full_scan = true,
                 }
             }
+            if self.touched.len() > MAX_INCREMENTAL_PATHS {
+                self.needs_full_scan = true;
+            }
+        }
+    }
 
-            // Periodically check if worktree folder was deleted externally
-            if last_existence_check.elapsed() >= existence_check_interval {
-                last_existence_check = std::time::Instant::now();
-                if !path.exists() {
-                    eprintln!(
-                        "[Watcher] Worktree folder deleted externally: {}",
-                        worktree_path
-                    );
-                    let _ = app.emit(
-                        "worktree-removed",
-                        WorktreeRemoved {
-                            worktree_path: worktree_path.clone(),
-                        },
-                    );
-                    break;
-                }
+    /// Queue whatever is due at `now`: the existence check, then (while the
+    /// folder is there) any due status update. Each kind of job runs at most
+    /// once at a time per worktree.
+    fn tick(&mut self, now: Instant, workers: &WorkerPool, reply: &Sender<WatchMessage>) {
+        if !self.checking && now.duration_since(self.last_existence_check) >= EXISTENCE_CHECK_INTERVAL {
+            self.last_existence_check = now;
+            self.checking = true;
+            let path = self.path.clone();
+            self.queue(workers, reply, move || WorkResult::Existence(Existence::probe(&path)));
+        }
+        if self.missing_since.is_some() || self.busy {
+            return;
+        }
+
+        if self.inspect {
+            let due = match &self.last_poll {
+                Some((polled_at, _)) => now.duration_since(*polled_at) >= INSPECT_POLL_INTERVAL,
+                None => true,
+            };
+            if due {
+                self.busy = true;
+                let (path, worktree_path, options) = (self.path.clone(), self.worktree_path.clone(), self.options);
+                self.queue(workers, reply, move || WorkResult::Polled(poll_status(&path, &worktree_path, options)));
+            }
+        } else if self.pending_update && now.duration_since(self.last_event_time) >= DEBOUNCE {
+            self.pending_update = false;
+            self.busy = true;
+            let full_scan = self.needs_full_scan || now.duration_since(self.last_full_scan) >= FULL_SCAN_INTERVAL;
+            if full_scan {
+                self.last_full_scan = now;
+            }
+            self.needs_full_scan = false;
+            let refresh = StatusRefresh {
+                worktree_path: self.worktree_path.clone(),
+                path: self.path.clone(),
+                emit: Arc::clone(&self.emit),
+                cache: Arc::clone(&self.cache),
+                scopes: self.touched.drain().collect(),
+                full_scan,
+                options: self.options,
+            };
+            self.queue(workers, reply, move || WorkResult::Refreshed {
+                needs_full_scan: refresh.run(),
+            });
+        }
+    }
+
+    /// Run `work` on a worker and report its result back to the dispatch thread
+    fn queue(
+        &self,
+        workers: &WorkerPool,
+        reply: &Sender<WatchMessage>,
+        work: impl FnOnce() -> WorkResult + Send + 'static,
+    ) {
+        let worktree_id = self.worktree_id.clone();
+        let reply = reply.clone();
+        workers.run(move || {
+            let result = work();
+            let _ = reply.send(WatchMessage::Done { worktree_id, result });
+        });
+    }
+
+    /// Notice the folder disappearing or coming back. A folder that's gone while
+    /// its parent is still there is taken as deleted, once it has stayed gone
+    /// for the grace period; if the parent is gone too (a dropped network
+    /// mount, say) it's only reported unavailable, however long that lasts.
+    fn on_existence(&mut self, now: Instant, existence: Existence) -> TickOutcome {
+        if existence.exists {
+            if self.missing_since.take().is_none() {
+                return TickOutcome::Watching;
             }
+            log::info!("[Watcher] Worktree folder is available again: {}", self.worktree_path);
+            self.emit(
+                "worktree-available",
+                WorktreeAvailable {
+                    worktree_path: self.worktree_path.clone(),
+                },
+            );
+            // Changes made meanwhile went unseen
+            self.needs_full_scan = true;
+            self.pending_update = true;
+            self.last_poll = None;
+            return TickOutcome::Restored;
+        }
 
-            // Process pending update after debounce period of quiet
-            if pending_update && last_event_time.elapsed() >= debounce_duration {
-                pending_update = false;
+        let first_noticed = self.missing_since.is_none();
+        let mount_unavailable = !existence.parent_exists;
+        if mount_unavailable {
+            // The grace period starts once the parent is back
+            self.missing_since = Some(now);
+        }
+        let missing_since = *self.missing_since.get_or_insert(now);
+        if !mount_unavailable && now.duration_since(missing_since) >= self.options.removal_grace {
+            log::info!("[Watcher] Worktree folder deleted externally: {}", self.worktree_path);
+            self.emit(
+                "worktree-removed",
+                WorktreeRemoved {
+                    worktree_path: self.worktree_path.clone(),
+                },
+            );
+            return TickOutcome::Removed;
+        }
+        if first_noticed {
+            log::warn!(
+                "[Watcher] Worktree folder unavailable{}: {}",
+                if mount_unavailable { " (parent folder missing too)" } else { "" },
+                self.worktree_path
+            );
+            self.emit(
+                "worktree-unavailable",
+                WorktreeUnavailable {
+                    worktree_path: self.worktree_path.clone(),
+                    mount_unavailable,
+                },
+            );
+        }
+        TickOutcome::Watching
+    }
 
-                // Get changed files and emit
-                if let Ok(files) = git::get_changed_files(path) {
-                    let _ = app.emit(
-                        "files-changed",
-                        FilesChanged {
-                            worktree_path: worktree_path.clone(),
-                            files,
-                        },
-                    );
-                }
+    /// Take an inspect-mode poll's result, emitting `files-changed` when it
+    /// differs from the last one
+    fn on_polled(&mut self, files: Vec<FileChange>) {
+        if self.last_poll.as_ref().map(|(_, last)| last) != Some(&files) {
+            self.emit(
+                "files-changed",
+                FilesChanged {
+                    worktree_path: self.worktree_path.clone(),
+                    files: files.clone(),
+                },
+            );
+        }
+        self.last_poll = Some((Instant::now(), files));
+    }
+}
+
+/// Read the status of a worktree in inspect mode. No filesystem watches are set
+/// up for these (a read-only mount may not deliver events anyway), and the
+/// status is read without git's optional index writes.
+fn poll_status(path: &Path, worktree_path: &str, options: WatchOptions) -> Vec<FileChange> {
+    let limits = git::GitLimits::timeout(options.git_timeout);
+    git::get_changed_files(path, options.detect_mode_changes, &limits).unwrap_or_else(|e| {
+        log::warn!("[Watcher] Failed to poll changed files for {}: {}", worktree_path, e);
+        Vec::new()
+    })
+}
+
+/// A status update after a debounced burst of events, run on a worker
+struct StatusRefresh {
+    worktree_path: String,
+    path: PathBuf,
+    emit: Emit,
+    cache: Arc<Mutex<StatusCache>>,
+    /// Paths touched since the last update, for an incremental scan
+    scopes: Vec<String>,
+    full_scan: bool,
+    options: WatchOptions,
+}
+
+impl StatusRefresh {
+    fn emit(&self, event: &str, payload: impl serde::Serialize) {
+        (self.emit)(event, serde_json::to_value(payload).unwrap_or_default());
+    }
+
+    /// Get changed files and emit them. Returns whether the next update needs
+    /// a full scan.
+    fn run(self) -> bool {
+        let detect_mode_changes = self.options.detect_mode_changes;
+        let limits = git::GitLimits::timeout(self.options.git_timeout);
+        if !self.options.incremental {
+            if let Ok(files) = git::get_changed_files(&self.path, detect_mode_changes, &limits) {
+                self.emit(
+                    "files-changed",
+                    FilesChanged {
+                        worktree_path: self.worktree_path.clone(),
+                        files,
+                    },
+                );
             }
+            return true;
         }
 
-        WATCHERS.lock().remove(&worktree_id_clone);
-    });
+        let mut cache = self.cache.lock();
+        let result = if self.full_scan {
+            git::get_changed_files(&self.path, detect_mode_changes, &limits).map(|files| cache.replace_all(files))
+        } else {
+            git::get_changed_files_at(&self.path, &self.scopes, detect_mode_changes, &limits)
+                .map(|files| cache.update_paths(&self.scopes, files))
+        };
+
+        match result {
+            Ok((changed, removed)) if !changed.is_empty() || !removed.is_empty() => {
+                self.emit(
+                    "files-changed-delta",
+                    FilesChangedDelta {
+                        worktree_path: self.worktree_path.clone(),
+                        changed,
+                        removed,
+                    },
+                );
+                // Full list too, for listeners that don't apply deltas
+                self.emit(
+                    "files-changed",
+                    FilesChanged {
+                        worktree_path: self.worktree_path.clone(),
+                        files: cache.snapshot(),
+                    },
+                );
+                false
+            }
+            Ok(_) => false,
+            Err(e) => {
+                log::warn!("[Watcher] Failed to get changed files for {}: {}", self.worktree_path, e);
+                true
+            }
+        }
+    }
+}
+
+/// Watch a worktree for file changes, emitting `files-changed` (and
+/// `files-changed-delta` with `watcher.incremental`) after each burst of
+/// changes, and `worktree-removed` if its folder is deleted externally.
+/// Worktrees in inspect mode are polled instead. Watching an already watched
+/// worktree does nothing.
+pub fn watch_worktree(app: AppHandle, worktree_id: String, worktree_path: String) {
+    let options = WatchOptions::for_worktree(&worktree_path);
+    let inspect = git::is_inspect_worktree(&worktree_id);
+    watch_with(worktree_id, worktree_path, options, inspect, app_emitter(app));
+}
+
+fn watch_with(worktree_id: String, worktree_path: String, options: WatchOptions, inspect: bool, emit: Emit) {
+    let worktree = WatchedWorktree::new(worktree_id, worktree_path, options, inspect, emit);
+    dispatch(WatchMessage::Watch(Box::new(worktree)));
+}
+
+/// Watch every worktree of the active projects in the persisted state, skipping
+/// suspended ones, and resume merge/rebase watchers for worktrees left mid-merge
+/// or mid-rebase. Worktrees already watched are left alone, so calling this again
+/// is harmless.
+pub fn start_all_watchers(app: AppHandle) {
+    let state = app.state::<Arc<AppState>>();
+    let watched = watch_all_with(&state.persisted.read().projects, WatchOptions::for_worktree, app_emitter(app.clone()));
+    for (worktree_id, worktree_path) in watched {
+        // Only resume watchers for an unfinished merge or rebase: with hooks
+        // installed they'd otherwise start two threads for every worktree
+        if git::merge_or_rebase_in_progress(Path::new(&worktree_path)) {
+            watch_merge_state(app.clone(), worktree_id.clone(), worktree_path.clone());
+            watch_rebase_state(app.clone(), worktree_id, worktree_path);
+        }
+    }
+}
+
+/// Start file watchers for the worktrees `start_all_watchers` covers, returning
+/// their IDs and paths
+fn watch_all_with(
+    projects: &[Project],
+    options: impl Fn(&str) -> WatchOptions,
+    emit: Emit,
+) -> Vec<(String, String)> {
+    let worktrees: Vec<(String, String)> = projects
+        .iter()
+        .filter(|p| p.is_active)
+        .flat_map(|p| &p.worktrees)
+        .filter(|wt| !wt.suspended)
+        .map(|wt| (wt.id.clone(), wt.path.clone()))
+        .collect();
+    for (worktree_id, worktree_path) in &worktrees {
+        let inspect = git::is_inspect_worktree(worktree_id);
+        watch_with(worktree_id.clone(), worktree_path.clone(), options(worktree_path), inspect, Arc::clone(&emit));
+    }
+    worktrees
 }
 
+/// Move a watched worktree's directory with `relocate`, which returns the new
+/// path. The file watcher is stopped first, and only once that has taken effect
+/// does the move start, so the old path vanishing isn't reported as
+/// `worktree-removed`. On success the worktree is watched at its new path (in
+/// inspect mode if it was), along with its git log if that was watched; on
+/// failure, again at the old one.
+pub fn relocate_worktree<E>(
+    app: AppHandle,
+    worktree_id: &str,
+    worktree_path: &str,
+    relocate: impl FnOnce() -> Result<String, E>,
+) -> Result<String, E> {
+    let options = WatchOptions::for_worktree(worktree_path);
+    let inspect = git::is_inspect_worktree(worktree_id);
+    relocate_with(worktree_id, worktree_path, options, inspect, app_emitter(app), relocate)
+}
+
+fn relocate_with<E>(
+    worktree_id: &str,
+    worktree_path: &str,
+    options: WatchOptions,
+    inspect: bool,
+    emit: Emit,
+    relocate: impl FnOnce() -> Result<String, E>,
+) -> Result<String, E> {
+    let watching_git_log = GIT_LOG_WATCHERS.lock().contains_key(worktree_id);
+    stop_watching(worktree_id);
+    let (tx, rx) = channel();
+    dispatch(WatchMessage::Flush(tx));
+    let _ = rx.recv_timeout(Duration::from_secs(10));
+
+    let result = relocate();
+    let path = result.as_ref().map_or(worktree_path, String::as_str);
+    watch_with(worktree_id.to_string(), path.to_string(), options, inspect, Arc::clone(&emit));
+    if watching_git_log {
+        watch_git_log_with(worktree_id.to_string(), path.to_string(), emit);
+    }
+    result
+}
+
+/// Stop a worktree's file watcher, and its git log watcher if one is running
 pub fn stop_watching(worktree_id: &str) {
-    if let Some(tx) = WATCHERS.lock().remove(worktree_id) {
-        let _ = tx.send(());
+    if let Some(sender) = DISPATCHER.lock().as_ref() {
+        let _ = sender.send(WatchMessage::Unwatch(worktree_id.to_string()));
     }
+    stop_git_log_watcher(worktree_id);
+}
+
+/// Number of worktrees with a file watcher
+pub fn watched_worktree_count() -> usize {
+    WATCHED_WORKTREES.load(Ordering::Relaxed)
 }
 
 pub fn stop_all_watchers() {
     // Stop file watchers
-    let watchers = std::mem::take(&mut *WATCHERS.lock());
-    for (_, tx) in watchers {
-        let _ = tx.send(());
+    if let Some(sender) = DISPATCHER.lock().as_ref() {
+        let _ = sender.send(WatchMessage::UnwatchAll);
     }
 
     // Stop config watcher
@@ -185,6 +934,17 @@ pub fn stop_all_watchers() {
 
     // Stop mappings watcher
     stop_mappings_watcher();
+
+    // Stop merge and rebase state watchers
+    for (_, tx) in std::mem::take(&mut *MERGE_WATCHERS.lock()) {
+        let _ = tx.send(());
+    }
+    for (_, tx) in std::mem::take(&mut *REBASE_WATCHERS.lock()) {
+        let _ = tx.send(());
+    }
+    for (_, tx) in std::mem::take(&mut *GIT_LOG_WATCHERS.lock()) {
+        let _ = tx.send(());
+    }
 }
 
 // Track active merge watchers
@@ -200,7 +960,11 @@ pub struct MergeComplete {
 }
 
 /// Watch for merge completion in a worktree.
-/// Detects when MERGE_HEAD is deleted (merge committed successfully).
+/// Detects when MERGE_HEAD is deleted (merge committed successfully), or when the
+/// worktree's post-merge hook fires if `git::install_worktree_hooks` was used. With
+/// hooks installed the sentinel is watched even without a merge in progress, so
+/// fast-forward merges and pulls, which never write MERGE_HEAD, are seen too, and
+/// the watcher keeps running for later merges until the hooks are removed.
 pub fn watch_merge_state(app: AppHandle, worktree_id: String, worktree_path: String) {
     // Check if already watching
     if MERGE_WATCHERS.lock().contains_key(&worktree_id) {
@@ -208,20 +972,25 @@ pub fn watch_merge_state(app: AppHandle, worktree_id: String, worktree_path: Str
     }
 
     // Resolve the actual git directory (handles both regular repos and worktrees)
-    let Some(git_dir) = resolve_git_dir(Path::new(&worktree_path)) else {
-        eprintln!("[MergeWatcher] Could not resolve git dir for {:?}", worktree_path);
-        return;
+    let git_dir = match git::resolve_git_dir(Path::new(&worktree_path)) {
+        Ok(git_dir) => git_dir,
+        Err(e) => {
+            log::warn!("[MergeWatcher] Could not resolve git dir for {:?}: {}", worktree_path, e);
+            return;
+        }
     };
 
     let merge_head_path = git_dir.join("MERGE_HEAD");
+    let mut sentinel = HookSentinel::new(&git_dir, "post-merge");
 
-    // Only start watching if MERGE_HEAD exists (we're in a merge state)
-    if !merge_head_path.exists() {
-        eprintln!("[MergeWatcher] No MERGE_HEAD found at {:?}, not watching", merge_head_path);
+    // Only start watching if MERGE_HEAD exists (we're in a merge state) or a hook can signal
+    let mut in_merge = merge_head_path.exists();
+    if !in_merge && !git::hooks_installed(&git_dir) {
+        log::debug!("[MergeWatcher] No MERGE_HEAD found at {:?}, not watching", merge_head_path);
         return;
     }
 
-    eprintln!("[MergeWatcher] Starting merge watcher for {} at {:?}", worktree_id, merge_head_path);
+    log::info!("[MergeWatcher] Starting merge watcher for {} at {:?}", worktree_id, merge_head_path);
 
     let (stop_tx, stop_rx) = channel::<()>();
     MERGE_WATCHERS.lock().insert(worktree_id.clone(), stop_tx);
@@ -235,13 +1004,23 @@ pub fn watch_merge_state(app: AppHandle, worktree_id: String, worktree_path: Str
         loop {
             // Check for stop signal
             if stop_rx.try_recv().is_ok() {
-                eprintln!("[MergeWatcher] Stopping merge watcher for {}", worktree_id_clone);
+                log::info!("[MergeWatcher] Stopping merge watcher for {}", worktree_id_clone);
                 break;
             }
 
-            // Check if MERGE_HEAD still exists
-            if !merge_head_path.exists() {
-                eprintln!("[MergeWatcher] MERGE_HEAD deleted - merge complete for {}", worktree_id_clone);
+            // Check the sentinel every poll so a signal is only acted on once
+            let fired = sentinel.fired();
+            if merge_head_path.exists() {
+                in_merge = true;
+            } else if in_merge || fired {
+                in_merge = false;
+                log::info!("[MergeWatcher] Merge complete for {}", worktree_id_clone);
+                crate::notifications::notify(
+                    &app,
+                    crate::notifications::NotificationEvent::MergeComplete,
+                    "Merge complete",
+                    &format!("Merge committed in {}", worktree_path_clone),
+                );
                 let _ = app.emit(
                     "merge-complete",
                     MergeComplete {
@@ -249,6 +1028,10 @@ pub fn watch_merge_state(app: AppHandle, worktree_id: String, worktree_path: Str
                         worktree_path: worktree_path_clone.clone(),
                     },
                 );
+            }
+
+            // Without hooks there's nothing more to see once the merge is done
+            if !in_merge && !git::hooks_installed(&git_dir) {
                 break;
             }
 
@@ -265,6 +1048,40 @@ pub fn stop_merge_watcher(worktree_id: &str) {
     }
 }
 
+/// Number of running merge watcher threads
+pub fn merge_watcher_count() -> usize {
+    MERGE_WATCHERS.lock().len()
+}
+
+/// The sentinel file written by a worktree's Shellflow hooks, the hook being
+/// watched for, and the sentinel's modification time when it was last checked
+struct HookSentinel {
+    git_dir: PathBuf,
+    hook: &'static str,
+    last: Option<std::time::SystemTime>,
+}
+
+impl HookSentinel {
+    fn new(git_dir: &Path, hook: &'static str) -> Self {
+        let last = Self::modified(git_dir);
+        Self { git_dir: git_dir.to_path_buf(), hook, last }
+    }
+
+    fn modified(git_dir: &Path) -> Option<std::time::SystemTime> {
+        std::fs::metadata(git_dir.join(git::HOOK_SENTINEL)).and_then(|m| m.modified()).ok()
+    }
+
+    /// Whether the watched hook wrote the sentinel since the last check
+    fn fired(&mut self) -> bool {
+        let current = Self::modified(&self.git_dir);
+        if current.is_none() || current == self.last {
+            return false;
+        }
+        self.last = current;
+        git::hook_signal(&self.git_dir).as_deref() == Some(self.hook)
+    }
+}
+
 // Track active rebase watchers
 lazy_static::lazy_static! {
     static ref REBASE_WATCHERS: Mutex<HashMap<String, Sender<()>>> = Mutex::new(HashMap::new());
@@ -278,7 +1095,11 @@ pub struct RebaseComplete {
 }
 
 /// Watch for rebase completion in a worktree.
-/// Detects when rebase-merge and rebase-apply are both gone (rebase finished).
+/// Detects when rebase-merge and rebase-apply are both gone (rebase finished), or
+/// when the worktree's post-rewrite hook fires if `git::install_worktree_hooks` was
+/// used. With hooks installed the sentinel is watched even without a rebase in
+/// progress, so rebasing pulls are seen too, and the watcher keeps running for
+/// later rebases until the hooks are removed. The hook doesn't signal amends.
 pub fn watch_rebase_state(app: AppHandle, worktree_id: String, worktree_path: String) {
     // Check if already watching
     if REBASE_WATCHERS.lock().contains_key(&worktree_id) {
@@ -286,21 +1107,26 @@ pub fn watch_rebase_state(app: AppHandle, worktree_id: String, worktree_path: St
     }
 
     // Resolve the actual git directory (handles both regular repos and worktrees)
-    let Some(git_dir) = resolve_git_dir(Path::new(&worktree_path)) else {
-        eprintln!("[RebaseWatcher] Could not resolve git dir for {:?}", worktree_path);
-        return;
+    let git_dir = match git::resolve_git_dir(Path::new(&worktree_path)) {
+        Ok(git_dir) => git_dir,
+        Err(e) => {
+            log::warn!("[RebaseWatcher] Could not resolve git dir for {:?}: {}", worktree_path, e);
+            return;
+        }
     };
 
     let rebase_merge_path = git_dir.join("rebase-merge");
     let rebase_apply_path = git_dir.join("rebase-apply");
+    let mut sentinel = HookSentinel::new(&git_dir, "post-rewrite");
 
-    // Only start watching if a rebase is in progress
-    if !rebase_merge_path.exists() && !rebase_apply_path.exists() {
-        eprintln!("[RebaseWatcher] No rebase in progress at {:?}, not watching", worktree_path);
+    // Only start watching if a rebase is in progress or a hook can signal
+    let mut in_rebase = rebase_merge_path.exists() || rebase_apply_path.exists();
+    if !in_rebase && !git::hooks_installed(&git_dir) {
+        log::debug!("[RebaseWatcher] No rebase in progress at {:?}, not watching", worktree_path);
         return;
     }
 
-    eprintln!("[RebaseWatcher] Starting rebase watcher for {} at {:?}", worktree_id, git_dir);
+    log::info!("[RebaseWatcher] Starting rebase watcher for {} at {:?}", worktree_id, git_dir);
 
     let (stop_tx, stop_rx) = channel::<()>();
     REBASE_WATCHERS.lock().insert(worktree_id.clone(), stop_tx);
@@ -314,13 +1140,17 @@ pub fn watch_rebase_state(app: AppHandle, worktree_id: String, worktree_path: St
         loop {
             // Check for stop signal
             if stop_rx.try_recv().is_ok() {
-                eprintln!("[RebaseWatcher] Stopping rebase watcher for {}", worktree_id_clone);
+                log::info!("[RebaseWatcher] Stopping rebase watcher for {}", worktree_id_clone);
                 break;
             }
 
-            // Check if rebase is still in progress
-            if !rebase_merge_path.exists() && !rebase_apply_path.exists() {
-                eprintln!("[RebaseWatcher] Rebase complete for {}", worktree_id_clone);
+            // Check the sentinel every poll so a signal is only acted on once
+            let fired = sentinel.fired();
+            if rebase_merge_path.exists() || rebase_apply_path.exists() {
+                in_rebase = true;
+            } else if in_rebase || fired {
+                in_rebase = false;
+                log::info!("[RebaseWatcher] Rebase complete for {}", worktree_id_clone);
                 let _ = app.emit(
                     "rebase-complete",
                     RebaseComplete {
@@ -328,6 +1158,10 @@ pub fn watch_rebase_state(app: AppHandle, worktree_id: String, worktree_path: St
                         worktree_path: worktree_path_clone.clone(),
                     },
                 );
+            }
+
+            // Without hooks there's nothing more to see once the rebase is done
+            if !in_rebase && !git::hooks_installed(&git_dir) {
                 break;
             }
 
@@ -344,6 +1178,146 @@ pub fn stop_rebase_watcher(worktree_id: &str) {
     }
 }
 
+/// Number of running rebase watcher threads
+pub fn rebase_watcher_count() -> usize {
+    REBASE_WATCHERS.lock().len()
+}
+
+// Track active git log watchers
+lazy_static::lazy_static! {
+    static ref GIT_LOG_WATCHERS: Mutex<HashMap<String, Sender<()>>> = Mutex::new(HashMap::new());
+}
+
+/// Commits sent with each `git-log-changed` event
+const GIT_LOG_COMMITS: usize = 50;
+
+/// Quiet period after the last HEAD update before the log is re-read
+const GIT_LOG_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitLogChanged {
+    pub worktree_id: String,
+    pub commits: Vec<git::CommitInfo>,
+}
+
+/// Watch a worktree's HEAD and its reflog (`logs/HEAD` in the resolved git dir),
+/// emitting `git-log-changed` with the latest commits whenever HEAD moves: a
+/// commit, amend, reset or checkout. Bursts of ref updates are debounced into
+/// one event, and nothing is sent if the commit list didn't change.
+pub fn watch_git_log(app: AppHandle, worktree_id: String, worktree_path: String) {
+    watch_git_log_with(worktree_id, worktree_path, app_emitter(app));
+}
+
+fn watch_git_log_with(worktree_id: String, worktree_path: String, emit: Emit) {
+    // Check if already watching
+    if GIT_LOG_WATCHERS.lock().contains_key(&worktree_id) {
+        return;
+    }
+
+    let git_dir = match git::resolve_git_dir(Path::new(&worktree_path)) {
+        Ok(git_dir) => git_dir,
+        Err(e) => {
+            log::warn!("[GitLogWatcher] Could not resolve git dir for {:?}: {}", worktree_path, e);
+            return;
+        }
+    };
+    let logs_dir = git_dir.join("logs");
+
+    // Set up the watcher before returning so no update after this call is missed
+    let (tx, rx) = channel::<notify::Result<Event>>();
+    let mut watcher: RecommendedWatcher = match Watcher::new(tx, Config::default()) {
+        Ok(w) => w,
+        Err(e) => {
+            log::warn!("[GitLogWatcher] Failed to create watcher: {}", e);
+            return;
+        }
+    };
+    // HEAD itself changes on checkout; logs/HEAD is appended to on every move,
+    // including commits to the checked-out branch. logs/ may not exist until
+    // the first commit, so it's picked up when it appears.
+    if let Err(e) = watcher.watch(&git_dir, RecursiveMode::NonRecursive) {
+        log::warn!("[GitLogWatcher] Failed to watch {:?}: {}", git_dir, e);
+        return;
+    }
+    let mut watching_logs = logs_dir.is_dir() && watcher.watch(&logs_dir, RecursiveMode::NonRecursive).is_ok();
+
+    log::info!("[GitLogWatcher] Starting git log watcher for {} at {:?}", worktree_id, git_dir);
+
+    let (stop_tx, stop_rx) = channel::<()>();
+    GIT_LOG_WATCHERS.lock().insert(worktree_id.clone(), stop_tx);
+
+    thread::spawn(move || {
+        let path = PathBuf::from(&worktree_path);
+        let hashes = |commits: &[git::CommitInfo]| commits.iter().map(|c| c.hash.clone()).collect::<Vec<_>>();
+        let mut last_hashes = git::get_recent_commits(&path, GIT_LOG_COMMITS)
+            .map(|commits| hashes(&commits))
+            .unwrap_or_default();
+
+        // Trailing-edge debounce
+        let mut pending_update = false;
+        let mut last_event_time = Instant::now();
+
+        loop {
+            // Check for stop signal
+            if stop_rx.try_recv().is_ok() {
+                log::info!("[GitLogWatcher] Stopping git log watcher for {}", worktree_id);
+                break;
+            }
+
+            match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(Ok(event)) => {
+                    if event.paths.iter().any(|p| p.ends_with("HEAD") || p.ends_with("logs")) {
+                        pending_update = true;
+                        last_event_time = Instant::now();
+                    }
+                }
+                Ok(Err(e)) => {
+                    log::warn!("[GitLogWatcher] Watch error: {}", e);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if !watching_logs && logs_dir.is_dir() {
+                watching_logs = watcher.watch(&logs_dir, RecursiveMode::NonRecursive).is_ok();
+            }
+
+            // Process pending update after debounce period
+            if pending_update && last_event_time.elapsed() >= GIT_LOG_DEBOUNCE {
+                pending_update = false;
+                let commits = match git::get_recent_commits(&path, GIT_LOG_COMMITS) {
+                    Ok(commits) => commits,
+                    Err(e) => {
+                        log::warn!("[GitLogWatcher] Failed to read log for {}: {}", worktree_id, e);
+                        continue;
+                    }
+                };
+                let current_hashes = hashes(&commits);
+                if current_hashes == last_hashes {
+                    continue;
+                }
+                last_hashes = current_hashes;
+
+                log::debug!("[GitLogWatcher] HEAD moved in {}, emitting event", worktree_id);
+                let payload = GitLogChanged { worktree_id: worktree_id.clone(), commits };
+                emit("git-log-changed", serde_json::to_value(payload).unwrap_or_default());
+            }
+        }
+    });
+}
+
+pub fn stop_git_log_watcher(worktree_id: &str) {
+    if let Some(tx) = GIT_LOG_WATCHERS.lock().remove(worktree_id) {
+        let _ = tx.send(());
+    }
+}
+
+/// Number of running git log watcher threads
+pub fn git_log_watcher_count() -> usize {
+    GIT_LOG_WATCHERS.lock().len()
+}
+
 // Track active config watcher
 lazy_static::lazy_static! {
     static ref CONFIG_WATCHER: Mutex<Option<Sender<()>>> = Mutex::new(None);
@@ -363,18 +1337,14 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
 
     let config_paths = config::get_config_paths(project_path.as_deref());
 
-    // Filter to only existing files and their parent directories
-    let watch_targets: Vec<PathBuf> = config_paths
+    // Watch parent directories rather than the files themselves, so we detect file
+    // creation and atomic saves (write to temp file + rename) that replace the inode
+    let mut watch_targets: Vec<PathBuf> = config_paths
         .iter()
-        .filter_map(|p| {
-            if p.exists() {
-                Some(p.clone())
-            } else {
-                // Watch parent directory so we detect file creation
-                p.parent().map(|parent| parent.to_path_buf())
-            }
-        })
+        .filter_map(|p| p.parent().map(|parent| parent.to_path_buf()))
+        .filter(|p| p.exists())
         .collect();
+    watch_targets.dedup();
 
     if watch_targets.is_empty() {
         return;
@@ -395,7 +1365,7 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
         let mut watcher: RecommendedWatcher = match Watcher::new(tx, watcher_config) {
             Ok(w) => w,
             Err(e) => {
-                eprintln!("[ConfigWatcher] Failed to create watcher: {}", e);
+                log::warn!("[ConfigWatcher] Failed to create watcher: {}", e);
                 *CONFIG_WATCHER.lock() = None;
                 return;
             }
@@ -404,11 +1374,11 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
         // Watch each target path
         for path in &watch_targets {
             if let Err(e) = watcher.watch(path, RecursiveMode::NonRecursive) {
-                eprintln!("[ConfigWatcher] Failed to watch {:?}: {}", path, e);
+                log::warn!("[ConfigWatcher] Failed to watch {:?}: {}", path, e);
             }
         }
 
-        eprintln!("[ConfigWatcher] Watching {} paths", watch_targets.len());
+        log::info!("[ConfigWatcher] Watching {} paths", watch_targets.len());
 
         // Trailing-edge debounce
         let debounce_duration = Duration::from_millis(300);
@@ -418,10 +1388,13 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
         // Track which files we care about
         let config_files: Vec<PathBuf> = config::get_config_paths(project_path_clone.as_deref());
 
+        // Theme directories, to tell the theme list to refresh when they change
+        let mut themes_config = config::load_config_for_project(project_path_clone.as_deref()).themes;
+
         loop {
             // Check for stop signal
             if stop_rx.try_recv().is_ok() {
-                eprintln!("[ConfigWatcher] Stopping config watcher");
+                log::info!("[ConfigWatcher] Stopping config watcher");
                 break;
             }
 
@@ -438,7 +1411,7 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
                     }
                 }
                 Ok(Err(e)) => {
-                    eprintln!("[ConfigWatcher] Watch error: {}", e);
+                    log::warn!("[ConfigWatcher] Watch error: {}", e);
                 }
                 Err(_) => {
                     // Timeout - check if we should process pending update
@@ -448,13 +1421,22 @@ pub fn watch_config(app: AppHandle, project_path: Option<String>) {
             // Process pending update after debounce period
             if pending_update && last_event_time.elapsed() >= debounce_duration {
                 pending_update = false;
-                eprintln!("[ConfigWatcher] Config changed, emitting event");
+                log::info!("[ConfigWatcher] Config changed, emitting event");
                 let _ = app.emit(
                     "config-changed",
                     ConfigChanged {
                         project_path: project_path_clone.clone(),
                     },
                 );
+
+                git::set_audit_log(config::load_config().git.audit_log);
+
+                let new_themes_config = config::load_config_for_project(project_path_clone.as_deref()).themes;
+                if new_themes_config != themes_config {
+                    log::info!("[ConfigWatcher] Theme directories changed, emitting event");
+                    themes_config = new_themes_config;
+                    let _ = app.emit("themes-changed", ());
+                }
             }
         }
 
@@ -504,19 +1486,19 @@ pub fn watch_mappings(app: AppHandle) {
         let mut watcher: RecommendedWatcher = match Watcher::new(tx, watcher_config) {
             Ok(w) => w,
             Err(e) => {
-                eprintln!("[MappingsWatcher] Failed to create watcher: {}", e);
+                log::warn!("[MappingsWatcher] Failed to create watcher: {}", e);
                 *MAPPINGS_WATCHER.lock() = None;
                 return;
             }
         };
 
         if let Err(e) = watcher.watch(&watch_target, RecursiveMode::NonRecursive) {
-            eprintln!("[MappingsWatcher] Failed to watch {:?}: {}", watch_target, e);
+            log::warn!("[MappingsWatcher] Failed to watch {:?}: {}", watch_target, e);
             *MAPPINGS_WATCHER.lock() = None;
             return;
         }
 
-        eprintln!("[MappingsWatcher] Watching {:?}", watch_target);
+        log::info!("[MappingsWatcher] Watching {:?}", watch_target);
 
         // Trailing-edge debounce
         let debounce_duration = Duration::from_millis(300);
@@ -526,7 +1508,7 @@ pub fn watch_mappings(app: AppHandle) {
         loop {
             // Check for stop signal
             if stop_rx.try_recv().is_ok() {
-                eprintln!("[MappingsWatcher] Stopping");
+                log::info!("[MappingsWatcher] Stopping");
                 break;
             }
 
@@ -546,7 +1528,7 @@ pub fn watch_mappings(app: AppHandle) {
                     }
                 }
                 Ok(Err(e)) => {
-                    eprintln!("[MappingsWatcher] Watch error: {}", e);
+                    log::warn!("[MappingsWatcher] Watch error: {}", e);
                 }
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                     // No event, check if we should emit debounced update
@@ -559,7 +1541,7 @@ pub fn watch_mappings(app: AppHandle) {
             // Check if debounce period has passed
             if pending_update && last_event_time.elapsed() >= debounce_duration {
                 pending_update = false;
-                eprintln!("[MappingsWatcher] Mappings changed, emitting event");
+                log::info!("[MappingsWatcher] Mappings changed, emitting event");
                 let _ = app.emit("mappings-changed", ());
             }
         }
@@ -573,3 +1555,320 @@ pub fn stop_mappings_watcher() {
         let _ = tx.send(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FileStatus;
+    use crate::test_support::{git_cmd, init_test_repo, worktree};
+
+    fn change(path: &str, status: FileStatus) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            status,
+            insertions: None,
+            deletions: None,
+            mode_changed: false,
+            old_path: None,
+        }
+    }
+
+    #[test]
+    fn worktree_relative_rejects_root_outside_and_git_dir() {
+        let root = Path::new("/repo");
+        let roots = [root];
+        assert_eq!(worktree_relative(&roots, Path::new("/repo/src/main.rs")).as_deref(), Some("src/main.rs"));
+        assert_eq!(worktree_relative(&roots, Path::new("/repo")), None);
+        assert_eq!(worktree_relative(&roots, Path::new("/repo/.git/index")), None);
+        assert_eq!(worktree_relative(&roots, Path::new("/elsewhere/file")), None);
+    }
+
+    #[test]
+    fn status_cache_reports_deltas() {
+        let mut cache = StatusCache::default();
+        let (changed, removed) =
+            cache.replace_all(vec![change("a.txt", FileStatus::Modified), change("dir/b.txt", FileStatus::Untracked)]);
+        assert_eq!(changed.len(), 2);
+        assert!(removed.is_empty());
+
+        // Scoped update: dir/b.txt was committed, dir/c.txt appeared, a.txt untouched
+        let (changed, removed) =
+            cache.update_paths(&["dir".to_string()], vec![change("dir/c.txt", FileStatus::Untracked)]);
+        assert_eq!(changed, vec![change("dir/c.txt", FileStatus::Untracked)]);
+        assert_eq!(removed, vec!["dir/b.txt"]);
+
+        // "di" isn't a parent of "dir/c.txt"
+        let (changed, removed) = cache.update_paths(&["di".to_string()], Vec::new());
+        assert!(changed.is_empty() && removed.is_empty());
+
+        // Unchanged entries aren't reported again
+        let (changed, removed) = cache.update_paths(&["a.txt".to_string()], vec![change("a.txt", FileStatus::Modified)]);
+        assert!(changed.is_empty() && removed.is_empty());
+
+        let paths: Vec<String> = cache.snapshot().into_iter().map(|f| f.path).collect();
+        assert_eq!(paths, vec!["a.txt", "dir/c.txt"]);
+
+        let (changed, removed) = cache.replace_all(vec![change("a.txt", FileStatus::Deleted)]);
+        assert_eq!(changed, vec![change("a.txt", FileStatus::Deleted)]);
+        assert_eq!(removed, vec!["dir/c.txt"]);
+    }
+
+    /// Handle everything sent so far, returning the watched worktrees (ID and path)
+    fn flush_paths() -> Vec<(String, String)> {
+        let (tx, rx) = channel();
+        dispatch(WatchMessage::Flush(tx));
+        rx.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+
+    /// Handle everything sent so far, returning the watched worktree IDs
+    fn flush() -> Vec<String> {
+        flush_paths().into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn many_worktrees_share_one_dispatch_thread() {
+        let dir = std::env::temp_dir().join(format!("shellflow-watcher-test-{}", uuid::Uuid::new_v4()));
+        let prefix = format!("{}-", dir.file_name().unwrap().to_string_lossy());
+        let ids: Vec<String> = (0..50).map(|i| format!("{}{}", prefix, i)).collect();
+        for id in &ids {
+            std::fs::create_dir_all(dir.join(id)).unwrap();
+        }
+        let emit: Emit = Arc::new(|_, _| {});
+        let watched_here = || flush().into_iter().filter(|id| id.starts_with(&prefix)).count();
+
+        for _ in 0..3 {
+            for id in &ids {
+                watch_with(id.clone(), dir.join(id).display().to_string(), WatchOptions::default(), false, Arc::clone(&emit));
+            }
+            // Watching twice is a no-op
+            watch_with(ids[0].clone(), dir.join(&ids[0]).display().to_string(), WatchOptions::default(), false, Arc::clone(&emit));
+            assert_eq!(watched_here(), ids.len());
+
+            for id in &ids {
+                stop_watching(id);
+            }
+            assert_eq!(watched_here(), 0);
+        }
+        assert_eq!(DISPATCH_THREADS.load(Ordering::SeqCst), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn start_all_watchers_skips_suspended_worktrees_and_hidden_projects() {
+        let dir = std::env::temp_dir().join(format!("shellflow-watcher-test-{}", uuid::Uuid::new_v4()));
+        let prefix = format!("{}-", dir.file_name().unwrap().to_string_lossy());
+        let worktree = |name: &str, suspended: bool| {
+            let path = dir.join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            crate::state::Worktree {
+                name: name.to_string(),
+                suspended,
+                ..worktree(&format!("{}{}", prefix, name), name, &path)
+            }
+        };
+        let project = |name: &str, is_active: bool, worktrees: Vec<crate::state::Worktree>| Project {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: dir.display().to_string(),
+            worktrees,
+            order: 0,
+            is_active,
+            last_accessed_at: None,
+            repo_root: None,
+        };
+        let projects = vec![
+            project("active", true, vec![worktree("a", false), worktree("b", false), worktree("c", true)]),
+            project("hidden", false, vec![worktree("d", false)]),
+        ];
+        let emit: Emit = Arc::new(|_, _| {});
+        let watched_here = || {
+            let mut ids: Vec<String> = flush().into_iter().filter(|id| id.starts_with(&prefix)).collect();
+            ids.sort();
+            ids
+        };
+
+        let started = watch_all_with(&projects, |_| WatchOptions::default(), Arc::clone(&emit));
+        assert_eq!(started.len(), 2);
+        assert_eq!(watched_here(), vec![format!("{}a", prefix), format!("{}b", prefix)]);
+
+        // Starting again doesn't add anything
+        watch_all_with(&projects, |_| WatchOptions::default(), emit);
+        assert_eq!(watched_here().len(), 2);
+
+        for (id, _) in started {
+            stop_watching(&id);
+        }
+        assert!(watched_here().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relocated_worktree_is_watched_at_its_new_path() {
+        let dir = std::env::temp_dir().join(format!("shellflow-watcher-test-{}", uuid::Uuid::new_v4()));
+        let old_path = dir.join("old").join("feature");
+        let new_path = dir.join("new").join("feature");
+        std::fs::create_dir_all(&old_path).unwrap();
+        std::fs::create_dir_all(new_path.parent().unwrap()).unwrap();
+        let id = dir.file_name().unwrap().to_string_lossy().to_string();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let emit: Emit = Arc::new(move |event, _| sink.lock().push(event.to_string()));
+        watch_with(id.clone(), old_path.display().to_string(), WatchOptions::default(), false, Arc::clone(&emit));
+        flush();
+
+        let old = old_path.display().to_string();
+        let moved = relocate_with(&id, &old, WatchOptions::default(), false, Arc::clone(&emit), || {
+            std::fs::rename(&old_path, &new_path).map(|()| new_path.display().to_string())
+        })
+        .unwrap();
+        assert_eq!(moved, new_path.display().to_string());
+
+        let watched: Vec<String> = flush_paths().into_iter().filter(|(w, _)| *w == id).map(|(_, p)| p).collect();
+        assert_eq!(watched, vec![new_path.display().to_string()]);
+
+        // Give the existence check a chance to run against the old path
+        thread::sleep(EXISTENCE_CHECK_INTERVAL + DISPATCH_TICK * 3);
+        assert!(!events.lock().iter().any(|event| event == "worktree-removed"));
+
+        // A failed move watches the original path again
+        let result: Result<String, String> =
+            relocate_with(&id, &moved, WatchOptions::default(), false, emit, || Err("disk full".to_string()));
+        assert!(result.is_err());
+        assert!(flush_paths().contains(&(id.clone(), moved.clone())));
+
+        stop_watching(&id);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_worktrees_get_a_grace_period_and_lost_mounts_are_never_removed() {
+        let dir = std::env::temp_dir().join(format!("shellflow-watcher-test-{}", uuid::Uuid::new_v4()));
+        let mount = dir.join("mount");
+        let path = mount.join("feature");
+        std::fs::create_dir_all(&path).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let emit: Emit = Arc::new(move |event, payload| sink.lock().push((event.to_string(), payload)));
+        let options = WatchOptions {
+            removal_grace: Duration::from_secs(10),
+            ..WatchOptions::default()
+        };
+        let mut worktree = WatchedWorktree::new("wt".to_string(), path.display().to_string(), options, false, emit);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let mut check = |secs| worktree.on_existence(at(secs), Existence::probe(&path));
+
+        // Gone with its parent still there, but back within the grace period
+        std::fs::remove_dir(&path).unwrap();
+        assert_eq!(check(0), TickOutcome::Watching);
+        assert_eq!(check(8), TickOutcome::Watching);
+        std::fs::create_dir(&path).unwrap();
+        assert_eq!(check(9), TickOutcome::Restored);
+        assert_eq!(check(10), TickOutcome::Watching);
+
+        // The whole mount disappears: unavailable for as long as it takes
+        std::fs::remove_dir_all(&mount).unwrap();
+        assert_eq!(check(20), TickOutcome::Watching);
+        assert_eq!(check(500), TickOutcome::Watching);
+
+        // The mount is back without the worktree: removed after the grace period
+        std::fs::create_dir_all(&mount).unwrap();
+        assert_eq!(check(501), TickOutcome::Watching);
+        assert_eq!(check(511), TickOutcome::Removed);
+
+        let events = events.lock();
+        let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["worktree-unavailable", "worktree-available", "worktree-unavailable", "worktree-removed"]
+        );
+        assert_eq!(events[0].1["mount_unavailable"], false);
+        assert_eq!(events[2].1["mount_unavailable"], true);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn events_are_routed_by_path_prefix() {
+        let emit: Emit = Arc::new(|_, _| {});
+        let mut worktree =
+            WatchedWorktree::new("wt".to_string(), "/repo/feature".to_string(), WatchOptions::default(), false, emit);
+        worktree.git_dir = Some(PathBuf::from("/repo/.git/worktrees/feature"));
+
+        assert!(worktree.owns(Path::new("/repo/feature/src/main.rs")));
+        assert!(worktree.owns(Path::new("/repo/.git/worktrees/feature/index")));
+        assert!(!worktree.owns(Path::new("/repo/feature-2/src/main.rs")));
+        assert!(!worktree.owns(Path::new("/repo/.git/index")));
+    }
+
+    /// Wait for a `git-log-changed` event whose newest commit has `subject`
+    fn wait_for_head(events: &Mutex<Vec<serde_json::Value>>, subject: &str) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if events.lock().iter().any(|e| e["commits"][0]["subject"] == subject) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[test]
+    fn hook_sentinel_fires_once_per_signal_from_its_hook() {
+        let dir = std::env::temp_dir().join(format!("shellflow-watcher-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let signal = |hook: &str| {
+            thread::sleep(Duration::from_millis(50));
+            std::fs::write(dir.join(git::HOOK_SENTINEL), format!("{}\n", hook)).unwrap();
+        };
+
+        signal("post-merge");
+        let mut sentinel = HookSentinel::new(&dir, "post-merge");
+        assert!(!sentinel.fired());
+
+        signal("post-merge");
+        assert!(sentinel.fired());
+        assert!(!sentinel.fired());
+
+        // Other hooks' signals are skipped, and don't hide the next one
+        signal("post-rewrite");
+        assert!(!sentinel.fired());
+        signal("post-merge");
+        assert!(sentinel.fired());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn git_log_watcher_emits_when_head_moves() {
+        let dir = init_test_repo();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let emit: Emit = Arc::new(move |event, payload| {
+            assert_eq!(event, "git-log-changed");
+            sink.lock().push(payload);
+        });
+        let id = dir.file_name().unwrap().to_string_lossy().to_string();
+        watch_git_log_with(id.clone(), dir.display().to_string(), emit);
+
+        std::fs::write(dir.join("README.md"), "change\n").unwrap();
+        git_cmd(&dir, &["commit", "-q", "-am", "second"]);
+        assert!(wait_for_head(&events, "second"));
+
+        git_cmd(&dir, &["commit", "-q", "--amend", "-m", "amended"]);
+        assert!(wait_for_head(&events, "amended"));
+
+        git_cmd(&dir, &["reset", "-q", "--hard", "HEAD~1"]);
+        assert!(wait_for_head(&events, "initial"));
+
+        stop_git_log_watcher(&id);
+        assert!(!GIT_LOG_WATCHERS.lock().contains_key(&id));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
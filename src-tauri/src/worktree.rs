@@ -1,9 +1,12 @@
-use crate::config::BaseBranch;
+use crate::config::{BaseBranch, CopyConfig, TransformRule, WorktreeLayout};
 use crate::git;
 use crate::state::{Project, Worktree};
 use crate::template::{expand_template, TemplateContext};
 use log::info;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 use thiserror::Error;
 use uuid::Uuid;
@@ -22,6 +25,10 @@ pub enum WorktreeError {
     NameGenerationFailed(u32),
     #[error("Template error: {0}")]
     Template(String),
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("Invalid worktree name: {0}")]
+    InvalidName(String),
 }
 
 /// Generate a random worktree name using petname (adjective-animal format)
@@ -48,7 +55,7 @@ pub fn generate_unique_worktree_name(repo_path: &Path) -> Result<String, Worktre
 /// Resolve worktree directory with Jinja2 template support.
 ///
 /// # Available Variables
-/// - `repo_directory` - the repository directory
+/// - `repo_directory` - the root of the repository containing the project
 /// - `branch` - the branch name (if provided)
 /// - `worktree_name` - the worktree name (if provided)
 ///
@@ -70,7 +77,7 @@ pub fn resolve_worktree_directory(
     branch: Option<&str>,
     worktree_name: Option<&str>,
 ) -> Result<PathBuf, WorktreeError> {
-    let repo_directory = project_path.to_string_lossy().to_string();
+    let repo_directory = git::repo_root(project_path).to_string_lossy().to_string();
     let template = worktree_directory.unwrap_or("{{ repo_directory }}/.worktrees");
 
     let mut ctx = TemplateContext::new(&repo_directory);
@@ -95,10 +102,43 @@ pub fn resolve_worktree_directory(
     Ok(path)
 }
 
-pub fn create_project(path: &Path) -> Result<Project, WorktreeError> {
-    if !git::is_git_repo(path) {
-        return Err(WorktreeError::NotARepository);
+/// Absolute path a worktree would be created at: the expanded `worktree.directory`
+/// joined with the name's path under `layout`. A relative directory is taken to be
+/// relative to the project.
+pub fn resolve_worktree_path(
+    worktree_directory: Option<&str>,
+    layout: WorktreeLayout,
+    project_path: &Path,
+    name: &NormalizedName,
+) -> Result<PathBuf, WorktreeError> {
+    let base = resolve_worktree_directory(
+        worktree_directory,
+        project_path,
+        Some(&name.branch),
+        Some(&name.directory),
+    )?;
+    let base = if base.is_absolute() { base } else { project_path.join(base) };
+    Ok(base.join(name.relative_path(layout)))
+}
+
+/// Remove the directories a nested-layout worktree was created under (`feature/`
+/// for `feature/foo`) once they're empty. Does nothing for flat-layout paths.
+pub fn remove_empty_layout_dirs(worktree_path: &Path, branch: &str) {
+    let depth = branch.split('/').count();
+    if depth < 2 || !worktree_path.ends_with(branch) {
+        return;
+    }
+    for dir in worktree_path.ancestors().skip(1).take(depth - 1) {
+        if std::fs::remove_dir(dir).is_err() {
+            break;
+        }
     }
+}
+
+pub fn create_project(path: &Path) -> Result<Project, WorktreeError> {
+    let repo_root = git::find_repo_root(path)
+        .filter(|root| git::is_git_repo(root))
+        .ok_or(WorktreeError::NotARepository)?;
 
     Ok(Project {
         id: Uuid::new_v4().to_string(),
@@ -108,6 +148,89 @@ pub fn create_project(path: &Path) -> Result<Project, WorktreeError> {
         order: 0,
         is_active: true,
         last_accessed_at: Some(chrono_lite_now()),
+        repo_root: Some(repo_root.to_string_lossy().to_string()),
+    })
+}
+
+/// A user-supplied worktree name made safe for git and the filesystem
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedName {
+    /// The name as entered (trimmed), shown in the UI
+    pub display: String,
+    /// A valid branch name (per `git check-ref-format`)
+    pub branch: String,
+    /// A single path segment for the worktree directory
+    pub directory: String,
+}
+
+impl NormalizedName {
+    /// Path of the worktree relative to `worktree.directory`
+    pub fn relative_path(&self, layout: WorktreeLayout) -> PathBuf {
+        match layout {
+            WorktreeLayout::Flat => PathBuf::from(&self.directory),
+            WorktreeLayout::Nested => self.branch.split('/').collect(),
+        }
+    }
+}
+
+/// Normalize a worktree name into a branch name and a directory segment.
+/// Whitespace becomes `-`, characters git forbids in refs are dropped, and each
+/// `/`-separated component is trimmed of leading dots/dashes and a `.lock` suffix.
+/// The directory is the branch with `/` replaced by `-`. Empty names, path traversal
+/// and control characters are rejected rather than repaired.
+pub fn normalize_worktree_name(raw: &str) -> Result<NormalizedName, String> {
+    let display = raw.trim();
+    if display.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    if display.chars().any(|c| c.is_control()) {
+        return Err("Name cannot contain control characters".to_string());
+    }
+    if display.split(['/', '\\']).any(|component| component == "." || component == "..") {
+        return Err("Name cannot contain '.' or '..' path components".to_string());
+    }
+
+    let mut cleaned = String::with_capacity(display.len());
+    for c in display.chars() {
+        match c {
+            c if c.is_whitespace() => {
+                if !cleaned.ends_with('-') {
+                    cleaned.push('-');
+                }
+            }
+            '~' | '^' | ':' | '?' | '*' | '[' | '\\' => {}
+            c => cleaned.push(c),
+        }
+    }
+    let cleaned = cleaned.replace("@{", "");
+
+    let components: Vec<String> = cleaned
+        .split('/')
+        .map(|component| {
+            let mut component = component.trim_start_matches(['.', '-']).to_string();
+            while component.contains("..") {
+                component = component.replace("..", ".");
+            }
+            while let Some(stripped) = component.strip_suffix(".lock") {
+                component = stripped.to_string();
+            }
+            component.trim_end_matches(['.', '-']).to_string()
+        })
+        .filter(|component| !component.is_empty())
+        .collect();
+    let branch = components.join("/");
+
+    if branch.is_empty() || branch == "@" {
+        return Err(format!("'{}' has no characters usable in a branch name", display));
+    }
+    if let Some(error) = git::validate_branch_name(&branch) {
+        return Err(error);
+    }
+
+    Ok(NormalizedName {
+        display: display.to_string(),
+        directory: branch.replace('/', "-"),
+        branch,
     })
 }
 
@@ -115,43 +238,59 @@ pub fn create_worktree(
     project: &mut Project,
     name: Option<String>,
     worktree_directory: Option<&str>,
+    layout: WorktreeLayout,
     base_branch: &BaseBranch,
 ) -> Result<Worktree, WorktreeError> {
     let total_start = Instant::now();
     info!("[worktree::create_worktree] Starting...");
 
-    let project_path = Path::new(&project.path);
-    let worktree_name = match name {
-        Some(n) => n,
-        None => generate_unique_worktree_name(project_path)?,
+    let project_path = Path::new(project.repo_path());
+    let normalized = match name {
+        Some(n) => normalize_worktree_name(&n).map_err(WorktreeError::InvalidName)?,
+        None => {
+            let generated = generate_unique_worktree_name(project_path)?;
+            NormalizedName {
+                display: generated.clone(),
+                branch: generated.clone(),
+                directory: generated,
+            }
+        }
     };
-    info!("[worktree::create_worktree] worktree_name: {}", worktree_name);
+    info!("[worktree::create_worktree] worktree_name: {:?}", normalized);
 
     // Create worktree directory using template expansion
-    let worktree_base = resolve_worktree_directory(
-        worktree_directory,
-        project_path,
-        Some(&worktree_name), // branch name is the same as worktree name
-        Some(&worktree_name),
-    )?;
-    let worktree_path = worktree_base.join(&worktree_name);
+    let worktree_path = resolve_worktree_path(worktree_directory, layout, project_path, &normalized)?;
 
     let start = Instant::now();
-    std::fs::create_dir_all(&worktree_base)?;
+    if let Some(worktree_base) = worktree_path.parent() {
+        std::fs::create_dir_all(worktree_base)?;
+    }
     info!("[worktree::create_worktree] create_dir_all took {:?}", start.elapsed());
 
     // Create git worktree
     let start = Instant::now();
-    git::create_worktree(project_path, &worktree_path, &worktree_name, base_branch)?;
+    let source_branch = match git::create_worktree(project_path, &worktree_path, &normalized.branch, base_branch) {
+        Ok(source_branch) => source_branch,
+        Err(e) => {
+            remove_empty_layout_dirs(&worktree_path, &normalized.branch);
+            return Err(e.into());
+        }
+    };
     info!("[worktree::create_worktree] git::create_worktree took {:?}", start.elapsed());
 
     let worktree = Worktree {
         id: Uuid::new_v4().to_string(),
-        name: worktree_name.clone(),
+        name: normalized.display,
         path: worktree_path.to_string_lossy().to_string(),
-        branch: worktree_name,
+        branch: normalized.branch,
+        base_branch: Some(source_branch),
         created_at: chrono_lite_now(),
         order: project.worktrees.len() as i32,
+        command: None,
+        args: Vec::new(),
+        suspended: false,
+        inspect: false,
+        environment: None,
     };
 
     project.worktrees.push(worktree.clone());
@@ -160,94 +299,355 @@ pub fn create_worktree(
     Ok(worktree)
 }
 
-/// Copy gitignored files from the project to the worktree, excluding patterns in `except`
-pub fn copy_gitignored_files(
-    project_path: &Path,
-    worktree_path: &Path,
-    except: &[String],
-) -> Result<(), WorktreeError> {
-    let total_start = Instant::now();
-    info!("[copy_gitignored_files] Starting...");
-    info!("[copy_gitignored_files] except patterns: {:?}", except);
+/// A transform rule with its glob compiled and its replacement values expanded
+struct CompiledTransform {
+    pattern: glob::Pattern,
+    match_file_name: bool,
+    replacements: Vec<(String, String)>,
+}
 
-    let start = Instant::now();
-    let ignored_entries = git::get_ignored_files(project_path)?;
-    info!("[copy_gitignored_files] get_ignored_files took {:?}, found {} entries", start.elapsed(), ignored_entries.len());
+impl CompiledTransform {
+    fn matches(&self, relative_path: &Path) -> bool {
+        self.pattern.matches_path(relative_path)
+            || (self.match_file_name
+                && relative_path
+                    .file_name()
+                    .is_some_and(|name| self.pattern.matches(&name.to_string_lossy())))
+    }
+}
 
-    // Compile glob patterns for exceptions
-    let patterns: Vec<glob::Pattern> = except
+fn compile_transforms(rules: &[TransformRule], ctx: &TemplateContext) -> Vec<CompiledTransform> {
+    rules
         .iter()
-        .filter_map(|p| glob::Pattern::new(p).ok())
-        .collect();
+        .filter_map(|rule| {
+            let pattern = match glob::Pattern::new(&rule.glob) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    info!("[copy_gitignored_files] Ignoring invalid transform glob {:?}: {}", rule.glob, e);
+                    return None;
+                }
+            };
+            let replacements = rule
+                .replace
+                .iter()
+                .map(|(from, to)| {
+                    let to = expand_template(to, ctx).unwrap_or_else(|_| to.clone());
+                    (from.clone(), to)
+                })
+                .collect();
+            Some(CompiledTransform {
+                pattern,
+                match_file_name: !rule.glob.contains('/'),
+                replacements,
+            })
+        })
+        .collect()
+}
 
-    let mut copied_count = 0;
-    let mut skipped_count = 0;
-    let mut copy_time = std::time::Duration::ZERO;
+/// Copy a single file, applying the replacements of every matching transform rule.
+/// The copy is byte-exact (line endings included) unless a replacement actually
+/// applies; files that aren't valid UTF-8 are never transformed.
+fn copy_file(src: &Path, dst: &Path, relative_path: &Path, transforms: &[CompiledTransform]) -> Result<(), std::io::Error> {
+    let matching: Vec<&CompiledTransform> = transforms.iter().filter(|t| t.matches(relative_path)).collect();
+    if matching.is_empty() {
+        std::fs::copy(src, dst)?;
+        return Ok(());
+    }
 
+    let Ok(original) = String::from_utf8(std::fs::read(src)?) else {
+        std::fs::copy(src, dst)?;
+        return Ok(());
+    };
+    let mut content = original.clone();
+    for transform in matching {
+        for (from, to) in &transform.replacements {
+            content = content.replace(from.as_str(), to);
+        }
+    }
+    if content == original {
+        std::fs::copy(src, dst)?;
+        return Ok(());
+    }
+    std::fs::write(dst, content)?;
+    // Keep the source permissions (e.g. executable scripts)
+    std::fs::set_permissions(dst, std::fs::metadata(src)?.permissions())
+}
+
+/// Progress of a gitignored-file copy
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyProgress {
+    pub files_copied: usize,
+    pub total: usize,
+    pub current_path: String,
+}
+
+/// Minimum time between progress reports while copying
+const COPY_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Whether a copy pattern matches a path relative to the project root.
+/// Matches the path itself, anything under it (so ".claude" covers ".claude/foo"),
+/// and - for patterns without a `/` - the entry's name at any depth.
+fn copy_pattern_matches(pattern: &glob::Pattern, relative_path: &str) -> bool {
+    pattern.matches(relative_path)
+        || relative_path == pattern.as_str()
+        || relative_path.starts_with(&format!("{}/", pattern.as_str()))
+        || (!pattern.as_str().contains('/')
+            && relative_path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| pattern.matches(name)))
+}
+
+/// An entry to create in the worktree, relative to the project root
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CopyEntry {
+    Dir(String),
+    File(String),
+}
+
+/// Add `relative_path` and, for a directory, everything below it to `entries`,
+/// skipping anything matching `except` at any depth
+fn walk_copy_entry(
+    project_path: &Path,
+    relative_path: String,
+    except: &[glob::Pattern],
+    cancel: &AtomicBool,
+    entries: &mut Vec<CopyEntry>,
+) -> Result<(), WorktreeError> {
+    if cancel.load(Ordering::SeqCst) {
+        return Err(WorktreeError::Cancelled);
+    }
+    if except.iter().any(|pattern| copy_pattern_matches(pattern, &relative_path)) {
+        return Ok(());
+    }
+
+    let path = project_path.join(&relative_path);
+    if path.is_dir() {
+        entries.push(CopyEntry::Dir(relative_path.clone()));
+        let mut children: Vec<String> = std::fs::read_dir(&path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        children.sort();
+        for child in children {
+            walk_copy_entry(project_path, format!("{}/{}", relative_path, child), except, cancel, entries)?;
+        }
+    } else if path.exists() {
+        entries.push(CopyEntry::File(relative_path));
+    }
+    Ok(())
+}
+
+/// Expand the top-level gitignored entries into every directory and file to copy,
+/// skipping anything matching `except` at any depth. When `include` is non-empty,
+/// only top-level entries matching one of its patterns are copied.
+fn collect_copy_entries(
+    project_path: &Path,
+    ignored_entries: &[String],
+    except: &[glob::Pattern],
+    include: &[glob::Pattern],
+    cancel: &AtomicBool,
+) -> Result<Vec<CopyEntry>, WorktreeError> {
+    let mut entries = Vec::new();
     for entry in ignored_entries {
         // Remove trailing slash if present (directories come with trailing /)
         let file_path = entry.trim_end_matches('/');
+        if !include.is_empty() && !include.iter().any(|pattern| copy_pattern_matches(pattern, file_path)) {
+            continue;
+        }
+        walk_copy_entry(project_path, file_path.to_string(), except, cancel, &mut entries)?;
+    }
+    Ok(entries)
+}
 
-        // Check if this path matches any exception pattern
-        let should_skip = patterns.iter().any(|pattern| {
-            // Match against the file path and also check if it starts with the pattern
-            // (to handle directories like ".claude" matching ".claude/foo")
-            pattern.matches(file_path)
-                || file_path.starts_with(&format!("{}/", pattern.as_str()))
-                || file_path == pattern.as_str()
-        });
-
-        if should_skip {
-            skipped_count += 1;
+/// Expand the paths listed in a copy manifest (one per line, relative to the
+/// project; blank lines and `#` comments ignored) into every directory and file
+/// to copy. Listed paths are copied as-is: `except` and `include` don't apply.
+/// Paths that are missing or point outside the project are skipped with a warning.
+fn collect_manifest_entries(
+    project_path: &Path,
+    manifest: &str,
+    cancel: &AtomicBool,
+) -> Result<Vec<CopyEntry>, WorktreeError> {
+    let mut entries = Vec::new();
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let relative_path = line.trim_start_matches("./").trim_end_matches('/');
+        let escapes = Path::new(relative_path)
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir));
+        if relative_path.is_empty() || escapes {
+            log::warn!("[copy_manifest] Skipping {:?}: not a path inside the project", line);
             continue;
         }
+        if !project_path.join(relative_path).exists() {
+            log::warn!("[copy_manifest] Skipping {:?}: not found in {:?}", relative_path, project_path);
+            continue;
+        }
+        walk_copy_entry(project_path, relative_path.to_string(), &[], cancel, &mut entries)?;
+    }
+    Ok(entries)
+}
 
-        let src = project_path.join(file_path);
-        let dst = worktree_path.join(file_path);
+/// Copy gitignored files from the project to the worktree, honoring the `except`,
+/// `include` and `transform` settings of `copy`, plus the paths listed in the
+/// `copy.manifest` file if there is one. Transform values are expanded with `ctx`.
+/// Progress is reported through `on_progress` at most every 100ms (plus once at the end).
+/// Setting `cancel` stops the copy between files with `WorktreeError::Cancelled`.
+pub fn copy_gitignored_files(
+    project_path: &Path,
+    worktree_path: &Path,
+    copy: &CopyConfig,
+    ctx: &TemplateContext,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(&CopyProgress),
+) -> Result<(), WorktreeError> {
+    let total_start = Instant::now();
+    info!("[copy_gitignored_files] Starting...");
+    info!("[copy_gitignored_files] except patterns: {:?}, include patterns: {:?}", copy.except, copy.include);
 
-        // Skip if source doesn't exist (shouldn't happen, but be safe)
-        if !src.exists() {
-            continue;
+    let ignored_entries = if copy.gitignored {
+        let start = Instant::now();
+        let ignored_entries = git::get_ignored_files(project_path)?;
+        info!("[copy_gitignored_files] get_ignored_files took {:?}, found {} entries", start.elapsed(), ignored_entries.len());
+        ignored_entries
+    } else {
+        Vec::new()
+    };
+
+    // Compile glob patterns
+    let compile = |patterns: &[String]| -> Vec<glob::Pattern> {
+        patterns.iter().filter_map(|p| glob::Pattern::new(p).ok()).collect()
+    };
+    let except = compile(&copy.except);
+    let include = compile(&copy.include);
+    let transforms = compile_transforms(&copy.transform, ctx);
+
+    let start = Instant::now();
+    let mut entries = collect_copy_entries(project_path, &ignored_entries, &except, &include, cancel)?;
+    if let Some(manifest) = &copy.manifest {
+        match std::fs::read_to_string(project_path.join(manifest)) {
+            Ok(content) => {
+                let mut seen: HashSet<CopyEntry> = entries.iter().cloned().collect();
+                for entry in collect_manifest_entries(project_path, &content, cancel)? {
+                    if seen.insert(entry.clone()) {
+                        entries.push(entry);
+                    }
+                }
+            }
+            Err(e) => info!("[copy_gitignored_files] No copy manifest at {:?}: {}", manifest, e),
         }
+    }
+    let total = entries.iter().filter(|e| matches!(e, CopyEntry::File(_))).count();
+    info!("[copy_gitignored_files] walk took {:?}, found {} files", start.elapsed(), total);
 
-        // Create parent directories if needed
-        if let Some(parent) = dst.parent() {
-            std::fs::create_dir_all(parent)?;
+    let mut progress = CopyProgress {
+        files_copied: 0,
+        total,
+        current_path: String::new(),
+    };
+    let mut last_report = Instant::now();
+    on_progress(&progress);
+
+    for entry in entries {
+        if cancel.load(Ordering::SeqCst) {
+            info!("[copy_gitignored_files] Cancelled after {} of {} files", progress.files_copied, total);
+            return Err(WorktreeError::Cancelled);
         }
 
-        // Copy file or directory
-        let start = Instant::now();
-        if src.is_dir() {
-            copy_dir_recursive(&src, &dst)?;
-        } else {
-            std::fs::copy(&src, &dst)?;
+        match entry {
+            CopyEntry::Dir(relative_path) => {
+                std::fs::create_dir_all(worktree_path.join(&relative_path))?;
+            }
+            CopyEntry::File(relative_path) => {
+                let dst = worktree_path.join(&relative_path);
+                // Create parent directories if needed
+                if let Some(parent) = dst.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                copy_file(&project_path.join(&relative_path), &dst, Path::new(&relative_path), &transforms)?;
+
+                progress.files_copied += 1;
+                progress.current_path = relative_path;
+                if last_report.elapsed() >= COPY_PROGRESS_INTERVAL {
+                    on_progress(&progress);
+                    last_report = Instant::now();
+                }
+            }
         }
-        copy_time += start.elapsed();
-        copied_count += 1;
     }
+    on_progress(&progress);
 
-    info!("[copy_gitignored_files] Copied {} entries, skipped {} entries", copied_count, skipped_count);
-    info!("[copy_gitignored_files] Total copy time: {:?}", copy_time);
+    info!("[copy_gitignored_files] Copied {} files", progress.files_copied);
     info!("[copy_gitignored_files] TOTAL took {:?}", total_start.elapsed());
     Ok(())
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
-    std::fs::create_dir_all(dst)?;
+/// Disk space taken by a worktree's checkout
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeUsage {
+    pub worktree_id: String,
+    pub bytes: u64,
+    pub file_count: u64,
+    /// Directories that couldn't be read (e.g. permission denied) and weren't counted
+    pub skipped_dirs: u64,
+    /// False for the partial totals reported while the walk is still running
+    pub complete: bool,
+}
+
+/// Minimum time between partial reports while measuring disk usage
+const USAGE_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
 
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
+/// Measure the files under a worktree, reporting partial totals through `on_progress`
+/// at most every 250ms. The top-level `.git` entry is skipped so the object store
+/// shared with the main checkout isn't counted, and symlinks aren't followed.
+/// Unreadable directories are counted in `skipped_dirs` rather than failing the walk.
+pub fn worktree_disk_usage(
+    worktree_id: &str,
+    worktree_path: &Path,
+    on_progress: &mut dyn FnMut(&WorktreeUsage),
+) -> WorktreeUsage {
+    let mut usage = WorktreeUsage {
+        worktree_id: worktree_id.to_string(),
+        ..WorktreeUsage::default()
+    };
+    let mut last_report = Instant::now();
+    let mut pending = vec![worktree_path.to_path_buf()];
 
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            std::fs::copy(&src_path, &dst_path)?;
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::debug!("[worktree_disk_usage] Skipping {:?}: {}", dir, e);
+                usage.skipped_dirs += 1;
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            if dir == worktree_path && entry.file_name() == ".git" {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                usage.bytes += metadata.len();
+                usage.file_count += 1;
+            }
+        }
+        if last_report.elapsed() >= USAGE_PROGRESS_INTERVAL {
+            on_progress(&usage);
+            last_report = Instant::now();
         }
     }
 
-    Ok(())
+    usage.complete = true;
+    on_progress(&usage);
+    usage
 }
 
 pub fn delete_worktree(project: &mut Project, worktree_id: &str) -> Result<(), WorktreeError> {
@@ -260,28 +660,93 @@ pub fn delete_worktree(project: &mut Project, worktree_id: &str) -> Result<(), W
     let worktree = &project.worktrees[worktree_idx];
 
     // Delete worktree
-    let project_path = Path::new(&project.path);
-    git::delete_worktree(project_path, &worktree.name)?;
+    let project_path = Path::new(project.repo_path());
+    let worktree_path = Path::new(&worktree.path);
+    git::delete_worktree(project_path, worktree_path)?;
 
     // Remove worktree directory if it still exists
-    let worktree_path = Path::new(&worktree.path);
     if worktree_path.exists() {
         std::fs::remove_dir_all(worktree_path)?;
     }
+    remove_empty_layout_dirs(worktree_path, &worktree.branch);
 
     project.worktrees.remove(worktree_idx);
 
     Ok(())
 }
 
+/// Add the project's linked worktrees that were created outside Shellflow
+/// (e.g. with `git worktree add`), returning the ones added. The main working
+/// tree, bare entries and worktrees whose folder is gone are skipped; a
+/// detached worktree gets its short commit hash as its branch. The creation
+/// time is taken from git's record of the worktree, falling back to now.
+pub fn import_existing_worktrees(project: &mut Project) -> Result<Vec<Worktree>, WorktreeError> {
+    let repo_path = PathBuf::from(project.repo_path());
+    let known: Vec<PathBuf> = std::iter::once(project.repo_path())
+        .chain(project.worktrees.iter().map(|w| w.path.as_str()))
+        .map(|path| Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path)))
+        .collect();
+
+    let mut imported = Vec::new();
+    // The first entry is the main working tree
+    for entry in git::list_worktrees(&repo_path, &git::GitLimits::default())?.into_iter().skip(1) {
+        let path = PathBuf::from(&entry.path);
+        if entry.is_bare || entry.prunable || !path.is_dir() {
+            continue;
+        }
+        if known.contains(&path.canonicalize().unwrap_or_else(|_| path.clone())) {
+            continue;
+        }
+        let Some(branch) = entry
+            .branch
+            .clone()
+            .or_else(|| entry.head.as_deref().map(|head| head.chars().take(7).collect()))
+        else {
+            continue;
+        };
+
+        let worktree = Worktree {
+            id: Uuid::new_v4().to_string(),
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| branch.clone()),
+            path: entry.path.clone(),
+            base_branch: git::infer_base_branch(&repo_path, &path),
+            branch,
+            created_at: worktree_created_at(&path).unwrap_or_else(chrono_lite_now),
+            order: project.worktrees.len() as i32,
+            command: None,
+            args: Vec::new(),
+            suspended: false,
+            inspect: false,
+            environment: None,
+        };
+        info!("[worktree::import_existing_worktrees] Importing {} ({})", worktree.path, worktree.branch);
+        project.worktrees.push(worktree.clone());
+        imported.push(worktree);
+    }
+    Ok(imported)
+}
+
+/// When git created a linked worktree, from its administrative directory
+fn worktree_created_at(worktree_path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(git::resolve_git_dir(worktree_path).ok()?).ok()?;
+    let created = metadata.created().or_else(|_| metadata.modified()).ok()?;
+    let secs = created.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    Some(format_timestamp(secs))
+}
+
 // Simple timestamp without external chrono dependency
 pub fn chrono_lite_now() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let duration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default();
-    let secs = duration.as_secs();
+    format_timestamp(duration.as_secs())
+}
 
+fn format_timestamp(secs: u64) -> String {
     // Convert to ISO-8601-ish format (simplified)
     let days_since_1970 = secs / 86400;
     let years = 1970 + days_since_1970 / 365;
@@ -301,8 +766,230 @@ pub fn chrono_lite_now() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{git_cmd, init_test_repo};
     use std::path::PathBuf;
 
+    #[test]
+    fn copy_file_applies_matching_transforms_only_to_text() {
+        let dir = std::env::temp_dir().join(format!("shellflow-copy-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("dst")).unwrap();
+        std::fs::write(dir.join("src/.env"), "PORT=3000\nNAME=app\n").unwrap();
+        std::fs::write(dir.join("src/other.txt"), "PORT=3000\n").unwrap();
+        std::fs::write(dir.join("src/blob.env"), [0xff, 0xfe, b'P', b'O', b'R', b'T']).unwrap();
+
+        let rules = vec![
+            TransformRule {
+                glob: "*.env".to_string(),
+                replace: [("PORT=3000".to_string(), "PORT={{ worktree_name }}-port".to_string())].into(),
+            },
+            TransformRule {
+                glob: ".env".to_string(),
+                replace: [("NAME=app".to_string(), "NAME={{ branch }}".to_string())].into(),
+            },
+        ];
+        let ctx = TemplateContext::new("/repo").with_branch("feature/x").with_worktree_name("feature-x");
+        let transforms = compile_transforms(&rules, &ctx);
+
+        for name in [".env", "other.txt", "blob.env"] {
+            copy_file(&dir.join("src").join(name), &dir.join("dst").join(name), Path::new(name), &transforms).unwrap();
+        }
+
+        assert_eq!(
+            std::fs::read_to_string(dir.join("dst/.env")).unwrap(),
+            "PORT=feature-x-port\nNAME=feature/x\n"
+        );
+        assert_eq!(std::fs::read_to_string(dir.join("dst/other.txt")).unwrap(), "PORT=3000\n");
+        assert_eq!(std::fs::read(dir.join("dst/blob.env")).unwrap(), [0xff, 0xfe, b'P', b'O', b'R', b'T']);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn copy_file_round_trips_crlf_and_invalid_utf8() {
+        let dir = std::env::temp_dir().join(format!("shellflow-copy-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("dst")).unwrap();
+        let crlf = b"KEY=value\r\nOTHER=1\r\n".to_vec();
+        let binary = vec![b'K', 0xc3, 0x28, b'\r', b'\n', 0x00, 0xff];
+        std::fs::write(dir.join("src/crlf.env"), &crlf).unwrap();
+        std::fs::write(dir.join("src/binary.env"), &binary).unwrap();
+
+        // A matching rule whose replacement doesn't occur, and no rules at all
+        let rules = vec![TransformRule {
+            glob: "*.env".to_string(),
+            replace: [("MISSING".to_string(), "x".to_string())].into(),
+        }];
+        let transforms = compile_transforms(&rules, &TemplateContext::new("/repo"));
+        for transforms in [&transforms[..], &[]] {
+            for name in ["crlf.env", "binary.env"] {
+                copy_file(&dir.join("src").join(name), &dir.join("dst").join(name), Path::new(name), transforms).unwrap();
+            }
+            assert_eq!(std::fs::read(dir.join("dst/crlf.env")).unwrap(), crlf);
+            assert_eq!(std::fs::read(dir.join("dst/binary.env")).unwrap(), binary);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn collect_copy_entries_honors_except_during_walk() {
+        let dir = std::env::temp_dir().join(format!("shellflow-copy-test-{}", Uuid::new_v4()));
+        for path in ["node_modules/a/.cache", "apps/web/.cache", "apps/web/dist"] {
+            std::fs::create_dir_all(dir.join(path)).unwrap();
+        }
+        for path in ["node_modules/a/index.js", "node_modules/a/.cache/x", "apps/web/.cache/y", "apps/web/dist/app.js", ".env", ".env.local"] {
+            std::fs::write(dir.join(path), "x").unwrap();
+        }
+
+        let ignored = vec!["node_modules/".to_string(), "apps/web/".to_string(), ".env".to_string(), ".env.local".to_string()];
+        let except = vec![glob::Pattern::new(".cache").unwrap(), glob::Pattern::new("apps/web/dist").unwrap()];
+        let files = |entries: Vec<CopyEntry>| -> Vec<String> {
+            entries
+                .into_iter()
+                .filter_map(|e| match e {
+                    CopyEntry::File(path) => Some(path),
+                    CopyEntry::Dir(_) => None,
+                })
+                .collect()
+        };
+
+        let entries = collect_copy_entries(&dir, &ignored, &except, &[], &AtomicBool::new(false)).unwrap();
+        assert_eq!(files(entries), vec!["node_modules/a/index.js", ".env", ".env.local"]);
+
+        let include = vec![glob::Pattern::new(".env*").unwrap()];
+        let entries = collect_copy_entries(&dir, &ignored, &except, &include, &AtomicBool::new(false)).unwrap();
+        assert_eq!(files(entries), vec![".env", ".env.local"]);
+
+        assert!(matches!(
+            collect_copy_entries(&dir, &ignored, &except, &[], &AtomicBool::new(true)),
+            Err(WorktreeError::Cancelled)
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn collect_manifest_entries_copies_listed_paths_and_skips_missing() {
+        let dir = std::env::temp_dir().join(format!("shellflow-copy-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("config/local")).unwrap();
+        for path in [".env", "config/local/a.json", "config/local/b.json", "notes.txt"] {
+            std::fs::write(dir.join(path), "x").unwrap();
+        }
+
+        let manifest = "# local secrets\n.env\n\n./config/local/\nmissing.txt\n../outside\n/etc/passwd\n";
+        let entries = collect_manifest_entries(&dir, manifest, &AtomicBool::new(false)).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                CopyEntry::File(".env".to_string()),
+                CopyEntry::Dir("config/local".to_string()),
+                CopyEntry::File("config/local/a.json".to_string()),
+                CopyEntry::File("config/local/b.json".to_string()),
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn copy_uses_manifest_without_gitignored() {
+        let dir = std::env::temp_dir().join(format!("shellflow-copy-test-{}", Uuid::new_v4()));
+        let project = dir.join("project");
+        let worktree = dir.join("worktree");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(project.join(".shellflow-copy"), ".env\nmissing.txt\n").unwrap();
+        std::fs::write(project.join(".env"), "SECRET=1\n").unwrap();
+        std::fs::write(project.join("other.txt"), "x").unwrap();
+
+        let copy = CopyConfig {
+            manifest: Some(".shellflow-copy".to_string()),
+            ..CopyConfig::default()
+        };
+        assert!(copy.is_enabled());
+        copy_gitignored_files(&project, &worktree, &copy, &TemplateContext::new("/repo"), &AtomicBool::new(false), &mut |_| {})
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(worktree.join(".env")).unwrap(), "SECRET=1\n");
+        assert!(!worktree.join("other.txt").exists());
+        assert!(!worktree.join("missing.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn worktree_disk_usage_skips_git_dir_and_unreadable_dirs() {
+        let dir = std::env::temp_dir().join(format!("shellflow-usage-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(".git/objects")).unwrap();
+        std::fs::create_dir_all(dir.join("node_modules/pkg")).unwrap();
+        std::fs::write(dir.join(".git/objects/blob"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+        std::fs::write(dir.join("node_modules/pkg/index.js"), vec![b'x'; 100]).unwrap();
+
+        let mut reports = Vec::new();
+        let usage = worktree_disk_usage("wt", &dir, &mut |usage| reports.push(usage.clone()));
+        assert_eq!(usage.bytes, 106);
+        assert_eq!(usage.file_count, 2);
+        assert_eq!(usage.skipped_dirs, 0);
+        assert!(usage.complete);
+        assert_eq!(reports.last(), Some(&usage));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let locked = dir.join("node_modules/pkg");
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+            // Permissions don't stop root, so only check when they apply
+            if std::fs::read_dir(&locked).is_err() {
+                let usage = worktree_disk_usage("wt", &dir, &mut |_| {});
+                assert_eq!((usage.bytes, usage.file_count, usage.skipped_dirs), (6, 1, 1));
+            }
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn normalize_worktree_name_handles_slashes_and_spaces() {
+        let name = normalize_worktree_name("  feature/foo bar  ").unwrap();
+        assert_eq!(name.display, "feature/foo bar");
+        assert_eq!(name.branch, "feature/foo-bar");
+        assert_eq!(name.directory, "feature-foo-bar");
+
+        let name = normalize_worktree_name("a//b/").unwrap();
+        assert_eq!(name.branch, "a/b");
+        assert_eq!(name.directory, "a-b");
+    }
+
+    #[test]
+    fn normalize_worktree_name_handles_dots() {
+        let name = normalize_worktree_name(".hidden/v1..2.lock").unwrap();
+        assert_eq!(name.branch, "hidden/v1.2");
+
+        assert!(normalize_worktree_name("..").is_err());
+        assert!(normalize_worktree_name("../escape").is_err());
+        assert!(normalize_worktree_name("a/./b").is_err());
+        assert!(normalize_worktree_name("...").is_err());
+    }
+
+    #[test]
+    fn normalize_worktree_name_rejects_empty_and_control_chars() {
+        assert!(normalize_worktree_name("").is_err());
+        assert!(normalize_worktree_name("   ").is_err());
+        assert!(normalize_worktree_name("bad\u{7}name").is_err());
+        assert!(normalize_worktree_name("~^:?*").is_err());
+    }
+
+    #[test]
+    fn normalize_worktree_name_keeps_unicode() {
+        let name = normalize_worktree_name("café über").unwrap();
+        assert_eq!(name.display, "café über");
+        assert_eq!(name.branch, "café-über");
+        assert_eq!(name.directory, "café-über");
+    }
+
     #[test]
     fn test_generate_worktree_name_format() {
         let name = generate_worktree_name();
@@ -424,6 +1111,68 @@ mod tests {
         assert!((10000..20000).contains(&port));
     }
 
+    #[test]
+    fn test_resolve_worktree_path_appends_normalized_directory() {
+        let project_path = PathBuf::from("/home/user/myproject");
+        let name = normalize_worktree_name("feature/login page").unwrap();
+        let result = resolve_worktree_path(
+            Some("{{ repo_directory }}/.worktrees/{{ branch | sanitize }}"),
+            WorktreeLayout::Flat,
+            &project_path,
+            &name,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            PathBuf::from("/home/user/myproject/.worktrees/feature-login-page/feature-login-page")
+        );
+    }
+
+    #[test]
+    fn test_resolve_worktree_path_relative_directory() {
+        let project_path = PathBuf::from("/home/user/myproject");
+        let name = normalize_worktree_name("happy-dolphin").unwrap();
+        let result = resolve_worktree_path(Some("../trees"), WorktreeLayout::Flat, &project_path, &name).unwrap();
+        assert_eq!(result, PathBuf::from("/home/user/myproject/../trees/happy-dolphin"));
+    }
+
+    #[test]
+    fn test_resolve_worktree_path_layouts() {
+        let project_path = PathBuf::from("/home/user/myproject");
+        let name = normalize_worktree_name("feature/foo").unwrap();
+
+        let flat = resolve_worktree_path(None, WorktreeLayout::Flat, &project_path, &name).unwrap();
+        assert_eq!(flat, PathBuf::from("/home/user/myproject/.worktrees/feature-foo"));
+
+        let nested = resolve_worktree_path(None, WorktreeLayout::Nested, &project_path, &name).unwrap();
+        assert_eq!(nested, PathBuf::from("/home/user/myproject/.worktrees/feature/foo"));
+    }
+
+    #[test]
+    fn test_remove_empty_layout_dirs() {
+        let dir = std::env::temp_dir().join(format!("shellflow-layout-test-{}", Uuid::new_v4()));
+        let base = dir.join(".worktrees");
+        std::fs::create_dir_all(base.join("feature/foo")).unwrap();
+        std::fs::create_dir_all(base.join("feature/bar")).unwrap();
+
+        // A sibling keeps feature/ alive
+        std::fs::remove_dir(base.join("feature/foo")).unwrap();
+        remove_empty_layout_dirs(&base.join("feature/foo"), "feature/foo");
+        assert!(base.join("feature").is_dir());
+
+        std::fs::remove_dir(base.join("feature/bar")).unwrap();
+        remove_empty_layout_dirs(&base.join("feature/bar"), "feature/bar");
+        assert!(!base.join("feature").exists());
+        assert!(base.is_dir());
+
+        // Flat paths don't end with the branch, so nothing above them is touched
+        std::fs::create_dir_all(base.join("empty")).unwrap();
+        remove_empty_layout_dirs(&base.join("empty/feature-baz"), "feature/baz");
+        assert!(base.join("empty").is_dir());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_chrono_lite_now_format() {
         let timestamp = chrono_lite_now();
@@ -442,4 +1191,65 @@ mod tests {
         assert!(date_parts[1].parse::<u32>().is_ok(), "Month should be numeric");
         assert!(date_parts[2].parse::<u32>().is_ok(), "Day should be numeric");
     }
+
+    #[test]
+    fn create_worktree_records_base_branch() {
+        let dir = init_test_repo();
+        git_cmd(&dir, &["branch", "develop"]);
+
+        let mut project = create_project(&dir).unwrap();
+        let worktrees_dir = dir.with_extension("worktrees");
+        let worktrees_dir = worktrees_dir.to_str().unwrap();
+
+        let auto = create_worktree(&mut project, Some("a".to_string()), Some(worktrees_dir), WorktreeLayout::Flat, &BaseBranch::default()).unwrap();
+        assert_eq!(auto.base_branch.as_deref(), Some("main"));
+
+        let named = BaseBranch::Named { name: "develop".to_string() };
+        let develop = create_worktree(&mut project, Some("b".to_string()), Some(worktrees_dir), WorktreeLayout::Flat, &named).unwrap();
+        assert_eq!(develop.base_branch.as_deref(), Some("develop"));
+        assert_eq!(project.worktrees[1].base_branch.as_deref(), Some("develop"));
+
+        // Older worktrees get the default branch inferred
+        assert_eq!(git::infer_base_branch(&dir, Path::new(&develop.path)).as_deref(), Some("main"));
+        assert_eq!(git::infer_base_branch(&dir, &dir), None);
+
+        let _ = std::fs::remove_dir_all(worktrees_dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn imports_worktrees_added_outside_the_app() {
+        let dir = init_test_repo();
+
+        let mut project = create_project(&dir).unwrap();
+        let worktrees_dir = dir.with_extension("worktrees");
+        let known = create_worktree(
+            &mut project,
+            Some("known".to_string()),
+            Some(worktrees_dir.to_str().unwrap()),
+            WorktreeLayout::Flat,
+            &BaseBranch::default(),
+        )
+        .unwrap();
+
+        let manual = worktrees_dir.join("manual");
+        let detached = worktrees_dir.join("detached");
+        git_cmd(&dir, &["worktree", "add", "-q", "-b", "manual-branch", manual.to_str().unwrap()]);
+        git_cmd(&dir, &["worktree", "add", "-q", "--detach", detached.to_str().unwrap()]);
+
+        let imported = import_existing_worktrees(&mut project).unwrap();
+        let summary: Vec<(&str, &str)> = imported.iter().map(|w| (w.name.as_str(), w.branch.as_str())).collect();
+        assert_eq!(summary.len(), 2);
+        assert!(summary.contains(&("manual", "manual-branch")));
+        let detached_entry = imported.iter().find(|w| w.name == "detached").unwrap();
+        assert_eq!(detached_entry.branch.len(), 7);
+        assert!(imported.iter().all(|w| w.created_at.contains('T') && w.id != known.id));
+        assert_eq!(project.worktrees.len(), 3);
+
+        // Already known worktrees aren't imported twice
+        assert!(import_existing_worktrees(&mut project).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&worktrees_dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
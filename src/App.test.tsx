@@ -334,6 +334,50 @@ describe('App', () => {
     });
   });
 
+  describe('Orphan Cleanup', () => {
+    const orphans = [{ pid: 4242, appPid: 1, name: 'vim' }];
+
+    it('closes the prompt when cleanup finishes without an answer', async () => {
+      render(<App />, { wrapper: TestWrapper });
+
+      await waitFor(() => {
+        expect(screen.getByText('Terminal 1')).toBeInTheDocument();
+      });
+
+      await act(async () => {
+        emitEvent('orphans-found', orphans);
+      });
+      expect(screen.getByText('Stop All')).toBeInTheDocument();
+
+      await act(async () => {
+        emitEvent('orphans-resolved', null);
+      });
+
+      await waitFor(() => {
+        expect(screen.queryByText('Stop All')).not.toBeInTheDocument();
+      });
+      expect(screen.getByText(/all leftover processes were stopped/)).toBeInTheDocument();
+    });
+
+    it('warns when the answer arrives after the timeout', async () => {
+      mockInvokeResponses.set('confirm_orphan_cleanup', false);
+      render(<App />, { wrapper: TestWrapper });
+
+      await waitFor(() => {
+        expect(screen.getByText('Terminal 1')).toBeInTheDocument();
+      });
+
+      await act(async () => {
+        emitEvent('orphans-found', orphans);
+      });
+      await userEvent.click(screen.getByText('Stop All'));
+
+      await waitFor(() => {
+        expect(screen.getByText(/already timed out/)).toBeInTheDocument();
+      });
+    });
+  });
+
   describe('Multiple Projects', () => {
     it('displays multiple projects', async () => {
       const projects = [
@@ -14,6 +14,7 @@ import { DeleteWorktreeModal } from './components/DeleteWorktreeModal';
 import { ConfirmModal } from './components/ConfirmModal';
 import { MergeModal } from './components/MergeModal';
 import { StashModal } from './components/StashModal';
+import { OrphanCleanupModal } from './components/OrphanCleanupModal';
 import { ShutdownScreen } from './components/ShutdownScreen';
 import { TaskSwitcher } from './components/TaskSwitcher/TaskSwitcher';
 import { CommandPalette } from './components/CommandPalette';
@@ -28,7 +29,7 @@ import { useDrawerTabs } from './hooks/useDrawerTabs';
 import { useSessionTabs, SessionTab } from './hooks/useSessionTabs';
 import { useSplitActions } from './contexts/SplitContext';
 import { log } from './lib/log';
-import { selectFolder, shutdown, ptyKill, ptyForceKill, stashChanges, stashPop, reorderProjects, reorderWorktrees, expandActionPrompt, ActionPromptContext, updateActionAvailability, touchProject } from './lib/tauri';
+import { selectFolder, shutdown, ptyKill, ptyForceKill, stashChanges, stashPop, reorderProjects, reorderWorktrees, expandActionPrompt, ActionPromptContext, updateActionAvailability, touchProject, confirmOrphanCleanup, pendingOrphans, OrphanInfo } from './lib/tauri';
 import { openUrl } from '@tauri-apps/plugin-opener';
 import { ActionContext, ActionId, getMenuAvailability } from './lib/actions';
 import { useActions, ActionHandlers } from './hooks/useActions';
@@ -133,7 +134,7 @@ function App() {
   const { resolveKeyEvent, getShortcut } = useMappings();
 
   // Toast notifications
-  const { toasts, dismissToast, showError } = useToast();
+  const { toasts, dismissToast, showError, showWarning } = useToast();
 
   // Open worktrees (main terminals are kept alive for these)
   const [openWorktreeIds, setOpenWorktreeIds] = useState<Set<string>>(new Set());
@@ -347,6 +348,10 @@ function App() {
   const [pendingMergeId, setPendingMergeId] = useState<string | null>(null);
   const [pendingStashProject, setPendingStashProject] = useState<Project | null>(null);
   const [pendingCloseProject, setPendingCloseProject] = useState<Project | null>(null);
+  // Processes left by a crashed instance, awaiting a choice (`cleanup.confirm`)
+  const [orphans, setOrphans] = useState<OrphanInfo[] | null>(null);
+  const orphansRef = useRef(orphans);
+  orphansRef.current = orphans;
   const [isTaskSwitcherOpen, setIsTaskSwitcherOpen] = useState(false);
   const [isCommandPaletteOpen, setIsCommandPaletteOpen] = useState(false);
   const [isProjectSwitcherOpen, setIsProjectSwitcherOpen] = useState(false);
@@ -1953,7 +1958,8 @@ function App() {
       setExpandedProjects((prev) => new Set([...prev, projectId]));
 
       try {
-        const worktree = await createWorktree(project.path);
+        // Chosen up front so the creation can be cancelled while it's still running
+        const worktree = await createWorktree(project.path, undefined, crypto.randomUUID());
         setLoadingWorktrees((prev) => new Set([...prev, worktree.id]));
         setOpenWorktreeIds((prev) => new Set([...prev, worktree.id]));
         setActiveWorktreeId(worktree.id);
@@ -1980,6 +1986,22 @@ function App() {
     [projects, createWorktree, config.worktree.focusNewBranchNames, showError]
   );
 
+  // Open worktrees created from the command line (`shellflow --new-worktree <name>`)
+  useEffect(() => {
+    const unlisten = listen<{ projectId: string; worktree: Worktree }>('ipc-worktree-created', async (event) => {
+      const { projectId, worktree } = event.payload;
+      await refreshProjects();
+      setExpandedProjects((prev) => new Set([...prev, projectId]));
+      setOpenWorktreeIds((prev) => new Set([...prev, worktree.id]));
+      setActiveWorktreeId(worktree.id);
+      setActiveScratchId(null);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [refreshProjects]);
+
   const handleStashAndCreate = useCallback(async () => {
     if (!pendingStashProject) return;
 
@@ -1993,7 +2015,7 @@ function App() {
       stashId = await stashChanges(project.path);
 
       // Create the worktree
-      const worktree = await createWorktree(project.path);
+      const worktree = await createWorktree(project.path, undefined, crypto.randomUUID());
       setActiveScratchId(null);
 
       // Pop the stash to restore changes
@@ -2544,6 +2566,41 @@ function App() {
     [activeWorktreeId, openWorktreeIds, refreshProjects]
   );
 
+  // Ask which leftover processes to stop. The prompt may have gone out before
+  // this listener existed, so check for one already pending too.
+  useEffect(() => {
+    const unlisten = listen<OrphanInfo[]>('orphans-found', (event) => {
+      setOrphans(event.payload);
+    });
+    // Cleanup is done; if the prompt is still open, nobody answered in time
+    const unlistenResolved = listen('orphans-resolved', () => {
+      if (!orphansRef.current) return;
+      setOrphans(null);
+      showWarning('No answer within a minute, so all leftover processes were stopped');
+    });
+    pendingOrphans()
+      .then((pending) => {
+        if (pending.length > 0) setOrphans(pending);
+      })
+      .catch(console.error);
+
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenResolved.then((fn) => fn());
+    };
+  }, [showWarning]);
+
+  const handleConfirmOrphans = useCallback((pids: number[]) => {
+    setOrphans(null);
+    confirmOrphanCleanup(pids)
+      .then((answered) => {
+        if (!answered) {
+          showWarning('The prompt had already timed out, so all leftover processes were stopped');
+        }
+      })
+      .catch(console.error);
+  }, [showWarning]);
+
   // Listen for merge-completed events (from action terminal's "Complete" button)
   useEffect(() => {
     const unlistenMerge = listen<MergeCompleted>('merge-completed', (event) => {
@@ -3270,7 +3327,7 @@ function App() {
     : null;
 
   return (
-    <ThemeProvider themeConfig={config.theme} borderStyle={effectiveBorderStyle}>
+    <ThemeProvider themeConfig={config.theme} borderStyle={effectiveBorderStyle} projectPath={activeProjectPath}>
     <div className="h-screen w-screen overflow-hidden flex flex-col">
       {/* Shutdown screen overlay */}
       <ShutdownScreen isVisible={isShuttingDown} />
@@ -3396,6 +3453,15 @@ function App() {
         />
       )}
 
+      {orphans && (
+        <OrphanCleanupModal
+          orphans={orphans}
+          onConfirm={handleConfirmOrphans}
+          onModalOpen={onModalOpen}
+          onModalClose={onModalClose}
+        />
+      )}
+
       {isThemeSwitcherOpen && (
         <ThemeSwitcher
           onClose={() => setIsThemeSwitcherOpen(false)}
@@ -3655,6 +3721,11 @@ function App() {
                               activeFocusState === 'drawer'
                             }
                                                         terminalConfig={drawerTerminalConfig}
+                            lazySpawn={
+                              config.terminal.lazySpawn &&
+                              !tab.directory &&
+                              !scratchTerminals.some((s) => s.id === entityId)
+                            }
                             onClose={() => handleCloseDrawerTab(tab.id, entityId)}
                             onFocus={() => handleDrawerFocused(entityId)}
                             onPtyIdReady={(ptyId) => handleDrawerPtyIdReady(tab.id, ptyId)}
@@ -87,6 +87,48 @@ describe('DrawerTerminal', () => {
         expect(onPtyIdReady).toHaveBeenCalledWith('pty-shell-123');
       });
     });
+
+    it('defers a lazy shell until the tab is first active', async () => {
+      mockInvokeResponses.set('spawn_terminal', 'pty-pane-123');
+      const { rerender } = render(<DrawerTerminal {...defaultProps} isActive={false} lazySpawn />);
+
+      await act(async () => {
+        await new Promise((resolve) => setTimeout(resolve, 50));
+      });
+      expect(invokeHistory.find((h) => h.command === 'spawn_terminal')).toBeUndefined();
+
+      rerender(<DrawerTerminal {...defaultProps} isActive lazySpawn />);
+
+      await waitFor(() => {
+        expect(invokeHistory.find((h) => h.command === 'spawn_terminal')).toBeDefined();
+      });
+    });
+
+    it('spawns a deferred lazy shell as the entity pane', async () => {
+      mockInvokeResponses.set('spawn_terminal', 'pty-pane-123');
+      const onPtyIdReady = vi.fn();
+      const { rerender } = render(
+        <DrawerTerminal {...defaultProps} isActive={false} lazySpawn onPtyIdReady={onPtyIdReady} />
+      );
+
+      rerender(<DrawerTerminal {...defaultProps} isActive lazySpawn onPtyIdReady={onPtyIdReady} />);
+
+      await waitFor(() => {
+        const spawnCall = invokeHistory.find((h) => h.command === 'spawn_terminal');
+        expect(spawnCall?.args).toHaveProperty('worktreeId', 'worktree-1');
+        expect(onPtyIdReady).toHaveBeenCalledWith('pty-pane-123');
+      });
+      expect(invokeHistory.find((h) => h.command === 'spawn_shell')).toBeUndefined();
+    });
+
+    it('spawns an active lazy shell eagerly with spawn_shell', async () => {
+      render(<DrawerTerminal {...defaultProps} lazySpawn />);
+
+      await waitFor(() => {
+        expect(invokeHistory.find((h) => h.command === 'spawn_shell')).toBeDefined();
+      });
+      expect(invokeHistory.find((h) => h.command === 'spawn_terminal')).toBeUndefined();
+    });
   });
 
   describe('auto-close on exit', () => {
@@ -125,6 +167,32 @@ describe('DrawerTerminal', () => {
     });
   });
 
+  describe('worktree relocation', () => {
+    it('keeps the tab and restarts a command in the moved directory', async () => {
+      mockInvokeResponses.set('spawn_command', 'pty-cmd-1');
+      const onClose = vi.fn();
+
+      render(<DrawerTerminal {...defaultProps} command="nvim" directory="/old/feature/src" onClose={onClose} />);
+
+      await waitFor(() => {
+        expect(invokeHistory.some((h) => h.command === 'spawn_command')).toBe(true);
+      });
+
+      await act(async () => {
+        emitEvent('worktree-relocating', { worktreeId: 'worktree-1', ptyIds: ['pty-cmd-1'] });
+        emitEvent('pty-exit', { ptyId: 'pty-cmd-1' });
+        emitEvent('worktree-relocated', { worktreeId: 'worktree-1', oldPath: '/old/feature', newPath: '/new/feature' });
+      });
+
+      expect(onClose).not.toHaveBeenCalled();
+      await waitFor(() => {
+        const spawns = invokeHistory.filter((h) => h.command === 'spawn_command');
+        expect(spawns).toHaveLength(2);
+        expect(spawns[1].args).toHaveProperty('directory', '/new/feature/src');
+      });
+    });
+  });
+
   describe('focus behavior', () => {
     it('accepts onFocus callback prop', async () => {
       const onFocus = vi.fn();
@@ -52,6 +52,8 @@ interface DrawerTerminalProps {
   /** Counter that triggers focus when incremented */
   focusTrigger?: number;
   terminalConfig: TerminalConfig;
+  /** Defer starting the shell until the tab is first active (`terminal.lazySpawn`) */
+  lazySpawn?: boolean;
   onClose?: () => void;
   onFocus?: () => void;
   onPtyIdReady?: (ptyId: string) => void;
@@ -59,11 +61,19 @@ interface DrawerTerminalProps {
   onTitleChange?: (title: string) => void;
 }
 
-export function DrawerTerminal({ id, entityId, directory, command, isActive, shouldAutoFocus, focusTrigger, terminalConfig, onClose, onFocus, onPtyIdReady, onTitleChange }: DrawerTerminalProps) {
+export function DrawerTerminal({ id, entityId, directory, command, isActive, shouldAutoFocus, focusTrigger, terminalConfig, lazySpawn = false, onClose, onFocus, onPtyIdReady, onTitleChange }: DrawerTerminalProps) {
   const containerRef = useRef<HTMLDivElement>(null);
   const terminalRef = useRef<Terminal | null>(null);
   const fitAddonRef = useRef<FitAddon | null>(null);
   const initializedRef = useRef(false);
+  // Spawn deferred by lazySpawn until the tab is first active
+  const pendingSpawnRef = useRef<(() => Promise<void>) | null>(null);
+  const isActiveRef = useRef(isActive);
+  const lazySpawnRef = useRef(lazySpawn);
+  useEffect(() => {
+    isActiveRef.current = isActive;
+    lazySpawnRef.current = lazySpawn;
+  }, [isActive, lazySpawn]);
 
   // Get theme from context (uses sideBar.background for visual hierarchy)
   const xtermTheme = useDrawerXtermTheme();
@@ -77,17 +87,19 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
     }
   }, []);
 
-  const { ptyId, spawnShell, spawnCommand, write, resize, kill } = usePty(handleOutput);
+  const { ptyId, spawn, spawnShell, spawnCommand, write, resize, kill } = usePty(handleOutput);
 
-  // Store spawnShell/spawnCommand/kill in refs so they're stable for the effect
+  // Store spawn/spawnShell/spawnCommand/kill in refs so they're stable for the effect
+  const spawnRef = useRef(spawn);
   const spawnShellRef = useRef(spawnShell);
   const spawnCommandRef = useRef(spawnCommand);
   const killRef = useRef(kill);
   useEffect(() => {
+    spawnRef.current = spawn;
     spawnShellRef.current = spawnShell;
     spawnCommandRef.current = spawnCommand;
     killRef.current = kill;
-  }, [spawnShell, spawnCommand, kill]);
+  }, [spawn, spawnShell, spawnCommand, kill]);
 
   // Store write function in ref so handlers can use it immediately
   const writeRef = useRef(write);
@@ -207,7 +219,9 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
     containerRef.current.addEventListener('focusin', handleFocus);
 
     // Fit terminal and spawn shell or command
-    const initPty = async () => {
+    // A lazily deferred shell is the entity's terminal pane, so the backend records
+    // it as the pane's session (spawn_terminal) rather than as a loose shell
+    const initPty = async (asPane = false) => {
       // Wait for next frame to ensure container is laid out
       await new Promise(resolve => requestAnimationFrame(resolve));
       if (!isMounted) return; // Component unmounted during wait
@@ -219,17 +233,25 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
       // If a command is specified, spawn it; otherwise spawn a shell
       const newPtyId = command
         ? await spawnCommandRef.current(entityId, directory ?? '', command, cols, rows)
-        : await spawnShellRef.current(entityId, directory, cols, rows);
+        : asPane
+          ? await spawnRef.current(entityId, 'shell', cols, rows)
+          : await spawnShellRef.current(entityId, directory, cols, rows);
 
       if (newPtyId && isMounted) {
         onPtyIdReadyRef.current?.(newPtyId);
       }
     };
 
-    initPty().catch(console.error);
+    // Commands always start right away; a lazy shell waits for first focus
+    if (lazySpawnRef.current && !command && !isActiveRef.current) {
+      pendingSpawnRef.current = () => initPty(true);
+    } else {
+      initPty().catch(console.error);
+    }
 
     return () => {
       isMounted = false;
+      pendingSpawnRef.current = null;
       onDataDisposable.dispose();
       titleChangeDisposable.dispose();
       cleanupKeyboardHandlers();
@@ -250,6 +272,14 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
     };
   }, [id, entityId, directory, command]);
 
+  // Start a lazily deferred shell the first time the tab becomes active
+  useEffect(() => {
+    if (!isActive || !pendingSpawnRef.current) return;
+    const initPty = pendingSpawnRef.current;
+    pendingSpawnRef.current = null;
+    initPty().catch(console.error);
+  }, [isActive]);
+
   // Update terminal theme when it changes
   useEffect(() => {
     if (terminalRef.current) {
@@ -271,12 +301,15 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
     }
   }, [isActive]);
 
+  // Session closed to move its worktree; its exit isn't the user's doing
+  const relocatingPtyIdRef = useRef<string | null>(null);
+
   // Listen for pty-exit event to auto-close the tab
   useEffect(() => {
     if (!ptyId) return;
 
     const unlisten = listen<{ ptyId: string }>('pty-exit', (event) => {
-      if (event.payload.ptyId === ptyId) {
+      if (event.payload.ptyId === ptyId && event.payload.ptyId !== relocatingPtyIdRef.current) {
         onClose?.();
       }
     });
@@ -286,6 +319,37 @@ export function DrawerTerminal({ id, entityId, directory, command, isActive, sho
     };
   }, [ptyId, onClose]);
 
+  // Moving the worktree closes its sessions. A shell's directory is the worktree's
+  // path, so it restarts when that prop changes; a command keeps its own
+  // directory and is started again here, moved along with the worktree. After a
+  // failed move the path stays the same, so shells are restarted here too.
+  useEffect(() => {
+    if (!ptyId) return;
+
+    const unlistenRelocating = listen<{ worktreeId: string; ptyIds: string[] }>('worktree-relocating', (event) => {
+      if (event.payload.ptyIds.includes(ptyId)) {
+        relocatingPtyIdRef.current = ptyId;
+      }
+    });
+    const unlistenRelocated = listen<{ worktreeId: string; oldPath: string; newPath: string }>('worktree-relocated', (event) => {
+      const terminal = terminalRef.current;
+      if (relocatingPtyIdRef.current !== ptyId || !terminal) return;
+      relocatingPtyIdRef.current = null;
+      const { oldPath, newPath } = event.payload;
+      if (!command && newPath !== oldPath) return;
+      const cwd = directory?.startsWith(oldPath) ? newPath + directory.slice(oldPath.length) : directory;
+      const respawn = command
+        ? spawnCommandRef.current(entityId, cwd ?? '', command, terminal.cols, terminal.rows)
+        : spawnShellRef.current(entityId, cwd, terminal.cols, terminal.rows);
+      respawn.then((newPtyId) => onPtyIdReadyRef.current?.(newPtyId)).catch(console.error);
+    });
+
+    return () => {
+      unlistenRelocating.then((fn) => fn());
+      unlistenRelocated.then((fn) => fn());
+    };
+  }, [ptyId, entityId, directory, command]);
+
   // Store resize function in ref
   const resizeRef = useRef(resize);
   const ptyIdRef = useRef(ptyId);
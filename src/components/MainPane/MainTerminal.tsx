@@ -168,12 +168,15 @@ export function MainTerminal({ entityId, sessionId, type = 'main', isActive, sho
 
   const { ptyId, spawn, write, resize, kill } = usePty(handleOutput, handleReady);
 
+  // Session closed to move its worktree; its exit isn't the user's doing
+  const relocatingPtyIdRef = useRef<string | null>(null);
+
   // Listen for pty-exit event
   useEffect(() => {
     if (!ptyId) return;
 
     const unlisten = listen<{ ptyId: string; worktreeId: string; command: string; exitCode: number | null }>('pty-exit', (event) => {
-      if (event.payload.ptyId === ptyId) {
+      if (event.payload.ptyId === ptyId && event.payload.ptyId !== relocatingPtyIdRef.current) {
         onExit?.();
       }
     });
@@ -237,6 +240,49 @@ export function MainTerminal({ entityId, sessionId, type = 'main', isActive, sho
     onPtyIdReadyRef.current = onPtyIdReady;
   }, [onPtyIdReady]);
 
+  // A suspended worktree refuses to spawn its main session; start it once resumed
+  useEffect(() => {
+    if (type !== 'main') return;
+
+    const unlisten = listen<{ worktreeId: string }>('worktree-resumed', (event) => {
+      const terminal = terminalRef.current;
+      if (event.payload.worktreeId !== spawnId || !terminal) return;
+      spawnedAtRef.current = Date.now();
+      spawnRef.current(spawnId, type, terminal.cols, terminal.rows)
+        .then((newPtyId) => onPtyIdReadyRef.current?.(newPtyId))
+        .catch(console.error);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [spawnId, type]);
+
+  // Moving the worktree closes its sessions; start this one again at the new path
+  useEffect(() => {
+    if (!ptyId) return;
+
+    const unlistenRelocating = listen<{ worktreeId: string; ptyIds: string[] }>('worktree-relocating', (event) => {
+      if (event.payload.ptyIds.includes(ptyId)) {
+        relocatingPtyIdRef.current = ptyId;
+      }
+    });
+    const unlistenRelocated = listen<{ worktreeId: string; newPath: string }>('worktree-relocated', () => {
+      const terminal = terminalRef.current;
+      if (relocatingPtyIdRef.current !== ptyId || !terminal) return;
+      relocatingPtyIdRef.current = null;
+      spawnedAtRef.current = Date.now();
+      spawnRef.current(spawnId, type, terminal.cols, terminal.rows)
+        .then((newPtyId) => onPtyIdReadyRef.current?.(newPtyId))
+        .catch(console.error);
+    });
+
+    return () => {
+      unlistenRelocating.then((fn) => fn());
+      unlistenRelocated.then((fn) => fn());
+    };
+  }, [ptyId, spawnId, type]);
+
   // Progress indicator logic:
   // - Activity (output/title): start timer, reset on more activity, turn off when timer expires
   // - OSC 9 progress: explicit start/stop (no timeout)
@@ -0,0 +1,29 @@
+import { describe, it, expect, vi } from 'vitest';
+import { render, screen, fireEvent } from '@testing-library/react';
+import { OrphanCleanupModal } from './OrphanCleanupModal';
+
+describe('OrphanCleanupModal', () => {
+  const orphans = [
+    { pid: 101, appPid: 1, name: 'zsh' },
+    { pid: 102, appPid: 1, name: 'node' },
+  ];
+
+  it('stops every orphan unless some are unchecked', () => {
+    const onConfirm = vi.fn();
+    render(<OrphanCleanupModal orphans={orphans} onConfirm={onConfirm} />);
+
+    fireEvent.click(screen.getByText('node'));
+    fireEvent.click(screen.getByText('Stop 1'));
+
+    expect(onConfirm).toHaveBeenCalledWith([101]);
+  });
+
+  it('keeps everything running on Keep All', () => {
+    const onConfirm = vi.fn();
+    render(<OrphanCleanupModal orphans={orphans} onConfirm={onConfirm} />);
+
+    fireEvent.click(screen.getByText('Keep All'));
+
+    expect(onConfirm).toHaveBeenCalledWith([]);
+  });
+});
@@ -0,0 +1,70 @@
+import { useCallback, useState } from 'react';
+import { Modal, ModalHeader, ModalBody, ModalActions, ModalButton, ModalText } from './Modal';
+import type { OrphanInfo } from '../lib/tauri';
+
+interface OrphanCleanupModalProps {
+  orphans: OrphanInfo[];
+  /** Called with the PIDs to stop; the rest keep running */
+  onConfirm: (pids: number[]) => void;
+  onModalOpen?: () => void;
+  onModalClose?: () => void;
+}
+
+export function OrphanCleanupModal({ orphans, onConfirm, onModalOpen, onModalClose }: OrphanCleanupModalProps) {
+  const [selected, setSelected] = useState<Set<number>>(() => new Set(orphans.map((orphan) => orphan.pid)));
+
+  const toggle = useCallback((pid: number) => {
+    setSelected((prev) => {
+      const next = new Set(prev);
+      if (next.has(pid)) {
+        next.delete(pid);
+      } else {
+        next.add(pid);
+      }
+      return next;
+    });
+  }, []);
+
+  const handleStop = useCallback(() => onConfirm([...selected]), [onConfirm, selected]);
+  const handleKeepAll = useCallback(() => onConfirm([]), [onConfirm]);
+
+  return (
+    <Modal
+      onClose={handleKeepAll}
+      onSubmit={handleStop}
+      onModalOpen={onModalOpen}
+      onModalClose={onModalClose}
+      closeOnBackdrop={false}
+    >
+      <ModalHeader>Leftover Processes</ModalHeader>
+
+      <ModalBody>
+        <ModalText muted>
+          A previous Shellflow session ended unexpectedly and left these processes running.
+          Choose which to stop.
+        </ModalText>
+        <ul className="mt-3 space-y-1.5">
+          {orphans.map((orphan) => (
+            <li key={orphan.pid}>
+              <label className="flex items-center gap-2 text-[13px]" style={{ color: 'var(--modal-item-text)' }}>
+                <input type="checkbox" checked={selected.has(orphan.pid)} onChange={() => toggle(orphan.pid)} />
+                <span className="font-mono">{orphan.name ?? 'unknown'}</span>
+                <span className="opacity-60">PID {orphan.pid}</span>
+              </label>
+            </li>
+          ))}
+        </ul>
+        <ModalText muted size="xs">
+          Without an answer within a minute, all of them are stopped.
+        </ModalText>
+      </ModalBody>
+
+      <ModalActions>
+        <ModalButton onClick={handleKeepAll}>Keep All</ModalButton>
+        <ModalButton onClick={handleStop} variant="primary">
+          {selected.size === orphans.length ? 'Stop All' : `Stop ${selected.size}`}
+        </ModalButton>
+      </ModalActions>
+    </Modal>
+  );
+}
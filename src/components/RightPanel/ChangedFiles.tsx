@@ -21,6 +21,9 @@ const statusConfig: Record<FileChange['status'], { color: string; label: string
   deleted: { color: 'text-red-400', label: 'D' },
   renamed: { color: 'text-blue-400', label: 'R' },
   untracked: { color: 'text-theme-2', label: '?' },
+  conflicted: { color: 'text-orange-400', label: 'U' },
+  copied: { color: 'text-blue-400', label: 'C' },
+  typechanged: { color: 'text-yellow-400', label: 'T' },
 };
 
 export function ChangedFiles({
@@ -133,7 +136,7 @@ export function ChangedFiles({
                     {config.label}
                   </span>
                   <span className="text-sm text-theme-1 truncate flex-1" title={file.path}>
-                    {file.path}
+                    {file.oldPath ? `${file.oldPath} → ${file.path}` : file.path}
                   </span>
                   {(file.insertions !== undefined || file.deletions !== undefined) && (
                     <span className="text-xs font-mono flex-shrink-0">
@@ -9,6 +9,8 @@ export interface TerminalConfig {
   fontSize: number;
   fontLigatures: boolean;
   padding: number;
+  /** Per-pane theme. If omitted, uses the top-level theme. */
+  theme?: ThemeConfig;
 }
 
 /** Drawer-specific configuration */
@@ -100,11 +102,28 @@ export interface ScratchConfig {
 }
 
 export interface WorktreeConfig {
+  /** Initialize and check out submodules in new worktrees */
+  initSubmodules: boolean;
   focusNewBranchNames: boolean;
   merge: MergeConfig;
   delete: DeleteConfig;
 }
 
+/** Worktree terminal pane behaviour (the `terminal` section) */
+export interface TerminalPaneConfig {
+  /** Start a worktree's terminal shell only when its pane is first focused */
+  lazySpawn: boolean;
+  /** Signal sent on quit to processes that survive SIGHUP, before SIGKILL */
+  shutdownSignal: 'SIGTERM' | 'SIGINT' | 'SIGHUP' | 'SIGUSR1' | 'SIGUSR2' | 'SIGKILL';
+  /** Thresholds for the heuristic session activity detection */
+  activity: {
+    /** Continuous output (ms) before a session counts as busy */
+    busyAfterMs: number;
+    /** Quiet period (ms) before a session counts as idle or waiting for input */
+    idleAfterMs: number;
+  };
+}
+
 export interface PanesConfig {
   /** Opacity (0.0 to 1.0) applied to unfocused split panes */
   unfocusedOpacity: number;
@@ -129,6 +148,7 @@ export interface Config {
   scratch: ScratchConfig;
   worktree: WorktreeConfig;
   panes: PanesConfig;
+  terminal: TerminalPaneConfig;
   /** Theme configuration - can be a single theme name or light/dark object */
   theme?: ThemeConfig;
   /** How to handle borders when adapting themes */
@@ -141,6 +161,14 @@ export interface ConfigError {
   message: string;
 }
 
+/** A config value that parses but can't work, from validate_config */
+export interface ConfigIssue {
+  /** Dotted key path as written in the config file, e.g. "main.fontSize" */
+  fieldPath: string;
+  severity: 'error' | 'warning';
+  message: string;
+}
+
 /** Result from get_config, includes config and any parse errors */
 interface ConfigResult {
   config: Config;
@@ -179,6 +207,7 @@ const defaultConfig: Config = {
     startOnLaunch: true,
   },
   worktree: {
+    initSubmodules: false,
     focusNewBranchNames: false,
     merge: {
       strategy: 'merge',
@@ -193,6 +222,14 @@ const defaultConfig: Config = {
   panes: {
     unfocusedOpacity: 0.95,
   },
+  terminal: {
+    lazySpawn: false,
+    shutdownSignal: 'SIGTERM',
+    activity: {
+      busyAfterMs: 1000,
+      idleAfterMs: 2000,
+    },
+  },
   themeBorderStyle: 'subtle',
 };
 
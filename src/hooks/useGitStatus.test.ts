@@ -258,7 +258,10 @@ describe('useGitStatus', () => {
       });
 
       const { result } = renderHook(() =>
-        useGitStatus({ id: 'worktree-1', path: '/path/to/worktree' }, { mode: 'uncommitted' })
+        useGitStatus(
+          { id: 'worktree-1', path: '/path/to/worktree' },
+          { mode: 'uncommitted', projectPath: '/path/to/project' }
+        )
       );
 
       await waitFor(() => {
@@ -266,7 +269,10 @@ describe('useGitStatus', () => {
       });
 
       const uncommittedCall = invokeHistory.find((h) => h.command === 'get_changed_files');
-      expect(uncommittedCall).toBeDefined();
+      expect(uncommittedCall?.args).toEqual({
+        worktreePath: '/path/to/worktree',
+        projectPath: '/path/to/project',
+      });
     });
 
     it('refetches data when mode changes', async () => {
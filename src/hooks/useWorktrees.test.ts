@@ -197,6 +197,25 @@ describe('useWorktrees', () => {
       expect(createCall?.args).toEqual({ projectPath: project.path, name: 'custom-name' });
     });
 
+    it('passes the caller-chosen operation ID', async () => {
+      const project = createTestProject({ id: 'proj-1' });
+      mockInvokeResponses.set('list_projects', [project]);
+      mockInvokeResponses.set('create_worktree', createTestWorktree({ id: 'wt-op' }));
+
+      const { result } = renderHook(() => useWorktrees());
+
+      await waitFor(() => {
+        expect(result.current.loading).toBe(false);
+      });
+
+      await act(async () => {
+        await result.current.createWorktree(project.path, undefined, 'op-1');
+      });
+
+      const createCall = invokeHistory.find((h) => h.command === 'create_worktree');
+      expect(createCall?.args).toEqual({ projectPath: project.path, opId: 'op-1' });
+    });
+
     it('throws when worktree creation fails', async () => {
       const project = createTestProject({ id: 'proj-1' });
       mockInvokeResponses.set('list_projects', [project]);
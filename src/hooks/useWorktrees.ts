@@ -58,6 +58,20 @@ export function useWorktrees() {
     };
   }, [loadProjects]);
 
+  // Reload when a worktree is suspended, resumed or moved so its `suspended` flag
+  // and path are current
+  useEffect(() => {
+    const unlisteners = ['worktree-suspended', 'worktree-resumed', 'worktree-relocated'].map((event) =>
+      listen<{ worktreeId: string }>(event, () => {
+        loadProjects();
+      })
+    );
+
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+    };
+  }, [loadProjects]);
+
   const addProject = useCallback(async (path: string) => {
     try {
       const project = await invoke<Project>('add_project', { path });
@@ -70,12 +84,13 @@ export function useWorktrees() {
   }, []);
 
   const createWorktree = useCallback(
-    async (projectPath: string, name?: string) => {
+    async (projectPath: string, name?: string, opId?: string) => {
       console.log('[useWorktrees.createWorktree] Called with path:', projectPath);
       try {
         const worktree = await invoke<Worktree>('create_worktree', {
           projectPath,
           name,
+          opId,
         });
         console.log('[useWorktrees.createWorktree] Success:', worktree.name);
         // Reload projects to get updated worktree list
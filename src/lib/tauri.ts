@@ -3,22 +3,46 @@ import { open } from '@tauri-apps/plugin-dialog';
 import {
   Project,
   Worktree,
+  CreatedWorktree,
   FileChange,
   MergeFeasibility,
+  DivergenceSummary,
+  PreflightResult,
+  Pane,
+  FocusedSession,
+  WorktreeUsage,
+  SubmoduleStatus,
   MergeWorkflowOptions,
   CleanupOptions,
   WorktreeDeleteStatus,
   DeleteWorktreeOptions,
   BranchInfo,
+  WorktreeStatus,
+  WorktreeSafety,
+  AppMetrics,
+  ConflictFile,
   DiffContent,
   ChangedFilesViewMode,
 } from '../types';
+import type { ConfigIssue } from '../hooks/useConfig';
 
 // Project commands
 export async function addProject(path: string): Promise<Project> {
   return invoke<Project>('add_project', { path });
 }
 
+export interface PathInspection {
+  isGitRepo: boolean;
+  repoRoot: string | null;
+  isBare: boolean;
+  isWorktree: boolean;
+  defaultBranch: string | null;
+}
+
+export async function inspectPath(path: string): Promise<PathInspection> {
+  return invoke<PathInspection>('inspect_path', { path });
+}
+
 export async function listProjects(): Promise<Project[]> {
   return invoke<Project[]>('list_projects');
 }
@@ -32,11 +56,48 @@ export async function touchProject(projectId: string): Promise<void> {
 }
 
 // Worktree commands
+/**
+ * Pass your own `opId` to be able to cancel the creation before this resolves;
+ * otherwise use the returned `creationOpId`.
+ */
 export async function createWorktree(
   projectPath: string,
-  name?: string
-): Promise<Worktree> {
-  return invoke<Worktree>('create_worktree', { projectPath, name });
+  name?: string,
+  opId?: string
+): Promise<CreatedWorktree> {
+  return invoke<CreatedWorktree>('create_worktree', { projectPath, name, opId });
+}
+
+/** Adopt worktrees added to the repository outside the app (e.g. `git worktree add`). */
+export async function importExistingWorktrees(projectId: string): Promise<Worktree[]> {
+  return invoke<Worktree[]>('import_existing_worktrees', { projectId });
+}
+
+export async function cancelWorktreeCreation(opId: string): Promise<boolean> {
+  return invoke<boolean>('cancel_worktree_creation', { opId });
+}
+
+export async function suspendWorktree(worktreeId: string): Promise<void> {
+  return invoke('suspend_worktree', { worktreeId });
+}
+
+export async function resumeWorktree(worktreeId: string): Promise<void> {
+  return invoke('resume_worktree', { worktreeId });
+}
+
+export async function setWorktreeInspect(worktreeId: string, inspect: boolean): Promise<void> {
+  return invoke('set_worktree_inspect', { worktreeId, inspect });
+}
+
+export async function setWorktreeEnvironment(worktreeId: string, profile: string | null): Promise<void> {
+  return invoke('set_worktree_environment', { worktreeId, profile });
+}
+
+export async function resolveWorktreePath(
+  projectPath: string,
+  name: string
+): Promise<{ path: string; exists: boolean }> {
+  return invoke('resolve_worktree_path', { projectPath, name });
 }
 
 export async function listWorktrees(projectPath: string): Promise<Worktree[]> {
@@ -84,6 +145,16 @@ export async function spawnTerminal(worktreeId: string): Promise<string> {
   return invoke<string>('spawn_terminal', { worktreeId });
 }
 
+/** PTY of a worktree pane, spawning it if the pane has no live shell yet */
+export async function focusSession(
+  worktreeId: string,
+  pane: Pane,
+  cols?: number,
+  rows?: number
+): Promise<FocusedSession> {
+  return invoke<FocusedSession>('focus_session', { worktreeId, pane, cols, rows });
+}
+
 export async function spawnAction(
   worktreeId: string,
   prompt: string,
@@ -97,10 +168,55 @@ export async function watchMergeState(worktreeId: string): Promise<void> {
   return invoke('watch_merge_state', { worktreeId });
 }
 
+export async function listConflicts(worktreePath: string): Promise<ConflictFile[]> {
+  return invoke<ConflictFile[]>('list_conflicts', { worktreePath });
+}
+
+/** Returns the conflicts still unresolved; call continueMerge once none are left */
+export async function resolveConflict(
+  worktreePath: string,
+  file: string,
+  side: 'ours' | 'theirs'
+): Promise<ConflictFile[]> {
+  return invoke<ConflictFile[]>('resolve_conflict', { worktreePath, file, side });
+}
+
+export async function markResolved(worktreePath: string, file: string): Promise<ConflictFile[]> {
+  return invoke<ConflictFile[]>('mark_resolved', { worktreePath, file });
+}
+
+/** Commit the resolved merge or continue the rebase; returns the next commit's conflicts, if any */
+export async function continueMerge(worktreeId: string, worktreePath: string): Promise<ConflictFile[]> {
+  return invoke<ConflictFile[]>('continue_merge', { worktreeId, worktreePath });
+}
+
 export async function stopMergeWatcher(worktreeId: string): Promise<void> {
   return invoke('stop_merge_watcher', { worktreeId });
 }
 
+export interface CommitInfo {
+  hash: string;
+  shortHash: string;
+  subject: string;
+  author: string;
+  /** ISO-8601 commit timestamp */
+  timestamp: string;
+}
+
+/** Payload of the `git-log-changed` event */
+export interface GitLogChanged {
+  worktreeId: string;
+  commits: CommitInfo[];
+}
+
+export async function watchGitLog(worktreeId: string, worktreePath: string): Promise<void> {
+  return invoke('watch_git_log', { worktreeId, worktreePath });
+}
+
+export async function stopGitLogWatcher(worktreeId: string): Promise<void> {
+  return invoke('stop_git_log_watcher', { worktreeId });
+}
+
 export async function watchRebaseState(worktreeId: string): Promise<void> {
   return invoke('watch_rebase_state', { worktreeId });
 }
@@ -154,9 +270,36 @@ export async function ptyForceKill(ptyId: string): Promise<void> {
   return invoke('pty_force_kill', { ptyId });
 }
 
+export interface SessionStats {
+  /** 100 = one fully busy core */
+  cpuPercent: number;
+  rssBytes: number;
+  numProcesses: number;
+}
+
+export async function sessionStats(ptyId: string, includeChildren = true): Promise<SessionStats> {
+  return invoke<SessionStats>('session_stats', { ptyId, includeChildren });
+}
+
+/**
+ * Heuristic guess at what a session is doing, from its output timing and last
+ * line. Changes are also emitted as `session-activity-changed` events.
+ * May misfire for programs that redraw constantly or use unusual prompts.
+ */
+export type SessionActivity = 'idle' | 'busy' | 'waitingForInput';
+
+export interface SessionActivityChanged {
+  sessionId: string;
+  activity: SessionActivity;
+}
+
+export async function sessionActivity(sessionId: string): Promise<SessionActivity> {
+  return invoke<SessionActivity>('session_activity', { sessionId });
+}
+
 // Git commands
-export async function getChangedFiles(worktreePath: string): Promise<FileChange[]> {
-  return invoke<FileChange[]>('get_changed_files', { worktreePath });
+export async function getChangedFiles(worktreePath: string, projectPath?: string): Promise<FileChange[]> {
+  return invoke<FileChange[]>('get_changed_files', { worktreePath, projectPath });
 }
 
 export async function hasUncommittedChanges(projectPath: string): Promise<boolean> {
@@ -171,8 +314,9 @@ export async function stashPop(projectPath: string, stashId: string): Promise<vo
   return invoke<void>('stash_pop', { projectPath, stashId });
 }
 
-export async function abortMerge(projectPath: string): Promise<void> {
-  return invoke<void>('abort_merge', { projectPath });
+/** Pass the worktree's ID so the abort can't overlap another operation on it. */
+export async function abortMerge(projectPath: string, worktreeId?: string): Promise<void> {
+  return invoke<void>('abort_merge', { projectPath, worktreeId });
 }
 
 export async function abortRebase(projectPath: string): Promise<void> {
@@ -186,6 +330,27 @@ export async function getBranchInfo(
   return invoke<BranchInfo>('get_branch_info', { worktreePath, projectPath });
 }
 
+export async function refreshAllWorktrees(): Promise<WorktreeStatus[]> {
+  return invoke<WorktreeStatus[]>('refresh_all_worktrees');
+}
+
+export type MaintenanceTask = 'gc' | 'worktreePrune' | 'remotePrune';
+
+export interface MaintenanceSummary {
+  tasks: { task: MaintenanceTask; error: string | null }[];
+  /** Worktrees git forgot; they're removed from the project too */
+  prunedWorktrees: string[];
+}
+
+/** Run git housekeeping on a project; output streams as `git-progress` */
+export async function runGitMaintenance(projectPath: string, tasks: MaintenanceTask[]): Promise<MaintenanceSummary> {
+  return invoke<MaintenanceSummary>('run_git_maintenance', { projectPath, tasks });
+}
+
+export async function classifyWorktrees(projectId: string): Promise<WorktreeSafety[]> {
+  return invoke<WorktreeSafety[]>('classify_worktrees', { projectId });
+}
+
 export async function getBranchChangedFiles(
   worktreePath: string,
   projectPath?: string
@@ -246,6 +411,60 @@ export async function checkMergeFeasibility(
   return invoke<MergeFeasibility>('check_merge_feasibility', { worktreePath, projectPath });
 }
 
+/** Ahead/behind counts against the worktree's base, and when it last synced. Rejects with a `GitError`. */
+export async function divergenceSummary(
+  worktreePath: string,
+  projectPath?: string
+): Promise<DivergenceSummary> {
+  return invoke<DivergenceSummary>('divergence_summary', { worktreePath, projectPath });
+}
+
+/** Trial-run a merge/rebase in memory; the worktree is left untouched */
+export async function mergePreflight(
+  worktreePath: string,
+  targetBranch: string,
+  strategy?: 'merge' | 'rebase',
+  projectPath?: string
+): Promise<PreflightResult> {
+  return invoke<PreflightResult>('merge_preflight', { worktreePath, targetBranch, strategy, projectPath });
+}
+
+/** Save a worktree's patch against targetBranch to outPath, returning its size in bytes */
+export async function exportWorktreePatch(
+  worktreePath: string,
+  targetBranch: string,
+  outPath: string
+): Promise<number> {
+  return invoke<number>('export_worktree_patch', { worktreePath, targetBranch, outPath });
+}
+
+/** Write every worktree's patch plus a manifest into a tarball at outPath */
+export async function exportProjectBundle(projectId: string, outPath: string): Promise<void> {
+  return invoke<void>('export_project_bundle', { projectId, outPath });
+}
+
+/** Disk space used by each of a project's worktrees, excluding the shared git object store */
+export async function worktreeDiskUsage(projectId: string): Promise<WorktreeUsage[]> {
+  return invoke<WorktreeUsage[]>('worktree_disk_usage', { projectId });
+}
+
+/** Init, sync and dirty state of each submodule in a worktree */
+export async function getSubmoduleStatus(worktreePath: string): Promise<SubmoduleStatus[]> {
+  return invoke<SubmoduleStatus[]>('get_submodule_status', { worktreePath });
+}
+
+/**
+ * Install post-merge/post-rewrite hooks in this worktree only, so merge and rebase
+ * completion is signalled by git rather than detected by polling
+ */
+export async function installWorktreeHooks(worktreePath: string): Promise<void> {
+  return invoke<void>('install_worktree_hooks', { worktreePath });
+}
+
+export async function uninstallWorktreeHooks(worktreePath: string): Promise<void> {
+  return invoke<void>('uninstall_worktree_hooks', { worktreePath });
+}
+
 export async function executeMergeWorkflow(
   worktreeId: string,
   options: MergeWorkflowOptions
@@ -278,3 +497,69 @@ export async function shutdown(): Promise<boolean> {
 export async function updateActionAvailability(availability: Record<string, boolean>): Promise<void> {
   return invoke<void>('update_action_availability', { availability });
 }
+
+export async function setLogLevel(level: 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace'): Promise<void> {
+  return invoke('set_log_level', { level });
+}
+
+/** Save a pane's font size to the global config (clamped to 6-72); resolves to the size saved */
+export async function setFontSize(pane: Pane, size: number): Promise<number> {
+  return invoke<number>('set_font_size', { pane, size });
+}
+
+export async function setFontFamily(pane: Pane, family: string): Promise<void> {
+  return invoke<void>('set_font_family', { pane, family });
+}
+
+export async function validateConfig(projectPath?: string): Promise<ConfigIssue[]> {
+  return invoke<ConfigIssue[]>('validate_config', { projectPath: projectPath ?? null });
+}
+
+// Open the config (~/.config/shellflow) or state (~/.shellflow) directory in the file manager
+export async function revealConfigDir(): Promise<void> {
+  return invoke<void>('reveal_config_dir');
+}
+
+export async function revealStateDir(): Promise<void> {
+  return invoke<void>('reveal_state_dir');
+}
+
+/** Rename only a worktree's branch, keeping its display name and directory */
+export async function renameWorktreeBranch(worktreeId: string, newName: string): Promise<void> {
+  return invoke<void>('rename_worktree_branch', { worktreeId, newName });
+}
+
+export async function relocateWorktree(worktreeId: string, newParentDir: string): Promise<string> {
+  return invoke<string>('relocate_worktree', { worktreeId, newParentDir });
+}
+
+/** A process left running by a crashed instance (`orphans-found` payload) */
+export interface OrphanInfo {
+  pid: number;
+  appPid: number;
+  name: string | null;
+}
+
+/** Choose which orphans to kill; resolves to false if the prompt already timed out */
+export async function confirmOrphanCleanup(pids: number[]): Promise<boolean> {
+  return invoke<boolean>('confirm_orphan_cleanup', { pids });
+}
+
+export async function pendingOrphans(): Promise<OrphanInfo[]> {
+  return invoke<OrphanInfo[]>('pending_orphans');
+}
+
+export type PidFileHealth =
+  | { status: 'healthy'; tracked: number }
+  | { status: 'rebuilt'; tracked: number; reason: string }
+  | { status: 'failed'; error: string };
+
+/** Check this instance's PID file, rebuilding it from live sessions if it's corrupt */
+export async function verifyPidFile(): Promise<PidFileHealth> {
+  return invoke<PidFileHealth>('verify_pid_file');
+}
+
+/** Session, watcher and PID counts plus memory and uptime; cheap to poll */
+export async function appMetrics(): Promise<AppMetrics> {
+  return invoke<AppMetrics>('app_metrics');
+}
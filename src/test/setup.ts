@@ -124,6 +124,7 @@ vi.mock('@tauri-apps/api/core', () => ({
       case 'spawn_shell':
         return Promise.resolve(`pty-${Date.now()}`);
       case 'list_themes':
+      case 'pending_orphans':
         return Promise.resolve([]);
       case 'read_theme':
         return Promise.resolve('{}');
@@ -3,6 +3,7 @@
  */
 
 import { createContext, useContext, useState, useEffect, useCallback, useMemo, ReactNode } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import type {
   ThemeContextValue,
   ThemeInfo,
@@ -27,6 +28,8 @@ interface ThemeProviderProps {
   themeConfig?: ThemeConfig;
   /** How to handle borders when adapting themes */
   borderStyle?: ThemeBorderStyle;
+  /** Active project, whose config may add theme directories */
+  projectPath?: string;
   /** Callback when theme changes (for persisting to config) */
   onThemeChange?: (themeName: string) => void;
   /** Callback when border style changes (for persisting to config) */
@@ -96,7 +99,7 @@ function createDefaultResolvedTheme(themeType: 'light' | 'dark'): ResolvedTheme
 /**
  * Theme provider component.
  */
-export function ThemeProvider({ children, themeConfig, borderStyle: configBorderStyle = 'subtle', onThemeChange, onBorderStyleChange }: ThemeProviderProps) {
+export function ThemeProvider({ children, themeConfig, borderStyle: configBorderStyle = 'subtle', projectPath, onThemeChange, onBorderStyleChange }: ThemeProviderProps) {
   const systemColorScheme = useColorScheme();
   const [forcedColorScheme, setForcedColorScheme] = useState<'light' | 'dark' | null>(null);
   const [availableThemes, setAvailableThemes] = useState<ThemeInfo[]>([]);
@@ -122,15 +125,23 @@ export function ThemeProvider({ children, themeConfig, borderStyle: configBorder
   // Current theme name (runtime override takes precedence)
   const currentThemeName = runtimeThemeName ?? configThemeName;
 
-  // Load available themes on mount
+  // Load available themes on mount, for the active project, and when theme directories change
   useEffect(() => {
-    listThemes()
-      .then(setAvailableThemes)
-      .catch((err) => {
-        console.error('Failed to list themes:', err);
-        setAvailableThemes([]);
-      });
-  }, []);
+    const refresh = () => {
+      listThemes(projectPath)
+        .then(setAvailableThemes)
+        .catch((err) => {
+          console.error('Failed to list themes:', err);
+          setAvailableThemes([]);
+        });
+    };
+    refresh();
+
+    const unlisten = listen('themes-changed', refresh);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [projectPath]);
 
   // Load and apply theme when name or available themes change
   useEffect(() => {
@@ -79,10 +79,10 @@ export function parseThemeJson(content: string): VSCodeTheme {
 }
 
 /**
- * List all available themes.
+ * List all available themes, including a project's own extra theme directories.
  */
-export async function listThemes(): Promise<ThemeInfo[]> {
-  return invoke<ThemeInfo[]>('list_themes');
+export async function listThemes(projectPath?: string): Promise<ThemeInfo[]> {
+  return invoke<ThemeInfo[]>('list_themes', { projectPath });
 }
 
 /**
@@ -92,6 +92,21 @@ export async function readThemeFile(path: string): Promise<string> {
   return invoke<string>('read_theme', { path });
 }
 
+/**
+ * Read a theme by display name; a user theme shadows a bundled one of the same name.
+ */
+export async function readThemeByName(name: string, projectPath?: string): Promise<string> {
+  return invoke<string>('read_theme_by_name', { name, projectPath });
+}
+
+/**
+ * A theme's colors as a flat name -> hex map (`#rrggbb` or `#rrggbbaa`), with
+ * token foregrounds under `tokenColors.<scope>` keys. For previews.
+ */
+export async function themeColors(path: string): Promise<Record<string, string>> {
+  return invoke<Record<string, string>>('theme_colors', { path });
+}
+
 /**
  * Load and parse a theme by path.
  */
@@ -6,6 +6,8 @@ export interface Project {
   order?: number;
   isActive: boolean;
   lastAccessedAt?: string;
+  /** Root of the git repository when `path` is a subfolder of it */
+  repoRoot?: string;
 }
 
 // Scratch terminal - a general-purpose terminal not tied to any project/worktree
@@ -78,15 +80,41 @@ export interface Worktree {
   name: string;
   path: string;
   branch: string;
+  /** Branch the worktree was created from */
+  baseBranch?: string;
   createdAt: string;
   order?: number;
+  command?: string;
+  args?: string[];
+  suspended?: boolean;
+  /** Read-only review: no sessions, polled status, mutating git commands refused */
+  inspect?: boolean;
+  /** Config `environments` profile applied to this worktree's terminals */
+  environment?: string;
+}
+
+export interface CreatedWorktree extends Worktree {
+  /** Set while the file copy or init command runs; pass to cancelWorktreeCreation */
+  creationOpId: string | null;
 }
 
 export interface FileChange {
   path: string;
-  status: 'added' | 'modified' | 'deleted' | 'renamed' | 'untracked';
+  status:
+    | 'added'
+    | 'modified'
+    | 'deleted'
+    | 'renamed'
+    | 'untracked'
+    | 'conflicted'
+    | 'copied'
+    | 'typechanged';
   insertions?: number;
   deletions?: number;
+  /** Set when the file mode (e.g. executable bit) changed; requires git.detectModeChanges */
+  modeChanged?: boolean;
+  /** Original path of a renamed or copied file */
+  oldPath?: string;
 }
 
 export type ChangedFilesViewMode = 'uncommitted' | 'branch';
@@ -98,6 +126,37 @@ export interface BranchInfo {
   commitsAhead: number;
 }
 
+export interface ConflictFile {
+  path: string;
+  /** Which versions git has: the common ancestor, ours and theirs */
+  base: boolean;
+  ours: boolean;
+  theirs: boolean;
+}
+
+export interface WorktreeStatus {
+  worktreeId: string;
+  path: string;
+  /** False when the worktree directory no longer exists */
+  exists: boolean;
+  files: FileChange[];
+  branchInfo: BranchInfo | null;
+  error: string | null;
+}
+
+/** Whether a worktree can be deleted without losing work */
+export interface WorktreeSafety {
+  id: string;
+  /** False when the directory was removed outside Shellflow */
+  exists: boolean;
+  hasUncommitted: boolean;
+  isMerged: boolean;
+  hasUnpushedCommits: boolean;
+  /** Has a live terminal session */
+  isRunning: boolean;
+  error: string | null;
+}
+
 export interface DiffContent {
   original: string;
   modified: string;
@@ -116,9 +175,78 @@ export interface FilesChanged {
   files: FileChange[];
 }
 
+// Structured error returned by git commands
+export type GitErrorCode =
+  | 'GIT'
+  | 'IO'
+  | 'NOT_GIT_REPO'
+  | 'NOTHING_TO_COMMIT'
+  | 'MERGE_CONFLICT'
+  | 'MERGE_ABORTED'
+  | 'BRANCH_NOT_FOUND'
+  | 'UNCOMMITTED_CHANGES'
+  | 'NO_UPSTREAM'
+  | 'AUTH_FAILED'
+  | 'BUSY'
+  | 'NO_DEFAULT_BRANCH'
+  | 'NO_MERGE_BASE'
+  | 'OTHER';
+
+export interface GitError {
+  code: GitErrorCode;
+  message: string;
+  files?: string[];
+}
+
 // Merge workflow types
 export type MergeStrategy = 'merge' | 'rebase';
 
+export interface PreflightResult {
+  willConflict: boolean;
+  /** For a rebase, the files conflicted at the first commit that stops */
+  conflictedFiles: string[];
+  fastForward: boolean;
+}
+
+export type Pane = 'main' | 'terminal';
+
+export interface FocusedSession {
+  ptyId: string;
+  /** The session was started by this call rather than already running */
+  spawned: boolean;
+}
+
+/** Sent as "export-bundle-progress" after each worktree is written */
+export interface BundleProgress {
+  current: number;
+  total: number;
+  worktree: string;
+}
+
+/** Disk space of a worktree's checkout; also sent as "worktree-disk-usage" while measuring */
+export interface WorktreeUsage {
+  worktreeId: string;
+  bytes: number;
+  fileCount: number;
+  /** Directories that couldn't be read and weren't counted */
+  skippedDirs: number;
+  /** False for partial totals reported mid-walk */
+  complete: boolean;
+}
+
+export interface SubmoduleStatus {
+  /** Path relative to the worktree */
+  path: string;
+  /** Commit checked out, or the one the superproject records if not initialized */
+  commit: string;
+  initialized: boolean;
+  /** The checked-out commit differs from the one the superproject records */
+  outOfSync: boolean;
+  conflicted: boolean;
+  /** Has uncommitted changes or untracked files */
+  dirty: boolean;
+}
+
 export interface MergeFeasibility {
   canMerge: boolean;
   hasUncommittedChanges: boolean;
@@ -131,11 +259,27 @@ export interface MergeFeasibility {
   error: string | null;
 }
 
+/** How a worktree's branch and its base have moved apart */
+export interface DivergenceSummary {
+  branch: string;
+  baseBranch: string;
+  ahead: number;
+  behind: number;
+  /** Commit the branch and base last had in common */
+  mergeBase: string;
+  /** ISO-8601 committer date of the merge base (when the branch last synced) */
+  mergeBaseDate: string;
+  /** The base has gained commits since the branch forked or last synced */
+  baseMoved: boolean;
+}
+
 export interface MergeWorkflowOptions {
   strategy: MergeStrategy;
   deleteWorktree: boolean;
   deleteLocalBranch: boolean;
   deleteRemoteBranch: boolean;
+  /** Branch to merge into; defaults to the repository's default branch */
+  targetBranch?: string | null;
 }
 
 export interface MergeWorkflowResult {
@@ -148,8 +292,22 @@ export interface MergeCompleted {
   worktreeId: string;
   success: boolean;
   branchName: string;
+  /** Branch that was merged into; null when no merge ran */
+  targetBranch: string | null;
   deletedWorktree: boolean;
   error: string | null;
+  cleanup: CleanupOutcome | null;
+}
+
+export type CleanupStep =
+  | { status: 'skipped' }
+  | { status: 'done' }
+  | { status: 'failed'; error: string };
+
+export interface CleanupOutcome {
+  worktree: CleanupStep;
+  localBranch: CleanupStep;
+  remoteBranch: CleanupStep;
 }
 
 export interface MergeProgress {
@@ -168,6 +326,7 @@ export interface WorktreeDeleteStatus {
   hasUncommittedChanges: boolean;
   unpushedCommits: number;
   branchName: string;
+  lockReason?: string | null;
 }
 
 export interface DeleteWorktreeOptions {
@@ -197,6 +356,21 @@ export interface RunningTask {
   exitCode?: number;
 }
 
+/** Backend self-metrics for the status bar */
+export interface AppMetrics {
+  ptySessions: number;
+  /** Worktrees with a file watcher */
+  watchedWorktrees: number;
+  mergeWatchers: number;
+  rebaseWatchers: number;
+  gitLogWatchers: number;
+  /** PIDs in this instance's PID file */
+  trackedPids: number;
+  /** Null where it can't be read (e.g. Windows) */
+  residentMemoryBytes: number | null;
+  uptimeSecs: number;
+}
+
 // Re-export split types
 export type {
   SplitPaneConfig,